@@ -0,0 +1,41 @@
+use crate::error::Warning;
+use crate::resolver::DependencyDescriptor;
+use serde::Serialize;
+use std::collections::HashSet;
+use swc_common::DUMMY_SP;
+use swc_ecma_ast::*;
+use swc_ecma_visit::{Node, Visit, VisitWith};
+
+/// The report of the analysis of a module, the transform passes run without the code generation.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnalysisReport {
+	/// the dependencies of the transformed module, they are tree-shaken like the emitted code.
+	pub deps: Vec<DependencyDescriptor>,
+	/// the value export names, like `parse_export_names` without the star exports, the modules of
+	/// the star exports are in the `deps`.
+	pub export_names: Vec<String>,
+	pub warnings: Vec<Warning>,
+	pub has_top_level_await: bool,
+	pub is_side_effect_free: bool,
+}
+
+/// Collect the values of the string literals of the program, the dependencies of the analysis are
+/// kept if their specifiers are used by the program like they're found in the emitted code.
+pub fn collect_string_literals(program: &Program) -> HashSet<String> {
+	let mut collector = StringLiteralsCollector {
+		values: HashSet::new(),
+	};
+	program.visit_with(&Invalid { span: DUMMY_SP } as _, &mut collector);
+	collector.values
+}
+
+struct StringLiteralsCollector {
+	values: HashSet<String>,
+}
+
+impl Visit for StringLiteralsCollector {
+	fn visit_str(&mut self, n: &Str, _: &dyn Node) {
+		self.values.insert(n.value.as_ref().into());
+	}
+}
@@ -0,0 +1,34 @@
+use std::{cell::RefCell, rc::Rc};
+use swc_ecma_ast::Module;
+use swc_ecma_visit::Fold;
+
+/// The AST dumps of the transform passes, in the order of `(pass name, dump)`.
+pub type AstDumps = Rc<RefCell<Vec<(String, String)>>>;
+
+/// Print the module AST in the pretty-printed `Debug` format.
+pub fn dump_module(module: &Module) -> String {
+	format!("{:#?}", module)
+}
+
+/// Wrap a transform pass to dump the AST after it runs, the pass runs as normal if `dumps` is `None`.
+pub fn dump_pass<F: Fold>(name: &'static str, fold: F, dumps: Option<AstDumps>) -> impl Fold {
+	DumpFold { name, fold, dumps }
+}
+
+struct DumpFold<F: Fold> {
+	name: &'static str,
+	fold: F,
+	dumps: Option<AstDumps>,
+}
+
+impl<F: Fold> Fold for DumpFold<F> {
+	fn fold_module(&mut self, module: Module) -> Module {
+		let module = self.fold.fold_module(module);
+		if let Some(dumps) = &self.dumps {
+			dumps
+				.borrow_mut()
+				.push((self.name.into(), dump_module(&module)));
+		}
+		module
+	}
+}
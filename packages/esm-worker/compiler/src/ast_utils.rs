@@ -0,0 +1,89 @@
+use swc_common::DUMMY_SP;
+use swc_ecma_ast::*;
+use swc_ecma_utils::quote_ident;
+
+/// create a variable declaration of the bindings with the optional initializers:
+/// `var a = 1, b`.
+pub fn var_decl(kind: VarDeclKind, decls: Vec<(Ident, Option<Expr>)>) -> VarDecl {
+	VarDecl {
+		span: DUMMY_SP,
+		kind,
+		declare: false,
+		decls: decls
+			.into_iter()
+			.map(|(name, init)| VarDeclarator {
+				span: DUMMY_SP,
+				name: Pat::Ident(name.into()),
+				init: init.map(Box::new),
+				definite: false,
+			})
+			.collect(),
+	}
+}
+
+// match: left = right
+pub fn assign(left: PatOrExpr, right: Expr) -> Expr {
+	Expr::Assign(AssignExpr {
+		span: DUMMY_SP,
+		op: AssignOp::Assign,
+		left,
+		right: Box::new(right),
+	})
+}
+
+// match: obj.prop
+pub fn member(obj: Expr, prop: &str) -> Expr {
+	Expr::Member(MemberExpr {
+		span: DUMMY_SP,
+		obj: ExprOrSuper::Expr(Box::new(obj)),
+		prop: Box::new(Expr::Ident(quote_ident!(prop))),
+		computed: false,
+	})
+}
+
+// match: callee(...args)
+pub fn call(callee: Expr, args: Vec<Expr>) -> Expr {
+	Expr::Call(CallExpr {
+		span: DUMMY_SP,
+		callee: ExprOrSuper::Expr(Box::new(callee)),
+		args: args
+			.into_iter()
+			.map(|expr| ExprOrSpread {
+				spread: None,
+				expr: Box::new(expr),
+			})
+			.collect(),
+		type_args: None,
+	})
+}
+
+pub fn expr_stmt(expr: Expr) -> Stmt {
+	Stmt::Expr(ExprStmt {
+		span: DUMMY_SP,
+		expr: Box::new(expr),
+	})
+}
+
+pub fn block(stmts: Vec<Stmt>) -> BlockStmt {
+	BlockStmt {
+		span: DUMMY_SP,
+		stmts,
+	}
+}
+
+// match: t, i18n.t, Object.freeze
+pub fn get_callee_name(callee: &Expr) -> Option<String> {
+	match callee {
+		Expr::Ident(id) => Some(id.sym.as_ref().into()),
+		Expr::Member(MemberExpr {
+			obj: ExprOrSuper::Expr(obj),
+			prop,
+			computed: false,
+			..
+		}) => match prop.as_ref() {
+			Expr::Ident(prop) => Some(format!("{}.{}", get_callee_name(obj)?, prop.sym)),
+			_ => None,
+		},
+		_ => None,
+	}
+}
@@ -0,0 +1,296 @@
+use serde::Deserialize;
+use swc_common::{BytePos, Span, Spanned, DUMMY_SP};
+use swc_ecma_ast::*;
+use swc_ecma_utils::quote_ident;
+use swc_ecma_visit::{noop_fold_type, Fold, FoldWith, Node, Visit, VisitWith};
+
+/// The version of the decorators proposal, the decorators of the auto-accessors are applied to
+/// the getters. The `2018-09` version doesn't support the private members of the decorated
+/// classes, including the backing fields of the auto-accessors.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq)]
+pub enum DecoratorsVersion {
+	/// the legacy decorators of TypeScript's `experimentalDecorators`.
+	#[default]
+	#[serde(rename = "legacy")]
+	Legacy,
+	/// the decorators of the 2018-09 proposal.
+	#[serde(rename = "2018-09")]
+	V2018_09,
+}
+
+/// Find the private members of the decorated classes that can't be lowered by the `2018-09`
+/// decorators, and the decorated private auto-accessors that can't be lowered by the legacy
+/// decorators. The `accessors` are the positions of the keys of the auto-accessor fields.
+pub fn find_unsupported_decorated_members(
+	module: &Module,
+	version: DecoratorsVersion,
+	accessors: &[BytePos],
+) -> Vec<(String, Span)> {
+	if accessors.is_empty() && version != DecoratorsVersion::V2018_09 {
+		return vec![];
+	}
+	let mut finder = DecoratedMemberFinder {
+		version,
+		accessors,
+		errors: vec![],
+	};
+	module.visit_with(&Invalid { span: DUMMY_SP } as _, &mut finder);
+	finder.errors
+}
+
+struct DecoratedMemberFinder<'a> {
+	version: DecoratorsVersion,
+	accessors: &'a [BytePos],
+	errors: Vec<(String, Span)>,
+}
+
+impl Visit for DecoratedMemberFinder<'_> {
+	fn visit_class(&mut self, class: &Class, _: &dyn Node) {
+		class.visit_children_with(self);
+		if self.version == DecoratorsVersion::Legacy {
+			// the legacy decorators skip the private members
+			for member in &class.body {
+				if let ClassMember::PrivateProp(prop) = member {
+					if !prop.decorators.is_empty() && self.accessors.contains(&prop.key.span.lo) {
+						let message = "the decorated private auto-accessors are not supported by \
+						               the legacy decorators";
+						self.errors.push((message.into(), member.span()));
+					}
+				}
+			}
+			return;
+		}
+		let is_decorated = !class.decorators.is_empty()
+			|| class.body.iter().any(|member| match member {
+				ClassMember::Method(method) => !method.function.decorators.is_empty(),
+				ClassMember::ClassProp(prop) => !prop.decorators.is_empty(),
+				_ => false,
+			});
+		if !is_decorated {
+			return;
+		}
+		for member in &class.body {
+			let kind = match member {
+				ClassMember::ClassProp(prop) if self.accessors.contains(&prop.key.span().lo) => {
+					"auto-accessors"
+				}
+				ClassMember::PrivateProp(prop) if self.accessors.contains(&prop.key.span.lo) => {
+					"auto-accessors"
+				}
+				ClassMember::PrivateMethod(_) | ClassMember::PrivateProp(_) => "private members",
+				_ => continue,
+			};
+			let message = format!(
+				"the {} of the decorated classes are not supported by the 2018-09 decorators",
+				kind
+			);
+			self.errors.push((message, member.span()));
+		}
+	}
+}
+
+/// Lower the auto-accessor class fields(`accessor x = 1`) into a private backing field with
+/// a getter/setter pair, the decorators of the field are moved to the getter. The private
+/// auto-accessors(`accessor #x = 1`) are lowered to the private getter/setter pairs.
+/// The `accessor` keyword is removed by the parser, the `accessors` are the positions of the
+/// keys of the auto-accessor fields.
+pub fn auto_accessor_fold(accessors: Vec<BytePos>) -> impl Fold {
+	AutoAccessorFold { accessors }
+}
+
+struct AutoAccessorFold {
+	accessors: Vec<BytePos>,
+}
+
+/// The key of the accessor methods of an auto-accessor.
+enum AccessorKey {
+	Public(PropName),
+	Private(PrivateName),
+}
+
+impl Fold for AutoAccessorFold {
+	noop_fold_type!();
+
+	fn fold_class(&mut self, class: Class) -> Class {
+		let class = class.fold_children_with(self);
+		if self.accessors.is_empty() {
+			return class;
+		}
+		let mut body: Vec<ClassMember> = Vec::with_capacity(class.body.len());
+		let mut index = 0;
+		for member in class.body {
+			match member {
+				ClassMember::ClassProp(prop)
+					if !prop.computed && self.accessors.contains(&prop.key.span().lo) =>
+				{
+					let key = match prop.key.as_ref() {
+						Expr::Ident(id) => PropName::Ident(id.clone()),
+						Expr::Lit(Lit::Str(s)) => PropName::Str(s.clone()),
+						Expr::Lit(Lit::Num(n)) => PropName::Num(*n),
+						_ => {
+							body.push(ClassMember::ClassProp(prop));
+							continue;
+						}
+					};
+					// match: accessor x = 1 => #x_accessor_storage = 1
+					let storage = match &key {
+						PropName::Ident(id) => format!("{}_accessor_storage", id.sym),
+						_ => {
+							index += 1;
+							format!("_{}_accessor_storage", index)
+						}
+					};
+					let storage = PrivateProp {
+						span: prop.span,
+						key: storage_name(storage.as_str()),
+						value: prop.value,
+						type_ann: prop.type_ann,
+						is_static: prop.is_static,
+						decorators: vec![],
+						computed: false,
+						accessibility: None,
+						is_abstract: false,
+						is_optional: false,
+						is_override: false,
+						readonly: false,
+						definite: prop.definite,
+					};
+					body.push(ClassMember::PrivateProp(storage.clone()));
+					body.extend(create_accessor_methods(
+						AccessorKey::Public(key),
+						&storage,
+						prop.accessibility,
+						prop.is_override,
+						prop.decorators,
+					));
+				}
+				ClassMember::PrivateProp(prop) if self.accessors.contains(&prop.key.span.lo) => {
+					// match: accessor #x = 1 => #x_private_accessor_storage = 1
+					let key = prop.key.clone();
+					let storage = format!("{}_private_accessor_storage", key.id.sym);
+					let (accessibility, is_override) = (prop.accessibility, prop.is_override);
+					let decorators = prop.decorators.clone();
+					let storage = PrivateProp {
+						key: storage_name(storage.as_str()),
+						decorators: vec![],
+						accessibility: None,
+						is_override: false,
+						..prop
+					};
+					body.push(ClassMember::PrivateProp(storage.clone()));
+					body.extend(create_accessor_methods(
+						AccessorKey::Private(key),
+						&storage,
+						accessibility,
+						is_override,
+						decorators,
+					));
+				}
+				_ => body.push(member),
+			}
+		}
+		Class { body, ..class }
+	}
+}
+
+fn storage_name(name: &str) -> PrivateName {
+	PrivateName {
+		span: DUMMY_SP,
+		id: quote_ident!(name),
+	}
+}
+
+/// create the getter/setter pair of the backing field, the decorators are applied to the getter.
+fn create_accessor_methods(
+	key: AccessorKey,
+	storage: &PrivateProp,
+	accessibility: Option<Accessibility>,
+	is_override: bool,
+	decorators: Vec<Decorator>,
+) -> Vec<ClassMember> {
+	let getter = create_accessor_function(
+		vec![],
+		vec![Stmt::Return(ReturnStmt {
+			span: DUMMY_SP,
+			arg: Some(Box::new(storage_expr(&storage.key))),
+		})],
+		decorators,
+	);
+	let setter = create_accessor_function(
+		vec![Param {
+			span: DUMMY_SP,
+			decorators: vec![],
+			pat: Pat::Ident(BindingIdent {
+				id: quote_ident!("value"),
+				type_ann: None,
+			}),
+		}],
+		vec![Stmt::Expr(ExprStmt {
+			span: DUMMY_SP,
+			expr: Box::new(Expr::Assign(AssignExpr {
+				span: DUMMY_SP,
+				op: AssignOp::Assign,
+				left: PatOrExpr::Expr(Box::new(storage_expr(&storage.key))),
+				right: Box::new(Expr::Ident(quote_ident!("value"))),
+			})),
+		})],
+		vec![],
+	);
+	vec![(MethodKind::Getter, getter), (MethodKind::Setter, setter)]
+		.into_iter()
+		.map(|(kind, function)| match &key {
+			AccessorKey::Public(key) => ClassMember::Method(ClassMethod {
+				span: DUMMY_SP,
+				key: key.clone(),
+				function,
+				kind,
+				is_static: storage.is_static,
+				accessibility,
+				is_abstract: false,
+				is_optional: false,
+				is_override,
+			}),
+			AccessorKey::Private(key) => ClassMember::PrivateMethod(PrivateMethod {
+				span: DUMMY_SP,
+				key: key.clone(),
+				function,
+				kind,
+				is_static: storage.is_static,
+				accessibility,
+				is_abstract: false,
+				is_optional: false,
+				is_override,
+			}),
+		})
+		.collect()
+}
+
+fn create_accessor_function(
+	params: Vec<Param>,
+	stmts: Vec<Stmt>,
+	decorators: Vec<Decorator>,
+) -> Function {
+	Function {
+		params,
+		decorators,
+		span: DUMMY_SP,
+		body: Some(BlockStmt {
+			span: DUMMY_SP,
+			stmts,
+		}),
+		is_generator: false,
+		is_async: false,
+		type_params: None,
+		return_type: None,
+	}
+}
+
+// match: this.#x_accessor_storage
+fn storage_expr(storage: &PrivateName) -> Expr {
+	Expr::Member(MemberExpr {
+		span: DUMMY_SP,
+		obj: ExprOrSuper::Expr(Box::new(Expr::This(ThisExpr { span: DUMMY_SP }))),
+		prop: Box::new(Expr::PrivateName(storage.clone())),
+		computed: false,
+	})
+}
@@ -0,0 +1,282 @@
+use crate::ast_utils::{member, var_decl};
+use swc_common::DUMMY_SP;
+use swc_ecma_ast::*;
+use swc_ecma_utils::{private_ident, quote_ident};
+use swc_ecma_visit::{noop_fold_type, Fold};
+
+/// Convert a CommonJS module to an ES module. The body runs with the `module` and `exports`
+/// objects declared, the `module.exports` is exported as the default export and the names
+/// assigned to the `exports` (`exports.foo = 1`) are exported as the named exports.
+///
+/// If the module is marked with `__esModule` by the transpilers
+/// (`Object.defineProperty(exports, "__esModule", { value: true })`), the `exports.default` is
+/// exported as the default export instead of the nested `default.default`. The re-exports
+/// (`module.exports = require("x")` and `module.exports = require("x").default`) are converted
+/// to the `export ... from "x"` declarations.
+pub fn cjs_to_esm_fold() -> impl Fold {
+	CjsToEsmFold {}
+}
+
+struct CjsToEsmFold {}
+
+impl Fold for CjsToEsmFold {
+	noop_fold_type!();
+
+	fn fold_module_items(&mut self, items: Vec<ModuleItem>) -> Vec<ModuleItem> {
+		// the module is already an ES module
+		if items.iter().any(|item| matches!(item, ModuleItem::ModuleDecl(_))) {
+			return items;
+		}
+
+		let mut is_es_module = false;
+		let mut names: Vec<String> = vec![];
+		let mut reexport: Option<(Str, bool)> = None;
+		let mut body: Vec<Stmt> = Vec::with_capacity(items.len());
+		for item in items {
+			if let ModuleItem::Stmt(stmt) = item {
+				match get_export_name(&stmt) {
+					Some(name) if name == "__esModule" => {
+						is_es_module = true;
+						continue;
+					}
+					Some(name) => {
+						if name != "default" && is_ident_name(name.as_str()) && !names.contains(&name) {
+							names.push(name);
+						}
+					}
+					None => {
+						if let Some(src) = get_reexport(&stmt) {
+							reexport = Some(src);
+							continue;
+						}
+					}
+				}
+				body.push(stmt);
+			}
+		}
+
+		let mut output: Vec<ModuleItem> = vec![];
+		if let Some((src, is_default)) = &reexport {
+			// match: export { default } from "x"
+			output.push(ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(NamedExport {
+				span: DUMMY_SP,
+				specifiers: vec![ExportSpecifier::Named(ExportNamedSpecifier {
+					span: DUMMY_SP,
+					orig: quote_ident!("default"),
+					exported: None,
+					is_type_only: false,
+				})],
+				src: Some(src.clone()),
+				type_only: false,
+				asserts: None,
+			})));
+			// match: export * from "x"
+			if !is_default {
+				output.push(ModuleItem::ModuleDecl(ModuleDecl::ExportAll(ExportAll {
+					span: DUMMY_SP,
+					src: src.clone(),
+					asserts: None,
+				})));
+			}
+		}
+		if body.is_empty() && names.is_empty() {
+			return output;
+		}
+
+		// match: var module = { exports: {} }, exports = module.exports
+		output.push(ModuleItem::Stmt(Stmt::Decl(Decl::Var(var_decl(
+			VarDeclKind::Var,
+			vec![
+				(
+					quote_ident!("module"),
+					Some(Expr::Object(ObjectLit {
+						span: DUMMY_SP,
+						props: vec![PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+							key: PropName::Ident(quote_ident!("exports")),
+							value: Box::new(Expr::Object(ObjectLit {
+								span: DUMMY_SP,
+								props: vec![],
+							})),
+						})))],
+					})),
+				),
+				(quote_ident!("exports"), Some(module_exports())),
+			],
+		)))));
+		output.extend(body.into_iter().map(ModuleItem::Stmt));
+		if reexport.is_none() {
+			// match: export default module.exports.default
+			output.push(ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultExpr(
+				ExportDefaultExpr {
+					span: DUMMY_SP,
+					expr: Box::new(if is_es_module {
+						member(module_exports(), "default")
+					} else {
+						module_exports()
+					}),
+				},
+			)));
+		}
+		// match: var _foo = module.exports.foo; export { _foo as foo }
+		for name in names {
+			let id = private_ident!(format!("_{}", name));
+			output.push(ModuleItem::Stmt(Stmt::Decl(Decl::Var(var_decl(
+				VarDeclKind::Var,
+				vec![(id.clone(), Some(member(module_exports(), name.as_str())))],
+			)))));
+			output.push(ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(NamedExport {
+				span: DUMMY_SP,
+				specifiers: vec![ExportSpecifier::Named(ExportNamedSpecifier {
+					span: DUMMY_SP,
+					orig: id,
+					exported: Some(quote_ident!(name.as_str())),
+					is_type_only: false,
+				})],
+				src: None,
+				type_only: false,
+				asserts: None,
+			})));
+		}
+		output
+	}
+}
+
+/// get the export name of the statement:
+/// - `exports.foo = 1`
+/// - `module.exports.foo = 1`
+/// - `Object.defineProperty(exports, "foo", { ... })`
+fn get_export_name(stmt: &Stmt) -> Option<String> {
+	let expr = match stmt {
+		Stmt::Expr(ExprStmt { expr, .. }) => expr.as_ref(),
+		_ => return None,
+	};
+	match expr {
+		Expr::Assign(AssignExpr {
+			op: AssignOp::Assign,
+			left: PatOrExpr::Expr(left),
+			..
+		}) => get_exports_member(left),
+		Expr::Assign(AssignExpr {
+			op: AssignOp::Assign,
+			left: PatOrExpr::Pat(left),
+			..
+		}) => match left.as_ref() {
+			Pat::Expr(left) => get_exports_member(left),
+			_ => None,
+		},
+		Expr::Call(CallExpr {
+			callee: ExprOrSuper::Expr(callee),
+			args,
+			..
+		}) if is_member(callee, "Object", "defineProperty") && args.len() == 3 => {
+			match (args[0].expr.as_ref(), args[1].expr.as_ref()) {
+				(obj, Expr::Lit(Lit::Str(Str { value, .. }))) if is_exports(obj) => Some(value.as_ref().into()),
+				_ => None,
+			}
+		}
+		_ => None,
+	}
+}
+
+// match: exports.foo, module.exports.foo
+fn get_exports_member(expr: &Expr) -> Option<String> {
+	match expr {
+		Expr::Member(MemberExpr {
+			obj: ExprOrSuper::Expr(obj),
+			prop,
+			computed,
+			..
+		}) if is_exports(obj) => match (prop.as_ref(), computed) {
+			(Expr::Ident(prop), false) => Some(prop.sym.as_ref().into()),
+			(Expr::Lit(Lit::Str(Str { value, .. })), true) => Some(value.as_ref().into()),
+			_ => None,
+		},
+		_ => None,
+	}
+}
+
+/// get the source of the re-export, `module.exports = require("x")` or
+/// `module.exports = require("x").default`, the flag is set for the latter.
+fn get_reexport(stmt: &Stmt) -> Option<(Str, bool)> {
+	let right = match stmt {
+		Stmt::Expr(ExprStmt { expr, .. }) => match expr.as_ref() {
+			Expr::Assign(AssignExpr {
+				op: AssignOp::Assign,
+				left: PatOrExpr::Expr(left),
+				right,
+				..
+			}) if is_member(left, "module", "exports") => right.as_ref(),
+			Expr::Assign(AssignExpr {
+				op: AssignOp::Assign,
+				left: PatOrExpr::Pat(left),
+				right,
+				..
+			}) if matches!(left.as_ref(), Pat::Expr(left) if is_member(left, "module", "exports")) => {
+				right.as_ref()
+			}
+			_ => return None,
+		},
+		_ => return None,
+	};
+	match right {
+		Expr::Member(MemberExpr {
+			obj: ExprOrSuper::Expr(obj),
+			prop,
+			computed: false,
+			..
+		}) if matches!(prop.as_ref(), Expr::Ident(id) if id.sym.as_ref() == "default") => {
+			get_require_src(obj).map(|src| (src, true))
+		}
+		right => get_require_src(right).map(|src| (src, false)),
+	}
+}
+
+// match: require("x")
+fn get_require_src(expr: &Expr) -> Option<Str> {
+	match expr {
+		Expr::Call(CallExpr {
+			callee: ExprOrSuper::Expr(callee),
+			args,
+			..
+		}) if matches!(callee.as_ref(), Expr::Ident(id) if id.sym.as_ref() == "require")
+			&& args.len() == 1 =>
+		{
+			match args[0].expr.as_ref() {
+				Expr::Lit(Lit::Str(src)) => Some(src.clone()),
+				_ => None,
+			}
+		}
+		_ => None,
+	}
+}
+
+// match: exports, module.exports
+fn is_exports(expr: &Expr) -> bool {
+	matches!(expr, Expr::Ident(id) if id.sym.as_ref() == "exports") || is_member(expr, "module", "exports")
+}
+
+fn is_member(expr: &Expr, obj: &str, prop: &str) -> bool {
+	match expr {
+		Expr::Member(MemberExpr {
+			obj: ExprOrSuper::Expr(o),
+			prop: p,
+			computed: false,
+			..
+		}) => {
+			matches!(o.as_ref(), Expr::Ident(id) if id.sym.as_ref() == obj)
+				&& matches!(p.as_ref(), Expr::Ident(id) if id.sym.as_ref() == prop)
+		}
+		_ => false,
+	}
+}
+
+fn is_ident_name(name: &str) -> bool {
+	!name.is_empty()
+		&& !name.starts_with(|c: char| c.is_ascii_digit())
+		&& name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+fn module_exports() -> Expr {
+	member(Expr::Ident(quote_ident!("module")), "exports")
+}
+
@@ -0,0 +1,53 @@
+use std::{fmt, rc::Rc};
+use swc_common::comments::{Comment, SingleThreadedComments, SingleThreadedCommentsMapInner};
+
+/// The predicate deciding whether a comment is kept in the emitted code by its text, the text
+/// is without the `//` and `/* */` delimiters:
+/// `CommentFilter::new(|text| text.contains("@ts-expect-error"))`.
+#[derive(Clone)]
+pub struct CommentFilter(Rc<dyn Fn(&str) -> bool>);
+
+impl CommentFilter {
+	pub fn new(predicate: impl Fn(&str) -> bool + 'static) -> Self {
+		CommentFilter(Rc::new(predicate))
+	}
+
+	fn keep(&self, comment: &Comment) -> bool {
+		(self.0)(comment.text.as_ref())
+	}
+}
+
+impl fmt::Debug for CommentFilter {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str("CommentFilter")
+	}
+}
+
+/// Copy the comments kept by the filter, the comments of the module are unchanged so it can be
+/// emitted again with the other options.
+pub fn filter_comments(
+	comments: &SingleThreadedComments,
+	filter: &CommentFilter,
+) -> SingleThreadedComments {
+	let (leading, trailing) = comments.borrow_all();
+	SingleThreadedComments::from_leading_and_trailing(
+		Rc::new(filter_map(&leading, filter).into()),
+		Rc::new(filter_map(&trailing, filter).into()),
+	)
+}
+
+fn filter_map(
+	map: &SingleThreadedCommentsMapInner,
+	filter: &CommentFilter,
+) -> SingleThreadedCommentsMapInner {
+	map.iter()
+		.filter_map(|(pos, comments)| {
+			let kept: Vec<Comment> = comments.iter().filter(|c| filter.keep(c)).cloned().collect();
+			if kept.is_empty() {
+				None
+			} else {
+				Some((*pos, kept))
+			}
+		})
+		.collect()
+}
@@ -0,0 +1,126 @@
+use swc_common::Span;
+use swc_ecma_ast::*;
+use swc_ecma_visit::{noop_fold_type, Fold, FoldWith};
+
+/// Fold the operations of the literals, the other expressions are kept:
+/// - the arithmetic of the numbers: `1 + 2 * 3` => `7`
+/// - the concatenation of the strings and the integers: `"react@" + 17` => `"react@17"`
+/// - the comparisons of the literals of the same type: `"x" === "x"` => `true`
+/// - the boolean logic of the booleans: `!true || false` => `false`
+///
+/// The operations giving `NaN`, an infinity or a negative number are kept.
+pub fn constant_fold() -> impl Fold {
+	ConstantFold {}
+}
+
+struct ConstantFold {}
+
+impl Fold for ConstantFold {
+	noop_fold_type!();
+
+	fn fold_expr(&mut self, expr: Expr) -> Expr {
+		let expr = expr.fold_children_with(self);
+		match &expr {
+			// match: (1 + 2)
+			Expr::Paren(ParenExpr { expr: inner, .. }) if matches!(inner.as_ref(), Expr::Lit(_)) => {
+				*inner.clone()
+			}
+			Expr::Bin(BinExpr {
+				span,
+				op,
+				left,
+				right,
+			}) => match (left.as_ref(), right.as_ref()) {
+				(Expr::Lit(left), Expr::Lit(right)) => {
+					fold_bin(*span, *op, left, right).unwrap_or(expr)
+				}
+				_ => expr,
+			},
+			// match: !true
+			Expr::Unary(UnaryExpr {
+				span,
+				op: UnaryOp::Bang,
+				arg,
+			}) => match arg.as_ref() {
+				Expr::Lit(Lit::Bool(Bool { value, .. })) => bool_lit(*span, !value),
+				_ => expr,
+			},
+			_ => expr,
+		}
+	}
+}
+
+fn fold_bin(span: Span, op: BinaryOp, left: &Lit, right: &Lit) -> Option<Expr> {
+	match (left, right) {
+		(Lit::Num(Number { value: a, .. }), Lit::Num(Number { value: b, .. })) => {
+			let (a, b) = (*a, *b);
+			let value = match op {
+				BinaryOp::Add => a + b,
+				BinaryOp::Sub => a - b,
+				BinaryOp::Mul => a * b,
+				BinaryOp::Div => a / b,
+				BinaryOp::Mod => a % b,
+				BinaryOp::Lt => return Some(bool_lit(span, a < b)),
+				BinaryOp::LtEq => return Some(bool_lit(span, a <= b)),
+				BinaryOp::Gt => return Some(bool_lit(span, a > b)),
+				BinaryOp::GtEq => return Some(bool_lit(span, a >= b)),
+				BinaryOp::EqEqEq | BinaryOp::EqEq => return Some(bool_lit(span, a == b)),
+				BinaryOp::NotEqEq | BinaryOp::NotEq => return Some(bool_lit(span, a != b)),
+				_ => return None,
+			};
+			// the negative numbers are the unary expressions
+			if value.is_finite() && value >= 0.0 && !(value == 0.0 && value.is_sign_negative()) {
+				Some(num_lit(span, value))
+			} else {
+				None
+			}
+		}
+		(Lit::Str(a), Lit::Str(b)) => match op {
+			BinaryOp::Add => Some(str_lit(span, format!("{}{}", a.value, b.value))),
+			BinaryOp::EqEqEq | BinaryOp::EqEq => Some(bool_lit(span, a.value == b.value)),
+			BinaryOp::NotEqEq | BinaryOp::NotEq => Some(bool_lit(span, a.value != b.value)),
+			_ => None,
+		},
+		(Lit::Str(a), Lit::Num(b)) if op == BinaryOp::Add => {
+			Some(str_lit(span, format!("{}{}", a.value, integer_str(b.value)?)))
+		}
+		(Lit::Num(a), Lit::Str(b)) if op == BinaryOp::Add => {
+			Some(str_lit(span, format!("{}{}", integer_str(a.value)?, b.value)))
+		}
+		(Lit::Bool(Bool { value: a, .. }), Lit::Bool(Bool { value: b, .. })) => match op {
+			BinaryOp::LogicalAnd => Some(bool_lit(span, *a && *b)),
+			BinaryOp::LogicalOr => Some(bool_lit(span, *a || *b)),
+			BinaryOp::EqEqEq | BinaryOp::EqEq => Some(bool_lit(span, a == b)),
+			BinaryOp::NotEqEq | BinaryOp::NotEq => Some(bool_lit(span, a != b)),
+			_ => None,
+		},
+		_ => None,
+	}
+}
+
+/// the string of the number like `String(17)`, only for the safe integers since the other
+/// numbers are formatted differently in JS.
+fn integer_str(value: f64) -> Option<String> {
+	if value.fract() == 0.0 && value.abs() < 9007199254740992.0 {
+		Some(format!("{}", value as i64))
+	} else {
+		None
+	}
+}
+
+fn num_lit(span: Span, value: f64) -> Expr {
+	Expr::Lit(Lit::Num(Number { span, value }))
+}
+
+fn bool_lit(span: Span, value: bool) -> Expr {
+	Expr::Lit(Lit::Bool(Bool { span, value }))
+}
+
+fn str_lit(span: Span, value: String) -> Expr {
+	Expr::Lit(Lit::Str(Str {
+		span,
+		value: value.into(),
+		has_escape: false,
+		kind: StrKind::Synthesized,
+	}))
+}
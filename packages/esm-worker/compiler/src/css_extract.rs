@@ -0,0 +1,108 @@
+use crate::resolver::{ExtractedCss, Resolver};
+use std::{cell::RefCell, rc::Rc};
+use swc_common::DUMMY_SP;
+use swc_ecma_ast::*;
+use swc_ecma_visit::{noop_fold_type, Fold, FoldWith};
+
+/// Extract the CSS of the static tagged templates of the CSS-in-JS libraries, like
+/// ``css`color: red` `` and ``styled.div`color: red` ``, into the `extracted_css` of the resolver.
+/// The templates with interpolations are kept since the CSS is only known at runtime.
+///
+/// If `replace` is set, the ``css`...` `` templates are replaced with the class names of the
+/// extracted CSS, the `styled` templates are kept since they create the components.
+pub fn extract_css_fold(
+	resolver: Rc<RefCell<Resolver>>,
+	tags: Vec<String>,
+	replace: bool,
+) -> impl Fold {
+	ExtractCssFold {
+		resolver,
+		tags,
+		replace,
+	}
+}
+
+struct ExtractCssFold {
+	resolver: Rc<RefCell<Resolver>>,
+	tags: Vec<String>,
+	replace: bool,
+}
+
+impl ExtractCssFold {
+	/// get the tag name of the template, and if the template can be replaced with the class name.
+	fn get_tag(&self, tag: &Expr) -> Option<(String, bool)> {
+		match tag {
+			// match: css`...`
+			Expr::Ident(id) if self.is_tag(id) => Some((id.sym.as_ref().into(), true)),
+			// match: styled.div`...`
+			Expr::Member(MemberExpr {
+				obj: ExprOrSuper::Expr(obj),
+				prop,
+				computed: false,
+				..
+			}) => match (obj.as_ref(), prop.as_ref()) {
+				(Expr::Ident(obj), Expr::Ident(prop)) if self.is_tag(obj) => {
+					Some((format!("{}.{}", obj.sym, prop.sym), false))
+				}
+				_ => None,
+			},
+			// match: styled(Button)`...`
+			Expr::Call(CallExpr {
+				callee: ExprOrSuper::Expr(callee),
+				..
+			}) => match callee.as_ref() {
+				Expr::Ident(id) if self.is_tag(id) => Some((format!("{}()", id.sym), false)),
+				_ => None,
+			},
+			_ => None,
+		}
+	}
+
+	fn is_tag(&self, id: &Ident) -> bool {
+		self.tags.iter().any(|tag| tag.as_str() == id.sym.as_ref())
+	}
+}
+
+impl Fold for ExtractCssFold {
+	noop_fold_type!();
+
+	fn fold_expr(&mut self, expr: Expr) -> Expr {
+		let expr = expr.fold_children_with(self);
+		if let Expr::TaggedTpl(TaggedTpl { tag, tpl, .. }) = &expr {
+			if let (Some((tag, replaceable)), true) = (self.get_tag(tag), tpl.exprs.is_empty()) {
+				let css: String = tpl.quasis.iter().map(|quasi| quasi.raw.value.as_ref()).collect();
+				let css = css.trim().to_owned();
+				let mut resolver = self.resolver.borrow_mut();
+				let hash = fnv_hash(&[resolver.specifier.as_str(), css.as_str()]);
+				let class_name = format!("css-{:08x}", hash);
+				let replace = self.replace && replaceable;
+				resolver.extracted_css.push(ExtractedCss {
+					tag,
+					css,
+					class_name: if replace { Some(class_name.clone()) } else { None },
+				});
+				if replace {
+					return Expr::Lit(Lit::Str(Str {
+						span: DUMMY_SP,
+						value: class_name.into(),
+						has_escape: false,
+						kind: Default::default(),
+					}));
+				}
+			}
+		}
+		expr
+	}
+}
+
+/// the 32-bit FNV-1a hash of the parts, for the stable class names.
+fn fnv_hash(parts: &[&str]) -> u32 {
+	let mut hash: u32 = 0x811c9dc5;
+	for part in parts {
+		for byte in part.bytes().chain(std::iter::once(0)) {
+			hash ^= byte as u32;
+			hash = hash.wrapping_mul(0x01000193);
+		}
+	}
+	hash
+}
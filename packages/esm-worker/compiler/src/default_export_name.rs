@@ -0,0 +1,88 @@
+use std::path::Path;
+use swc_common::DUMMY_SP;
+use swc_ecma_ast::*;
+use swc_ecma_utils::private_ident;
+use swc_ecma_visit::{noop_fold_type, Fold};
+
+/// Give the anonymous default export(`export default function() {}`, `export default () => {}`)
+/// a name derived from the module specifier, like `App_default` for `/components/App.tsx`.
+/// The name is only for debugging, the behavior is not changed. The `hygiene` pass renames the
+/// synthesized name if it conflicts with an existing binding.
+pub fn default_export_name_fold(specifier: &str) -> impl Fold {
+	DefaultExportNameFold {
+		name: get_default_export_name(specifier),
+	}
+}
+
+struct DefaultExportNameFold {
+	name: String,
+}
+
+impl Fold for DefaultExportNameFold {
+	noop_fold_type!();
+
+	fn fold_module_items(&mut self, items: Vec<ModuleItem>) -> Vec<ModuleItem> {
+		let mut output: Vec<ModuleItem> = Vec::with_capacity(items.len());
+		for item in items {
+			match item {
+				// match: export default function() {}, export default class {}
+				ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultDecl(mut decl)) => {
+					match &mut decl.decl {
+						DefaultDecl::Fn(FnExpr { ident, .. }) | DefaultDecl::Class(ClassExpr { ident, .. })
+							if ident.is_none() =>
+						{
+							*ident = Some(private_ident!(self.name.as_str()));
+						}
+						_ => {}
+					}
+					output.push(ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultDecl(decl)));
+				}
+				// match: export default () => {}, export default async () => {}
+				ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultExpr(ExportDefaultExpr { span, expr }))
+					if matches!(expr.as_ref(), Expr::Arrow(_)) =>
+				{
+					let id = private_ident!(self.name.as_str());
+					output.push(ModuleItem::Stmt(Stmt::Decl(Decl::Var(VarDecl {
+						span: DUMMY_SP,
+						kind: VarDeclKind::Const,
+						declare: false,
+						decls: vec![VarDeclarator {
+							span: DUMMY_SP,
+							name: Pat::Ident(BindingIdent {
+								id: id.clone(),
+								type_ann: None,
+							}),
+							init: Some(expr),
+							definite: false,
+						}],
+					}))));
+					output.push(ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultExpr(
+						ExportDefaultExpr {
+							span,
+							expr: Box::new(Expr::Ident(id)),
+						},
+					)));
+				}
+				_ => output.push(item),
+			}
+		}
+		output
+	}
+}
+
+/// get the name for the default export by the specifier, like `App_default` for `/App.tsx`.
+fn get_default_export_name(specifier: &str) -> String {
+	let path = specifier.split(|c| c == '?' || c == '#').next().unwrap_or_default();
+	let stem = Path::new(path)
+		.file_stem()
+		.map(|stem| stem.to_string_lossy().to_string())
+		.unwrap_or_default();
+	let mut name: String = stem
+		.chars()
+		.map(|c| if c.is_ascii_alphanumeric() || c == '_' || c == '$' { c } else { '_' })
+		.collect();
+	if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+		name.insert(0, '_');
+	}
+	name + "_default"
+}
@@ -0,0 +1,95 @@
+use regex::Regex;
+use serde::Serialize;
+use swc_common::comments::{Comment, CommentKind, SingleThreadedComments};
+use swc_common::SourceMap;
+
+/// The directives in the comments of a module.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Directives {
+	/// the triple-slash references: `/// <reference types="node" />`
+	pub references: Vec<Reference>,
+	/// the TypeScript comment directives: `// @ts-ignore`
+	pub ts_directives: Vec<TsDirective>,
+	/// the JSX fragment factory of the pragma: `/* @jsxFrag Fragment */`, the `@jsxFragment` and
+	/// `@jsxFragmentFactory` aliases are recognized too, the first one wins.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub jsx_fragment_factory: Option<String>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Reference {
+	pub kind: ReferenceKind,
+	pub value: String,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ReferenceKind {
+	/// `/// <reference path="./globals.d.ts" />`
+	Path,
+	/// `/// <reference types="node" />`
+	Types,
+	/// `/// <reference lib="dom" />`
+	Lib,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TsDirective {
+	/// the directive name without the `@`, like `ts-expect-error`.
+	pub name: String,
+	/// the text after the name, like the reason of `// @ts-expect-error: the reason`.
+	pub text: String,
+	/// the 1-based line number of the comment.
+	pub line: usize,
+}
+
+/// Extract the directives from the comments, in the source order.
+pub fn get_directives(comments: &SingleThreadedComments, source_map: &SourceMap) -> Directives {
+	let re_reference =
+		Regex::new(r#"^/\s*<reference\s+(path|types|lib)\s*=\s*["']([^"']*)["'].*/>"#).unwrap();
+	let re_ts_directive = Regex::new(r"^[/*\s]*@(ts-[a-z\-]+)\b[\s:]*(.*?)[\s*]*$").unwrap();
+	let re_jsx_frag = Regex::new(r"@(?:jsxFragmentFactory|jsxFragment|jsxFrag)\s+([^\s*]+)").unwrap();
+
+	let (leading, trailing) = comments.borrow_all();
+	let mut all: Vec<&Comment> = leading
+		.values()
+		.chain(trailing.values())
+		.flatten()
+		.collect();
+	all.sort_by_key(|comment| comment.span.lo);
+
+	let mut directives = Directives::default();
+	for comment in all {
+		let text = comment.text.trim_end();
+		if comment.kind == CommentKind::Line {
+			if let Some(caps) = re_reference.captures(text) {
+				let kind = match &caps[1] {
+					"path" => ReferenceKind::Path,
+					"types" => ReferenceKind::Types,
+					_ => ReferenceKind::Lib,
+				};
+				directives.references.push(Reference {
+					kind,
+					value: caps[2].into(),
+				});
+				continue;
+			}
+		}
+		if directives.jsx_fragment_factory.is_none() {
+			if let Some(caps) = re_jsx_frag.captures(text) {
+				directives.jsx_fragment_factory = Some(caps[1].into());
+			}
+		}
+		if let Some(caps) = re_ts_directive.captures(text) {
+			directives.ts_directives.push(TsDirective {
+				name: caps[1].into(),
+				text: caps[2].into(),
+				line: source_map.lookup_char_pos(comment.span.lo).line,
+			});
+		}
+	}
+	directives
+}
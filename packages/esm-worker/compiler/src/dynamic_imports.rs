@@ -0,0 +1,69 @@
+use crate::resolve_fold::is_call_expr_by_name;
+use serde::Serialize;
+use swc_common::{SourceMap, Spanned, DUMMY_SP};
+use swc_ecma_ast::*;
+use swc_ecma_visit::{Node, Visit, VisitWith};
+
+/// A dynamic `import()` call site of a module.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DynamicImportSite {
+	pub specifier: DynamicSpecifier,
+	/// the 1-based line number of the `import()` call.
+	pub line: usize,
+	/// the 0-based column of the `import()` call.
+	pub col: usize,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(tag = "kind", content = "value", rename_all = "camelCase")]
+pub enum DynamicSpecifier {
+	/// `import("./page.ts")` and `import(`./page.ts`)`
+	Literal(String),
+	/// `import("./pages/" + name)`, the value is the source code of the argument.
+	Computed(String),
+}
+
+/// Collect the dynamic `import()` call sites of the module in the source order, the specifiers
+/// aren't resolved.
+pub fn get_dynamic_imports(module: &Module, source_map: &SourceMap) -> Vec<DynamicImportSite> {
+	let mut collector = DynamicImportsCollector {
+		source_map,
+		sites: vec![],
+	};
+	module.visit_with(&Invalid { span: DUMMY_SP } as _, &mut collector);
+	collector.sites
+}
+
+struct DynamicImportsCollector<'a> {
+	source_map: &'a SourceMap,
+	sites: Vec<DynamicImportSite>,
+}
+
+impl Visit for DynamicImportsCollector<'_> {
+	fn visit_call_expr(&mut self, n: &CallExpr, _: &dyn Node) {
+		if is_call_expr_by_name(n, "import") {
+			if let Some(ExprOrSpread { spread: None, expr }) = n.args.first() {
+				let specifier = match expr.as_ref() {
+					Expr::Lit(Lit::Str(Str { value, .. })) => {
+						DynamicSpecifier::Literal(value.as_ref().into())
+					}
+					Expr::Tpl(Tpl { exprs, quasis, .. }) if exprs.is_empty() => {
+						let value = quasis.iter().map(|quasi| quasi.raw.value.as_ref()).collect();
+						DynamicSpecifier::Literal(value)
+					}
+					expr => DynamicSpecifier::Computed(
+						self.source_map.span_to_snippet(expr.span()).unwrap_or_default(),
+					),
+				};
+				let loc = self.source_map.lookup_char_pos(n.span.lo);
+				self.sites.push(DynamicImportSite {
+					specifier,
+					line: loc.line,
+					col: loc.col_display,
+				});
+			}
+		}
+		n.visit_children_with(self);
+	}
+}
@@ -0,0 +1,248 @@
+use crate::auto_accessor::DecoratorsVersion;
+use crate::comment_filter::CommentFilter;
+use crate::forbidden::ForbiddenFeature;
+use crate::import_attributes::ImportAttributes;
+use crate::inject::InjectImport;
+use crate::output_format::OutputFormat;
+use crate::require_in_esm::RequireMode;
+use crate::strip_hook::StripHook;
+use crate::target::GeneratorRuntime;
+use crate::text_writer::{Indent, Newline};
+
+use serde::Deserialize;
+use std::collections::HashMap;
+use swc_ecmascript::parser::JscTarget;
+
+/// Options for transpiling a module, they are deserialized from the options of the worker in
+/// camelCase, the missing options are the defaults.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct EmitOptions {
+	/// the JSX pragmas, they are read from the `swcOptions` of the worker.
+	#[serde(skip)]
+	pub jsx_factory: String,
+	#[serde(skip)]
+	pub jsx_fragment_factory: String,
+	pub source_map: bool,
+	pub is_dev: bool,
+	/// the ECMAScript version of the emitted code, default is `es2022`. The syntax newer than the
+	/// target is lowered, `transform_targets` lowers it for each of its own targets instead.
+	pub target: JscTarget,
+	/// append the `target` to the remote specifiers as `?target=es2017`, the outputs of the
+	/// `transform_targets` append their own targets.
+	pub target_query: bool,
+	/// the runtime of the generators lowered from the async functions, default is the native
+	/// generators.
+	pub generator_runtime: GeneratorRuntime,
+	/// re-parse the emitted code to make sure the transform didn't produce broken syntax.
+	pub verify_output: bool,
+	/// the indentation of the emitted code, default is 4 spaces.
+	pub indent: Indent,
+	/// the line ending of the emitted code, default is `\n`.
+	pub newline: Newline,
+	/// wrap the lines longer than the width after the commas, ignored if the `minify` is set.
+	pub line_width: Option<usize>,
+	/// minify the whitespaces of the emitted code, the names aren't mangled so the protocol methods
+	/// like `[Symbol.iterator]()` are kept as written.
+	pub minify: bool,
+	/// the module format of the emitted code.
+	pub output_format: OutputFormat,
+	/// the variable name of the exports for the IIFE output.
+	pub global_name: Option<String>,
+	/// emit the `"use strict"` directive, default is to omit for ESM and to include for non-ESM.
+	pub emit_use_strict: Option<bool>,
+	/// the `import.meta.url` for the IIFE or the custom-register output, default is
+	/// `location.href`.
+	pub import_meta_url: Option<String>,
+	/// define the `let`/`var` exports as getters for the non-ESM output, like the ESM live bindings.
+	pub live_bindings: bool,
+	/// mark the exports of the non-ESM output with `__esModule` for the default-interop helpers
+	/// of the CommonJS consumers, default is `true`.
+	pub esmodule_interop: bool,
+	/// freeze the exports object of the non-ESM output after it's populated, the `let`/`var`
+	/// exports are defined as getters.
+	pub freeze_exports: bool,
+	/// dump the AST after each transform pass, for debugging.
+	pub dump_ast: bool,
+	/// convert the `React.createElement` calls back to JSX elements.
+	pub restore_jsx: bool,
+	/// fold the constant expressions on literals, like `1 + 2` => `3` and `"a" + "b"` => `"ab"`.
+	pub constant_fold: bool,
+	/// remove the unused code, like the unused declarations and the expressions without side effects.
+	pub dce: bool,
+	/// remove the top-level declarations of the ES module that aren't referenced by the exports or
+	/// by the retained code, the declarations that may have side effects are kept.
+	pub tree_shake_locals: bool,
+	/// the functions without side effects for the `dce` option, the unused calls of them are
+	/// removed, like `Object.freeze` and `Symbol`.
+	pub pure_functions: Vec<String>,
+	/// rename the top-level bindings with the prefix, like `const x` => `const m1_x`, for the
+	/// modules sharing a scope. The exports keep the original names.
+	pub scope_prefix: Option<String>,
+	/// collapse the imported bindings that are only re-exported into the direct re-exports, like
+	/// `import { x } from "./a.ts"; export { x }` => `export { x } from "./a.ts"`.
+	pub optimize_reexports: bool,
+	/// inline the literal constants imported from the dependencies, the sources are fetched by the
+	/// `source_fetcher` of the resolver.
+	pub inline_constants: bool,
+	/// expand the `export * from "./a.ts"` to the named re-exports, the sources are fetched by the
+	/// `source_fetcher` of the resolver.
+	pub expand_star_exports: bool,
+	/// expand the `import.meta.glob("./pages/*.ts")` calls to the objects of the importers, the
+	/// files are listed by the `file_lister` of the resolver.
+	pub glob_imports: bool,
+	/// convert the CommonJS module to an ES module, the module is kept if it has any import or export.
+	pub cjs_to_esm: bool,
+	/// how the stray `require("x")` calls of an ES module are handled, the `DynamicImport` mode
+	/// imports the modules.
+	pub require_in_esm: RequireMode,
+	/// the version of the decorators proposal, default is the legacy decorators.
+	pub decorators_version: DecoratorsVersion,
+	/// fail the transform if any dependency specifier can't be resolved, like the bare specifiers
+	/// kept by the `UnresolvedPolicy::LeaveAsIs` and the relative specifiers escaping the root.
+	pub require_all_resolved: bool,
+	/// reference the helpers from `__ALEPH__.helpers` instead of injecting them, for the bundles.
+	/// the used helpers are recorded in the resolver, see `build_shared_helpers`.
+	pub shared_helpers: bool,
+	/// extract the CSS of the static tagged templates like ``css`color: red` `` into the
+	/// `extracted_css` of the resolver.
+	pub extract_css: bool,
+	/// the tags of the templates for the `extract_css` option, default is `css` and `styled`.
+	pub css_tags: Vec<String>,
+	/// replace the extracted ``css`...` `` templates with the class names.
+	pub replace_extracted_css: bool,
+	/// extract the string literals passed to the translation functions into the
+	/// `extracted_strings` of the resolver, for the i18n tools.
+	pub extract_strings: bool,
+	/// the translation functions for the `extract_strings` option, default is `t` and `i18n.t`.
+	pub translation_functions: Vec<String>,
+	/// record the names and the kinds of the exports after the transform into the `export_shape`
+	/// of the resolver, the stripped type exports are not included.
+	pub export_shape: bool,
+	/// give the anonymous default export a name derived from the specifier, for debugging.
+	pub name_default_export: bool,
+	/// strip all the runtime code but keep the export shape: `export const foo = undefined`.
+	pub types_only: bool,
+	/// the forbidden syntax features, like `eval`, the transform fails if any of them is used.
+	pub forbidden: Vec<ForbiddenFeature>,
+	/// warn the uses of `eval` and the `Function` constructor, they defeat the bundling and the CSP.
+	pub warn_on_dynamic_code: bool,
+	/// wrap the top-level execution in a `try/catch` reporting the errors, only in development.
+	pub error_overlay: bool,
+	/// the global function to report the errors of the `error_overlay`, default is
+	/// `import.meta.hot.reportError`.
+	pub error_handler: Option<String>,
+	/// report the unhandled rejections of the async functions too, for the `error_overlay`.
+	pub catch_rejections: bool,
+	/// re-emit the `#!shebang` line of the source at the top of the output, for executable scripts.
+	/// the line is kept verbatim with the interpreter args, like `#!/usr/bin/env -S node --flag`.
+	pub emit_shebang: bool,
+	/// how the import attributes(`with { type: "css" }`) are emitted, default is to drop them.
+	pub import_attributes: ImportAttributes,
+	/// prepend the export names as a comment, like `/* esm.sh exports: name,version,default */`,
+	/// for the runtimes reading the exports without parsing. The comment is added after the code
+	/// generation, so it's kept if the other comments are stripped. The star exports are not
+	/// listed.
+	pub exports_comment: bool,
+	/// split the emitted code into the top-level import statements and the rest, for the worker to
+	/// emit the preload headers of the imports before the body. The split is stored in the
+	/// `split_imports` of the resolver, the `code` is kept.
+	pub split_imports: bool,
+	/// the imports injected at the top of the module, like a JSX runtime or a polyfill.
+	pub inject: Vec<InjectImport>,
+	/// import the free globals from the modules providing them, like `Buffer` from `./buffer.ts`,
+	/// the global names map to the specifiers and the bindings. Only the referenced globals are
+	/// imported.
+	pub provide: HashMap<String, (String, String)>,
+	/// import the polyfills of the called methods, the method names map to the specifiers, like
+	/// `at` to `core-js/modules/es.array.at`. Only the polyfills of the called methods are imported.
+	pub polyfill_methods: HashMap<String, String>,
+	/// the source map of the input code if it was pre-processed, the generated source map is
+	/// composed with it to point at the original source.
+	pub input_source_map: Option<String>,
+	/// the `file` field of the source map, the name of the generated file when the served name
+	/// differs from the specifier.
+	pub source_map_file: Option<String>,
+	/// report the size of the gzipped code in the `gzip_size` of the outputs, for the size
+	/// budgets. The code is compressed once more, so it's off by default.
+	pub gzip_size: bool,
+	/// report the import map of the resolved bare specifiers in the `import_map` of the outputs,
+	/// see `Resolver::build_import_map`.
+	pub generate_import_map: bool,
+	/// keep only the comments accepted by the filter in the emitted code, by their text, like the
+	/// `@ts-expect-error` comments or the build markers. Default is to keep all the comments.
+	/// The worker builds it from the `keepComments` markers.
+	#[serde(skip)]
+	pub comment_filter: Option<CommentFilter>,
+	/// a custom pass invoked before the `strip` pass, with the type annotations of the module.
+	#[serde(skip)]
+	pub strip_hook: Option<StripHook>,
+}
+
+impl Default for EmitOptions {
+	fn default() -> Self {
+		EmitOptions {
+			jsx_factory: "React.createElement".into(),
+			jsx_fragment_factory: "React.Fragment".into(),
+			is_dev: false,
+			source_map: false,
+			target: JscTarget::Es2022,
+			target_query: false,
+			generator_runtime: GeneratorRuntime::default(),
+			verify_output: false,
+			indent: Indent::default(),
+			newline: Newline::default(),
+			line_width: None,
+			minify: false,
+			output_format: OutputFormat::default(),
+			global_name: None,
+			emit_use_strict: None,
+			import_meta_url: None,
+			live_bindings: false,
+			esmodule_interop: true,
+			freeze_exports: false,
+			dump_ast: false,
+			restore_jsx: false,
+			constant_fold: false,
+			dce: false,
+			tree_shake_locals: false,
+			pure_functions: vec![],
+			scope_prefix: None,
+			optimize_reexports: false,
+			inline_constants: false,
+			expand_star_exports: false,
+			glob_imports: false,
+			cjs_to_esm: false,
+			require_in_esm: RequireMode::default(),
+			decorators_version: DecoratorsVersion::default(),
+			require_all_resolved: false,
+			shared_helpers: false,
+			extract_css: false,
+			css_tags: vec!["css".into(), "styled".into()],
+			replace_extracted_css: false,
+			extract_strings: false,
+			translation_functions: vec!["t".into(), "i18n.t".into()],
+			export_shape: false,
+			name_default_export: false,
+			types_only: false,
+			forbidden: vec![],
+			warn_on_dynamic_code: false,
+			error_overlay: false,
+			error_handler: None,
+			catch_rejections: false,
+			emit_shebang: false,
+			import_attributes: ImportAttributes::default(),
+			exports_comment: false,
+			split_imports: false,
+			inject: vec![],
+			provide: HashMap::new(),
+			polyfill_methods: HashMap::new(),
+			input_source_map: None,
+			source_map_file: None,
+			gzip_size: false,
+			generate_import_map: false,
+			comment_filter: None,
+			strip_hook: None,
+		}
+	}
+}
@@ -1,3 +1,4 @@
+use serde::Serialize;
 use std::{fmt, sync::Arc, sync::RwLock};
 use swc_common::{
   errors::{Diagnostic, DiagnosticBuilder, Emitter},
@@ -40,6 +41,8 @@ impl fmt::Display for DiagnosticBuffer {
   }
 }
 
+impl std::error::Error for DiagnosticBuffer {}
+
 impl DiagnosticBuffer {
   pub fn from_error_buffer<F>(error_buffer: ErrorBuffer, get_loc: F) -> Self
   where
@@ -64,3 +67,22 @@ impl DiagnosticBuffer {
     Self(diagnostics)
   }
 }
+
+/// A warning reported by the compiler, the `line` is 1-based and the `col` is 0-based.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Warning {
+  pub message: String,
+  pub line: usize,
+  pub col: usize,
+}
+
+impl Warning {
+  pub fn new(message: String, loc: Loc) -> Self {
+    Self {
+      message,
+      line: loc.line,
+      col: loc.col_display,
+    }
+  }
+}
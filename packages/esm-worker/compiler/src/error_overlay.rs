@@ -0,0 +1,243 @@
+use crate::ast_utils::{assign, call, expr_stmt, member, var_decl};
+use swc_common::DUMMY_SP;
+use swc_ecma_ast::*;
+use swc_ecma_utils::{find_ids, private_ident, quote_ident};
+use swc_ecma_visit::{noop_fold_type, Fold};
+
+/// Wrap the top-level execution of the module in a `try/catch` that reports the errors to the
+/// `handler` and rethrows them, for the error overlays in development.
+///
+/// The module declarations and the function declarations are kept at the top level, the
+/// variables and the classes are declared at the top level with `let` and initialized in the
+/// `try` block, so the exports and the hoisted functions still see the bindings. The top-level
+/// `await` is kept as the block is not wrapped in a function.
+///
+/// The `handler` is the name of a global function, the `import.meta.hot.reportError` is used
+/// if it's not specified. The rejections inside the async functions are not caught by the
+/// `try/catch`, they are reported by an `unhandledrejection` listener if `catch_rejections` is set.
+pub fn error_overlay_fold(handler: Option<String>, catch_rejections: bool) -> impl Fold {
+	ErrorOverlayFold {
+		handler,
+		catch_rejections,
+	}
+}
+
+struct ErrorOverlayFold {
+	handler: Option<String>,
+	catch_rejections: bool,
+}
+
+impl ErrorOverlayFold {
+	// match: if (import.meta.hot) import.meta.hot.reportError(err)
+	// match: if (typeof handler === "function") handler(err)
+	fn report(&self, arg: Expr) -> Stmt {
+		let (test, callee) = match &self.handler {
+			Some(name) => (
+				Expr::Bin(BinExpr {
+					span: DUMMY_SP,
+					op: BinaryOp::EqEqEq,
+					left: Box::new(Expr::Unary(UnaryExpr {
+						span: DUMMY_SP,
+						op: UnaryOp::TypeOf,
+						arg: Box::new(Expr::Ident(quote_ident!(name.as_str()))),
+					})),
+					right: Box::new(Expr::Lit(Lit::Str(quote_str("function")))),
+				}),
+				Expr::Ident(quote_ident!(name.as_str())),
+			),
+			None => {
+				let hot = member(
+					Expr::MetaProp(MetaPropExpr {
+						meta: quote_ident!("import"),
+						prop: quote_ident!("meta"),
+					}),
+					"hot",
+				);
+				(hot.clone(), member(hot, "reportError"))
+			}
+		};
+		Stmt::If(IfStmt {
+			span: DUMMY_SP,
+			test: Box::new(test),
+			cons: Box::new(expr_stmt(call(callee, vec![arg]))),
+			alt: None,
+		})
+	}
+
+	// match: addEventListener("unhandledrejection", (e) => { report(e.reason) })
+	fn rejection_listener(&self) -> Stmt {
+		let event = private_ident!("e");
+		expr_stmt(call(
+			Expr::Ident(quote_ident!("addEventListener")),
+			vec![
+				Expr::Lit(Lit::Str(quote_str("unhandledrejection"))),
+				Expr::Arrow(ArrowExpr {
+					span: DUMMY_SP,
+					params: vec![Pat::Ident(BindingIdent {
+						id: event.clone(),
+						type_ann: None,
+					})],
+					body: BlockStmtOrExpr::BlockStmt(BlockStmt {
+						span: DUMMY_SP,
+						stmts: vec![self.report(member(Expr::Ident(event), "reason"))],
+					}),
+					is_async: false,
+					is_generator: false,
+					type_params: None,
+					return_type: None,
+				}),
+			],
+		))
+	}
+}
+
+impl Fold for ErrorOverlayFold {
+	noop_fold_type!();
+
+	fn fold_module_items(&mut self, items: Vec<ModuleItem>) -> Vec<ModuleItem> {
+		let mut output: Vec<ModuleItem> = Vec::with_capacity(items.len() + 1);
+		let mut block: Vec<Stmt> = vec![];
+		if self.catch_rejections {
+			block.push(self.rejection_listener());
+		}
+		for item in items {
+			match item {
+				ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl { span, decl })) => {
+					let decl = match split_decl(decl) {
+						Ok((decl, init)) => {
+							block.extend(init);
+							Decl::Var(decl)
+						}
+						Err(decl) => decl,
+					};
+					output.push(ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl {
+						span,
+						decl,
+					})));
+				}
+				// match: export default class App {}
+				ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultDecl(ExportDefaultDecl {
+					decl: DefaultDecl::Class(class),
+					..
+				})) => {
+					let id = class.ident.clone().unwrap_or_else(|| private_ident!("_default"));
+					output.push(declare_let(id.clone()));
+					output.push(export_as_default(id.clone()));
+					block.push(assign_stmt(Pat::Ident(id.into()), Expr::Class(class)));
+				}
+				// match: export default expr
+				ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultExpr(ExportDefaultExpr { expr, .. })) => {
+					let id = private_ident!("_default");
+					output.push(declare_let(id.clone()));
+					output.push(export_as_default(id.clone()));
+					block.push(assign_stmt(Pat::Ident(id.into()), *expr));
+				}
+				// the function declarations are hoisted
+				ModuleItem::Stmt(Stmt::Decl(decl)) => match split_decl(decl) {
+					Ok((decl, init)) => {
+						output.push(ModuleItem::Stmt(Stmt::Decl(Decl::Var(decl))));
+						block.extend(init);
+					}
+					Err(decl) => output.push(ModuleItem::Stmt(Stmt::Decl(decl))),
+				},
+				ModuleItem::ModuleDecl(_) => output.push(item),
+				ModuleItem::Stmt(stmt) => block.push(stmt),
+			}
+		}
+		if block.is_empty() {
+			return output;
+		}
+
+		let err = private_ident!("err");
+		output.push(ModuleItem::Stmt(Stmt::Try(TryStmt {
+			span: DUMMY_SP,
+			block: BlockStmt {
+				span: DUMMY_SP,
+				stmts: block,
+			},
+			handler: Some(CatchClause {
+				span: DUMMY_SP,
+				param: Some(Pat::Ident(err.clone().into())),
+				body: BlockStmt {
+					span: DUMMY_SP,
+					stmts: vec![
+						self.report(Expr::Ident(err.clone())),
+						Stmt::Throw(ThrowStmt {
+							span: DUMMY_SP,
+							arg: Box::new(Expr::Ident(err)),
+						}),
+					],
+				},
+			}),
+			finalizer: None,
+		})));
+		output
+	}
+}
+
+/// split the variable or class declaration into a `let` declaration of the bindings and the
+/// initializations, the other declarations are returned unchanged.
+fn split_decl(decl: Decl) -> Result<(VarDecl, Vec<Stmt>), Decl> {
+	match decl {
+		// match: const { a, b } = obj
+		Decl::Var(VarDecl { kind, decls, .. }) => {
+			let ids: Vec<Ident> = find_ids(&decls);
+			let kind = match kind {
+				VarDeclKind::Var => VarDeclKind::Var,
+				_ => VarDeclKind::Let,
+			};
+			let init = decls
+				.into_iter()
+				.filter_map(|VarDeclarator { name, init, .. }| {
+					init.map(|init| assign_stmt(name, *init))
+				})
+				.collect();
+			Ok((var_decl(kind, ids.into_iter().map(|id| (id, None)).collect()), init))
+		}
+		// match: class C {}
+		Decl::Class(ClassDecl { ident, class, .. }) => Ok((
+			var_decl(VarDeclKind::Let, vec![(ident.clone(), None)]),
+			vec![assign_stmt(
+				Pat::Ident(ident.clone().into()),
+				Expr::Class(ClassExpr {
+					ident: Some(ident),
+					class,
+				}),
+			)],
+		)),
+		decl => Err(decl),
+	}
+}
+
+fn declare_let(id: Ident) -> ModuleItem {
+	ModuleItem::Stmt(Stmt::Decl(Decl::Var(var_decl(VarDeclKind::Let, vec![(id, None)]))))
+}
+
+// match: export { id as default }
+fn export_as_default(id: Ident) -> ModuleItem {
+	ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(NamedExport {
+		span: DUMMY_SP,
+		specifiers: vec![ExportSpecifier::Named(ExportNamedSpecifier {
+			span: DUMMY_SP,
+			orig: id,
+			exported: Some(quote_ident!("default")),
+			is_type_only: false,
+		})],
+		src: None,
+		type_only: false,
+		asserts: None,
+	}))
+}
+
+fn assign_stmt(left: Pat, right: Expr) -> Stmt {
+	expr_stmt(assign(PatOrExpr::Pat(Box::new(left)), right))
+}
+
+fn quote_str(s: &str) -> Str {
+	Str {
+		span: DUMMY_SP,
+		value: s.into(),
+		has_escape: false,
+		kind: Default::default(),
+	}
+}
@@ -1,15 +1,38 @@
+use crate::facade::ExportName;
 use swc_ecma_ast::*;
 use swc_ecma_visit::{noop_fold_type, Fold};
 
 // in `bundle` mode, we need to know what export names are in the start export
 pub struct ExportParser {
   pub names: Vec<String>,
+  /// the value export names of `names` with the enums, the type-only names are in the `types`
+  pub values: Vec<String>,
+  /// the type-only export names: interfaces, type aliases and `export type { T }`
+  pub types: Vec<String>,
+  /// the named value re-exports with the sources: `export { default as Thing } from "./m"`, the
+  /// renamed re-exports of the other names are not recorded since the model has no source names
+  pub reexports: Vec<ExportName>,
 }
 
 impl ExportParser {
+  pub fn new() -> Self {
+    ExportParser {
+      names: vec![],
+      values: vec![],
+      types: vec![],
+      reexports: vec![],
+    }
+  }
+
+  /// record the export name of a value
+  fn push_name(&mut self, name: String) {
+    self.values.push(name.clone());
+    self.names.push(name);
+  }
+
   fn push_pat(&mut self, pat: &Pat) {
     match pat {
-      Pat::Ident(BindingIdent { id, .. }) => self.names.push(id.sym.as_ref().into()),
+      Pat::Ident(BindingIdent { id, .. }) => self.push_name(id.sym.as_ref().into()),
       Pat::Array(ArrayPat { elems, .. }) => elems.into_iter().for_each(|e| {
         if let Some(el) = e {
           self.push_pat(el)
@@ -18,7 +41,7 @@ impl ExportParser {
       Pat::Assign(AssignPat { left, .. }) => self.push_pat(left.as_ref()),
       Pat::Object(ObjectPat { props, .. }) => props.into_iter().for_each(|prop| match prop {
         ObjectPatProp::Assign(AssignPatProp { key, .. }) => {
-          self.names.push(key.sym.as_ref().into())
+          self.push_name(key.sym.as_ref().into())
         }
         ObjectPatProp::KeyValue(KeyValuePatProp { value, .. }) => self.push_pat(value.as_ref()),
         ObjectPatProp::Rest(RestPat { arg, .. }) => self.push_pat(arg.as_ref()),
@@ -33,6 +56,17 @@ impl Fold for ExportParser {
   noop_fold_type!();
 
   fn fold_module_items(&mut self, module_items: Vec<ModuleItem>) -> Vec<ModuleItem> {
+    // the local interfaces and type aliases, for `export { T }`
+    let mut local_types: Vec<String> = vec![];
+    for item in &module_items {
+      if let ModuleItem::Stmt(Stmt::Decl(decl)) = item {
+        match decl {
+          Decl::TsInterface(TsInterfaceDecl { id, .. }) => local_types.push(id.sym.as_ref().into()),
+          Decl::TsTypeAlias(TsTypeAliasDecl { id, .. }) => local_types.push(id.sym.as_ref().into()),
+          _ => {}
+        }
+      }
+    }
     for item in &module_items {
       match item {
         ModuleItem::ModuleDecl(decl) => match decl {
@@ -40,47 +74,82 @@ impl Fold for ExportParser {
           // match: export function foo() {}
           // match: export class foo {}
           ModuleDecl::ExportDecl(ExportDecl { decl, .. }) => match decl {
-            Decl::Class(ClassDecl { ident, .. }) => self.names.push(ident.sym.as_ref().into()),
-            Decl::Fn(FnDecl { ident, .. }) => self.names.push(ident.sym.as_ref().into()),
+            Decl::Class(ClassDecl { ident, .. }) => self.push_name(ident.sym.as_ref().into()),
+            Decl::Fn(FnDecl { ident, .. }) => self.push_name(ident.sym.as_ref().into()),
             Decl::Var(VarDecl { decls, .. }) => decls.into_iter().for_each(|decl| {
               self.push_pat(&decl.name);
             }),
+            // match: export enum Foo {}
+            Decl::TsEnum(TsEnumDecl { id, .. }) => self.values.push(id.sym.as_ref().into()),
+            // match: export interface Foo {}
+            // match: export type Foo = string
+            Decl::TsInterface(TsInterfaceDecl { id, .. }) => self.types.push(id.sym.as_ref().into()),
+            Decl::TsTypeAlias(TsTypeAliasDecl { id, .. }) => self.types.push(id.sym.as_ref().into()),
             _ => {}
           },
           // match: export default function
           // match: export default class
-          ModuleDecl::ExportDefaultDecl(_) => self.names.push("default".into()),
+          ModuleDecl::ExportDefaultDecl(_) => self.push_name("default".into()),
           // match: export default foo
-          ModuleDecl::ExportDefaultExpr(_) => self.names.push("default".into()),
+          ModuleDecl::ExportDefaultExpr(_) => self.push_name("default".into()),
           // match: export { default as React, useState } from "https://esm.sh/react"
           // match: export * as React from "https://esm.sh/react"
+          // match: export type { Foo } from "./types.ts"
+          // match: export { type Foo, bar }
           ModuleDecl::ExportNamed(NamedExport {
             type_only,
             specifiers,
+            src,
             ..
           }) => {
-            if !type_only {
+            if *type_only {
+              specifiers.iter().for_each(|specifier| {
+                if let ExportSpecifier::Named(ExportNamedSpecifier { orig, exported, .. }) = specifier {
+                  self.types.push(exported.as_ref().unwrap_or(orig).sym.as_ref().into());
+                }
+              });
+            } else {
               specifiers
                 .into_iter()
                 .for_each(|specifier| match specifier {
-                  ExportSpecifier::Named(ExportNamedSpecifier { orig, exported, .. }) => {
-                    match exported {
-                      Some(name) => self.names.push(name.sym.as_ref().into()),
-                      None => self.names.push(orig.sym.as_ref().into()),
+                  ExportSpecifier::Named(ExportNamedSpecifier {
+                    orig,
+                    exported,
+                    is_type_only,
+                    ..
+                  }) => {
+                    let name: String = exported.as_ref().unwrap_or(orig).sym.as_ref().into();
+                    let is_local_type = src.is_none() && local_types.contains(&orig.sym.as_ref().into());
+                    if *is_type_only || is_local_type {
+                      self.names.push(name.clone());
+                      self.types.push(name)
+                    } else {
+                      let source_default = orig.sym == *"default";
+                      match src {
+                        Some(src) if source_default || exported.is_none() => {
+                          self.reexports.push(ExportName::Reexport {
+                            name: name.clone(),
+                            source: src.value.as_ref().into(),
+                            source_default,
+                          })
+                        }
+                        _ => {}
+                      }
+                      self.push_name(name)
                     }
                   }
                   ExportSpecifier::Default(ExportDefaultSpecifier { exported, .. }) => {
-                    self.names.push(exported.sym.as_ref().into());
+                    self.push_name(exported.sym.as_ref().into());
                   }
                   ExportSpecifier::Namespace(ExportNamespaceSpecifier { name, .. }) => {
-                    self.names.push(name.sym.as_ref().into())
+                    self.push_name(name.sym.as_ref().into())
                   }
                 });
             }
           }
           // match: export * from "https://esm.sh/react"
           ModuleDecl::ExportAll(ExportAll { src, .. }) => {
-            self.names.push(format!("{{{}}}", src.value))
+            self.push_name(format!("{{{}}}", src.value))
           }
           _ => {}
         },
@@ -0,0 +1,113 @@
+use crate::resolver::{ExportKind, ExportShape};
+use std::collections::HashMap;
+use swc_atoms::JsWord;
+use swc_ecma_ast::*;
+use swc_ecma_utils::find_ids;
+
+/// Get the export shape of the transformed module, the names and the kinds of the exports in the
+/// order of the declarations. The type exports are stripped before, and the default export is
+/// named by the `name_default_export` option.
+pub fn get_export_shape(module: &Module) -> Vec<ExportShape> {
+	// the kinds of the local bindings, for `export { x }`
+	let mut locals: HashMap<JsWord, ExportKind> = HashMap::new();
+	for item in &module.body {
+		match item {
+			ModuleItem::Stmt(Stmt::Decl(decl))
+			| ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl { decl, .. })) => {
+				for (sym, kind) in get_decl_kinds(decl) {
+					locals.insert(sym, kind);
+				}
+			}
+			ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl { specifiers, .. })) => {
+				for specifier in specifiers {
+					let local = match specifier {
+						ImportSpecifier::Named(ImportNamedSpecifier { local, .. })
+						| ImportSpecifier::Default(ImportDefaultSpecifier { local, .. })
+						| ImportSpecifier::Namespace(ImportStarAsSpecifier { local, .. }) => local,
+					};
+					locals.insert(local.sym.clone(), ExportKind::Reexport);
+				}
+			}
+			_ => {}
+		}
+	}
+
+	let mut shape: Vec<ExportShape> = vec![];
+	let mut push = |name: &str, kind: ExportKind| {
+		shape.push(ExportShape {
+			name: name.into(),
+			kind,
+		})
+	};
+	for item in &module.body {
+		if let ModuleItem::ModuleDecl(decl) = item {
+			match decl {
+				// match: export const foo = 'bar'
+				ModuleDecl::ExportDecl(ExportDecl { decl, .. }) => {
+					for (sym, kind) in get_decl_kinds(decl) {
+						push(sym.as_ref(), kind);
+					}
+				}
+				// match: export default function App() {}
+				ModuleDecl::ExportDefaultDecl(ExportDefaultDecl { decl, .. }) => match decl {
+					DefaultDecl::Fn(_) => push("default", ExportKind::Function),
+					DefaultDecl::Class(_) => push("default", ExportKind::Class),
+					DefaultDecl::TsInterfaceDecl(_) => {}
+				},
+				// match: export default foo
+				ModuleDecl::ExportDefaultExpr(_) => push("default", ExportKind::Variable),
+				// match: export { foo, bar as default }
+				// match: export { default as React } from "https://esm.sh/react"
+				// match: export * as React from "https://esm.sh/react"
+				ModuleDecl::ExportNamed(NamedExport {
+					specifiers,
+					src,
+					type_only: false,
+					..
+				}) => {
+					for specifier in specifiers {
+						match specifier {
+							ExportSpecifier::Named(ExportNamedSpecifier {
+								orig,
+								exported,
+								is_type_only: false,
+								..
+							}) => {
+								let name = exported.as_ref().unwrap_or(orig).sym.as_ref();
+								let kind = match src {
+									Some(_) => ExportKind::Reexport,
+									None => locals.get(&orig.sym).copied().unwrap_or(ExportKind::Variable),
+								};
+								push(name, kind);
+							}
+							ExportSpecifier::Named(_) => {}
+							ExportSpecifier::Default(ExportDefaultSpecifier { exported }) => {
+								push(exported.sym.as_ref(), ExportKind::Reexport)
+							}
+							ExportSpecifier::Namespace(ExportNamespaceSpecifier { name, .. }) => {
+								push(name.sym.as_ref(), ExportKind::Namespace)
+							}
+						}
+					}
+				}
+				// match: export * from "https://esm.sh/react"
+				ModuleDecl::ExportAll(ExportAll { src, .. }) => push(src.value.as_ref(), ExportKind::Star),
+				_ => {}
+			}
+		}
+	}
+	shape
+}
+
+/// get the bindings of the declaration with the kinds.
+fn get_decl_kinds(decl: &Decl) -> Vec<(JsWord, ExportKind)> {
+	match decl {
+		Decl::Fn(FnDecl { ident, .. }) => vec![(ident.sym.clone(), ExportKind::Function)],
+		Decl::Class(ClassDecl { ident, .. }) => vec![(ident.sym.clone(), ExportKind::Class)],
+		Decl::Var(VarDecl { decls, .. }) => {
+			let ids: Vec<Ident> = find_ids(decls);
+			ids.into_iter().map(|id| (id.sym, ExportKind::Variable)).collect()
+		}
+		_ => vec![],
+	}
+}
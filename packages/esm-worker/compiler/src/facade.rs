@@ -0,0 +1,85 @@
+use serde::{Deserialize, Serialize};
+
+/// An export of the target module of a facade.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ExportName {
+	/// the default export, it's not re-exported by the star export
+	Default,
+	/// a named export, like `useState`
+	Named(String),
+	/// all the named exports of the target: `export * from "src"`
+	Star,
+	/// a named export re-exported from the `source` instead of the target, like `Thing` of
+	/// `export { default as Thing } from "./m"`, the `source_default` references the default
+	/// export of the source
+	#[serde(rename_all = "camelCase")]
+	Reexport {
+		name: String,
+		source: String,
+		source_default: bool,
+	},
+}
+
+/// Build a facade module that only re-exports the `names` from the `target` module:
+/// ```js
+/// export * from "https://esm.sh/react";
+/// export { default, useState } from "https://esm.sh/react";
+/// ```
+/// The re-exports are grouped by the sources, the duplicate names are re-exported once.
+pub fn build_facade(target: &str, names: &[ExportName]) -> String {
+	let src = quote(target);
+	let mut lines: Vec<String> = vec![];
+	if names.contains(&ExportName::Star) {
+		lines.push(format!("export * from {};", src));
+	}
+	let mut names_seen: Vec<&str> = vec![];
+	let mut values: Vec<&str> = vec![];
+	// the re-exports of the other sources, like `default as Thing` of `./m`
+	let mut reexports: Vec<(&str, Vec<String>)> = vec![];
+	for export_name in names {
+		let name = match export_name {
+			ExportName::Default => "default",
+			ExportName::Named(name) | ExportName::Reexport { name, .. } => name.as_str(),
+			ExportName::Star => continue,
+		};
+		if names_seen.contains(&name) {
+			continue;
+		}
+		names_seen.push(name);
+		match export_name {
+			ExportName::Reexport {
+				source,
+				source_default,
+				..
+			} => {
+				let specifier = if *source_default {
+					format!("default as {}", name)
+				} else {
+					name.into()
+				};
+				match reexports.iter_mut().find(|(src, _)| src == source) {
+					Some((_, specifiers)) => specifiers.push(specifier),
+					None => reexports.push((source.as_str(), vec![specifier])),
+				}
+			}
+			_ => values.push(name),
+		}
+	}
+	if !values.is_empty() {
+		lines.push(format!("export {{ {} }} from {};", values.join(", "), src));
+	}
+	for (source, specifiers) in reexports {
+		lines.push(format!("export {{ {} }} from {};", specifiers.join(", "), quote(source)));
+	}
+	if lines.is_empty() {
+		lines.push("export {};".into());
+	}
+	let mut facade = lines.join("\n");
+	facade.push('\n');
+	facade
+}
+
+fn quote(s: &str) -> String {
+	format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
@@ -0,0 +1,163 @@
+use serde::Deserialize;
+use swc_common::Span;
+use swc_ecma_ast::*;
+use swc_ecma_visit::{Node, Visit, VisitWith};
+
+/// A syntax feature that can be forbidden for the safe subset of JavaScript.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum ForbiddenFeature {
+	/// any reference of `eval`
+	Eval,
+	/// `new Function("...")` and `Function("...")`
+	FunctionConstructor,
+	/// `import("...")`
+	DynamicImport,
+	/// the `await` out of the functions
+	TopLevelAwait,
+}
+
+impl ForbiddenFeature {
+	fn name(&self) -> &'static str {
+		match self {
+			ForbiddenFeature::Eval => "eval",
+			ForbiddenFeature::FunctionConstructor => "Function constructor",
+			ForbiddenFeature::DynamicImport => "dynamic import",
+			ForbiddenFeature::TopLevelAwait => "top-level await",
+		}
+	}
+}
+
+/// Find the uses of the forbidden features, returns the error messages with the spans.
+pub fn find_forbidden_features(
+	module: &Module,
+	forbidden: &[ForbiddenFeature],
+) -> Vec<(String, Span)> {
+	if forbidden.is_empty() {
+		return vec![];
+	}
+	find_features(module, forbidden)
+		.into_iter()
+		.map(|(feature, span)| (format!("Forbidden feature \"{}\"", feature.name()), span))
+		.collect()
+}
+
+/// Find the dynamic code evaluations, `eval` and the `Function` constructor, that defeat the
+/// bundling and the CSP, returns the warning messages with the spans.
+pub fn find_dynamic_code(module: &Module) -> Vec<(String, Span)> {
+	let features = [ForbiddenFeature::Eval, ForbiddenFeature::FunctionConstructor];
+	find_features(module, &features)
+		.into_iter()
+		.map(|(feature, span)| {
+			let message = format!("Dynamic code evaluation with \"{}\"", feature.name());
+			(message, span)
+		})
+		.collect()
+}
+
+/// Check if the module has the top-level await, like `await init()` and `for await (...)`.
+pub fn has_top_level_await(module: &Module) -> bool {
+	find_top_level_await(module).is_some()
+}
+
+/// Find the first top-level await of the module.
+pub fn find_top_level_await(module: &Module) -> Option<Span> {
+	let features = find_features(module, &[ForbiddenFeature::TopLevelAwait]);
+	features.first().map(|(_, span)| *span)
+}
+
+fn find_features(module: &Module, features: &[ForbiddenFeature]) -> Vec<(ForbiddenFeature, Span)> {
+	let mut finder = ForbiddenFeatureFinder {
+		forbidden: features,
+		fn_depth: 0,
+		found: vec![],
+	};
+	module.visit_with(&Invalid { span: Span::default() } as _, &mut finder);
+	finder.found
+}
+
+struct ForbiddenFeatureFinder<'a> {
+	forbidden: &'a [ForbiddenFeature],
+	// the depth of the function scopes, `0` is the top level
+	fn_depth: usize,
+	found: Vec<(ForbiddenFeature, Span)>,
+}
+
+impl ForbiddenFeatureFinder<'_> {
+	fn check(&mut self, feature: ForbiddenFeature, span: Span) {
+		if self.forbidden.contains(&feature) {
+			self.found.push((feature, span));
+		}
+	}
+}
+
+impl Visit for ForbiddenFeatureFinder<'_> {
+	// match: eval("x"), (0, eval)("x")
+	fn visit_expr(&mut self, n: &Expr, _: &dyn Node) {
+		if let Expr::Ident(id) = n {
+			if id.sym.as_ref() == "eval" {
+				self.check(ForbiddenFeature::Eval, id.span);
+			}
+		}
+		n.visit_children_with(self);
+	}
+
+	// the non-computed properties aren't references: obj.eval, x.Function
+	fn visit_member_expr(&mut self, n: &MemberExpr, _: &dyn Node) {
+		n.obj.visit_with(n as _, self);
+		if n.computed {
+			n.prop.visit_with(n as _, self);
+		}
+	}
+
+	// match: Function("return 1"), import("./a.ts")
+	fn visit_call_expr(&mut self, n: &CallExpr, _: &dyn Node) {
+		if let ExprOrSuper::Expr(callee) = &n.callee {
+			if let Expr::Ident(id) = callee.as_ref() {
+				match id.sym.as_ref() {
+					"Function" => self.check(ForbiddenFeature::FunctionConstructor, n.span),
+					"import" => self.check(ForbiddenFeature::DynamicImport, n.span),
+					_ => {}
+				}
+			}
+		}
+		n.visit_children_with(self);
+	}
+
+	// match: new Function("return 1")
+	fn visit_new_expr(&mut self, n: &NewExpr, _: &dyn Node) {
+		if let Expr::Ident(id) = n.callee.as_ref() {
+			if id.sym.as_ref() == "Function" {
+				self.check(ForbiddenFeature::FunctionConstructor, n.span);
+			}
+		}
+		n.visit_children_with(self);
+	}
+
+	fn visit_await_expr(&mut self, n: &AwaitExpr, _: &dyn Node) {
+		if self.fn_depth == 0 {
+			self.check(ForbiddenFeature::TopLevelAwait, n.span);
+		}
+		n.visit_children_with(self);
+	}
+
+	// match: for await (const x of xs) {}
+	fn visit_for_of_stmt(&mut self, n: &ForOfStmt, _: &dyn Node) {
+		if self.fn_depth == 0 && n.await_token.is_some() {
+			self.check(ForbiddenFeature::TopLevelAwait, n.span);
+		}
+		n.visit_children_with(self);
+	}
+
+	fn visit_function(&mut self, n: &Function, _: &dyn Node) {
+		self.fn_depth += 1;
+		n.visit_children_with(self);
+		self.fn_depth -= 1;
+	}
+
+	fn visit_arrow_expr(&mut self, n: &ArrowExpr, _: &dyn Node) {
+		self.fn_depth += 1;
+		n.visit_children_with(self);
+		self.fn_depth -= 1;
+	}
+}
@@ -0,0 +1,189 @@
+use crate::resolver::Resolver;
+use std::{cell::RefCell, rc::Rc};
+use swc_common::{Mark, DUMMY_SP};
+use swc_ecma_ast::*;
+use swc_ecma_utils::{private_ident, quote_ident};
+use swc_ecma_visit::{noop_fold_type, Fold, FoldWith};
+
+/// Expand the Vite-style `import.meta.glob("./pages/*.ts")` to an object of the lazy importers of
+/// the matched files, the files are listed by the `file_lister` of the resolver:
+/// ```js
+/// const pages = { "./pages/a.ts": () => import("./pages/a.ts") }
+/// ```
+/// The `import.meta.globEager("./pages/*.ts")` and the `{ eager: true }` globs are expanded to the
+/// namespace imports of the matched files instead. The imports are resolved and recorded as the
+/// dependencies by the `resolve` pass, the specifiers of the lazy importers are marked with the
+/// `glob_mark` to be recorded as the `Glob` dependencies.
+///
+/// The calls are kept if there is no `file_lister` or the pattern is not a string literal.
+pub fn glob_import_fold(resolver: Rc<RefCell<Resolver>>, glob_mark: Mark) -> impl Fold {
+	GlobImportFold {
+		resolver,
+		glob_mark,
+		imports: vec![],
+	}
+}
+
+struct GlobImportFold {
+	resolver: Rc<RefCell<Resolver>>,
+	glob_mark: Mark,
+	// the namespace imports of the eager globs
+	imports: Vec<(Ident, Str)>,
+}
+
+impl GlobImportFold {
+	/// list the files matched by the pattern, returns `None` if there is no `file_lister`.
+	fn list_files(&self, pattern: &str) -> Option<Vec<String>> {
+		let resolver = self.resolver.borrow();
+		let file_lister = resolver.file_lister.as_ref()?;
+		Some(file_lister(pattern))
+	}
+}
+
+impl Fold for GlobImportFold {
+	noop_fold_type!();
+
+	fn fold_module_items(&mut self, items: Vec<ModuleItem>) -> Vec<ModuleItem> {
+		let items = items.fold_children_with(self);
+		if self.imports.is_empty() {
+			return items;
+		}
+		// match: import * as __glob_0 from "./pages/a.ts"
+		let mut output: Vec<ModuleItem> = self
+			.imports
+			.drain(..)
+			.map(|(local, src)| {
+				ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
+					span: DUMMY_SP,
+					specifiers: vec![ImportSpecifier::Namespace(ImportStarAsSpecifier {
+						span: DUMMY_SP,
+						local,
+					})],
+					src,
+					type_only: false,
+					asserts: None,
+				}))
+			})
+			.collect();
+		output.extend(items);
+		output
+	}
+
+	fn fold_expr(&mut self, expr: Expr) -> Expr {
+		let expr = expr.fold_children_with(self);
+		let (pattern, eager) = match get_glob_call(&expr) {
+			Some(glob) => glob,
+			None => return expr,
+		};
+		let files = match self.list_files(pattern.as_str()) {
+			Some(files) => files,
+			None => return expr,
+		};
+		let props = files
+			.into_iter()
+			.map(|file| {
+				let value = if eager {
+					let local = private_ident!(format!("__glob_{}", self.imports.len()));
+					self.imports.push((local.clone(), quote_str(file.as_str())));
+					Expr::Ident(local)
+				} else {
+					// match: () => import("./pages/a.ts")
+					Expr::Arrow(ArrowExpr {
+						span: DUMMY_SP,
+						params: vec![],
+						body: BlockStmtOrExpr::Expr(Box::new(Expr::Call(CallExpr {
+							span: DUMMY_SP,
+							callee: ExprOrSuper::Expr(Box::new(Expr::Ident(quote_ident!("import")))),
+							args: vec![ExprOrSpread {
+								spread: None,
+								expr: Box::new(Expr::Lit(Lit::Str(Str {
+									span: DUMMY_SP.apply_mark(self.glob_mark),
+									..quote_str(file.as_str())
+								}))),
+							}],
+							type_args: None,
+						}))),
+						is_async: false,
+						is_generator: false,
+						type_params: None,
+						return_type: None,
+					})
+				};
+				PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+					key: PropName::Str(quote_str(file.as_str())),
+					value: Box::new(value),
+				})))
+			})
+			.collect();
+		Expr::Object(ObjectLit {
+			span: DUMMY_SP,
+			props,
+		})
+	}
+}
+
+/// get the pattern of the `import.meta.glob` call, and if the glob is eager.
+fn get_glob_call(expr: &Expr) -> Option<(String, bool)> {
+	let (callee, args) = match expr {
+		Expr::Call(CallExpr {
+			callee: ExprOrSuper::Expr(callee),
+			args,
+			..
+		}) => (callee.as_ref(), args),
+		_ => return None,
+	};
+	let name = match callee {
+		Expr::Member(MemberExpr {
+			obj: ExprOrSuper::Expr(obj),
+			prop,
+			computed: false,
+			..
+		}) => match (obj.as_ref(), prop.as_ref()) {
+			(Expr::MetaProp(MetaPropExpr { meta, prop: meta_prop }), Expr::Ident(prop))
+				if meta.sym.as_ref() == "import" && meta_prop.sym.as_ref() == "meta" =>
+			{
+				prop.sym.as_ref()
+			}
+			_ => return None,
+		},
+		_ => return None,
+	};
+	let pattern = match args.first().map(|arg| arg.expr.as_ref()) {
+		Some(Expr::Lit(Lit::Str(Str { value, .. }))) => value.as_ref().to_owned(),
+		_ => return None,
+	};
+	match name {
+		"glob" => Some((pattern, args.get(1).map(|arg| is_eager(&arg.expr)).unwrap_or(false))),
+		"globEager" => Some((pattern, true)),
+		_ => None,
+	}
+}
+
+// match: { eager: true }
+fn is_eager(options: &Expr) -> bool {
+	match options {
+		Expr::Object(ObjectLit { props, .. }) => props.iter().any(|prop| match prop {
+			PropOrSpread::Prop(prop) => match prop.as_ref() {
+				Prop::KeyValue(KeyValueProp {
+					key: PropName::Ident(key),
+					value,
+				}) => {
+					key.sym.as_ref() == "eager"
+						&& matches!(value.as_ref(), Expr::Lit(Lit::Bool(Bool { value: true, .. })))
+				}
+				_ => false,
+			},
+			_ => false,
+		}),
+		_ => false,
+	}
+}
+
+fn quote_str(s: &str) -> Str {
+	Str {
+		span: DUMMY_SP,
+		value: s.into(),
+		has_escape: false,
+		kind: Default::default(),
+	}
+}
@@ -0,0 +1,10 @@
+use flate2::{write::GzEncoder, Compression};
+use std::io::Write;
+
+/// Get the size of the code gzipped with the default compression level, for the size budgets of
+/// the modules. The served size may differ with the compression of the server.
+pub fn gzip_size(code: &str) -> usize {
+	let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+	encoder.write_all(code.as_bytes()).unwrap();
+	encoder.finish().unwrap().len()
+}
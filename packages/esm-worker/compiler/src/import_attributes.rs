@@ -0,0 +1,354 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use swc_atoms::JsWord;
+use swc_common::{BytePos, Span, DUMMY_SP};
+use swc_ecma_ast::*;
+use swc_ecmascript::codegen::text_writer::WriteJs;
+use swc_ecmascript::parser::{
+	token::{Keyword, Token, TokenAndSpan, Word},
+	JscTarget,
+};
+
+type Result = std::io::Result<()>;
+
+/// How the import attributes(`import sheet from "./s.css" with { type: "css" }`) are emitted, the
+/// code generator drops them.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum ImportAttributes {
+	/// drop the attributes.
+	#[default]
+	Strip,
+	/// keep the keyword of the source, `with` or `assert`.
+	Preserve,
+	/// rewrite the attributes with the `with` keyword.
+	With,
+	/// rewrite the attributes with the `assert` keyword, for the engines supporting the import
+	/// assertions only.
+	Assert,
+}
+
+/// The `with` clause of an import/export statement, the offsets are relative to the source.
+pub struct WithClause {
+	/// the offset of the specifier string.
+	pub src: u32,
+	pub lo: u32,
+	pub hi: u32,
+	pub attributes: Vec<(PropName, JsWord)>,
+}
+
+/// The parser only supports the `assert` import attributes, this function blanks the
+/// `with { type: "css" }` of the import/export statements(keeps the positions of the source map)
+/// and returns the clauses, the attributes are added back to the AST as the `asserts`.
+pub fn strip_with_clauses(source: &mut String, tokens: &[TokenAndSpan]) -> Vec<WithClause> {
+	let mut clauses = vec![];
+	for i in 1..tokens.len() {
+		// match: from "./s.css" with {, import "./s.css" with {
+		let is_with_clause = tokens.len() > i + 2
+			&& matches!(&tokens[i - 1].token, Token::Word(word) if {
+				let word: JsWord = word.clone().into();
+				word == *"from" || word == *"import"
+			})
+			&& matches!(tokens[i].token, Token::Str { .. })
+			&& matches!(tokens[i + 1].token, Token::Word(Word::Keyword(Keyword::With)))
+			&& matches!(tokens[i + 2].token, Token::LBrace);
+		if !is_with_clause {
+			continue;
+		}
+		if let Some((attributes, end)) = parse_attributes(&tokens[i + 3..]) {
+			let (lo, hi) = (tokens[i + 1].span.lo.0, tokens[i + 3 + end].span.hi.0);
+			source.replace_range(lo as usize..hi as usize, " ".repeat((hi - lo) as usize).as_str());
+			clauses.push(WithClause {
+				src: tokens[i].span.lo.0,
+				lo,
+				hi,
+				attributes,
+			});
+		}
+	}
+	clauses
+}
+
+// match: type: "css", "x-key": "value" }
+fn parse_attributes(tokens: &[TokenAndSpan]) -> Option<(Vec<(PropName, JsWord)>, usize)> {
+	let mut attributes = vec![];
+	let mut i = 0;
+	loop {
+		match &tokens.get(i)?.token {
+			Token::RBrace => return Some((attributes, i)),
+			token => {
+				let key = match token {
+					Token::Word(word) => PropName::Ident(Ident::new(word.clone().into(), DUMMY_SP)),
+					Token::Str { value, .. } => PropName::Str(Str {
+						span: DUMMY_SP,
+						value: value.clone(),
+						has_escape: false,
+						kind: Default::default(),
+					}),
+					_ => return None,
+				};
+				let value = match (&tokens.get(i + 1)?.token, &tokens.get(i + 2)?.token) {
+					(Token::Colon, Token::Str { value, .. }) => value.clone(),
+					_ => return None,
+				};
+				attributes.push((key, value));
+				i += 3;
+				if matches!(tokens.get(i)?.token, Token::Comma) {
+					i += 1;
+				}
+			}
+		}
+	}
+}
+
+/// create the `asserts` of the clause, the span of the object is the span of the clause.
+pub fn with_clause_to_object(clause: &WithClause, start_pos: BytePos) -> ObjectLit {
+	ObjectLit {
+		span: Span::new(
+			start_pos + BytePos(clause.lo),
+			start_pos + BytePos(clause.hi),
+			Default::default(),
+		),
+		props: clause
+			.attributes
+			.iter()
+			.map(|(key, value)| {
+				PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+					key: key.clone(),
+					value: Box::new(Expr::Lit(Lit::Str(Str {
+						span: DUMMY_SP,
+						value: value.clone(),
+						has_escape: false,
+						kind: Default::default(),
+					}))),
+				})))
+			})
+			.collect(),
+	}
+}
+
+/// get the `type` of the import attributes, like `css` or `json`.
+pub fn get_import_type(attributes: &ObjectLit) -> Option<String> {
+	attributes.props.iter().find_map(|prop| match prop {
+		PropOrSpread::Prop(prop) => match prop.as_ref() {
+			Prop::KeyValue(KeyValueProp { key, value }) if get_key(key)? == "type" => {
+				match value.as_ref() {
+					Expr::Lit(Lit::Str(Str { value, .. })) => Some(value.as_ref().into()),
+					_ => None,
+				}
+			}
+			_ => None,
+		},
+		_ => None,
+	})
+}
+
+/// The attributes emitted after a specifier.
+pub struct AttributesClause {
+	keyword: &'static str,
+	/// the keys and the values, written as in the source.
+	attributes: Vec<(String, String)>,
+}
+
+/// Collect the clauses of the import attributes by the positions of the specifiers, the
+/// specifiers keep their spans in the `resolve_fold`. The `with_clauses` are the positions of
+/// the attributes written with the `with` keyword.
+pub fn collect_attributes_clauses(
+	module: &Module,
+	mode: ImportAttributes,
+	with_clauses: &[BytePos],
+) -> HashMap<BytePos, AttributesClause> {
+	let mut clauses = HashMap::new();
+	if mode == ImportAttributes::Strip {
+		return clauses;
+	}
+	for item in &module.body {
+		let (src, attributes) = match item {
+			ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl { src, asserts, .. }))
+			| ModuleItem::ModuleDecl(ModuleDecl::ExportAll(ExportAll { src, asserts, .. }))
+			| ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(NamedExport {
+				src: Some(src),
+				asserts,
+				..
+			})) => match asserts {
+				Some(attributes) if !attributes.props.is_empty() && !src.span.is_dummy() => {
+					(src, attributes)
+				}
+				_ => continue,
+			},
+			_ => continue,
+		};
+		let keyword = match mode {
+			ImportAttributes::Preserve if with_clauses.contains(&attributes.span.lo) => "with",
+			ImportAttributes::Preserve | ImportAttributes::Assert => "assert",
+			_ => "with",
+		};
+		let attributes = attributes
+			.props
+			.iter()
+			.filter_map(|prop| match prop {
+				PropOrSpread::Prop(prop) => match prop.as_ref() {
+					Prop::KeyValue(KeyValueProp { key, value }) => match (key, value.as_ref()) {
+						(PropName::Ident(id), Expr::Lit(Lit::Str(Str { value, .. }))) => {
+							Some((id.sym.as_ref().into(), quote(value)))
+						}
+						(PropName::Str(key), Expr::Lit(Lit::Str(Str { value, .. }))) => {
+							Some((quote(&key.value), quote(value)))
+						}
+						_ => None,
+					},
+					_ => None,
+				},
+				_ => None,
+			})
+			.collect();
+		clauses.insert(
+			src.span.lo,
+			AttributesClause {
+				keyword,
+				attributes,
+			},
+		);
+	}
+	clauses
+}
+
+/// A writer emits the import attributes after the specifiers, since the code generator drops
+/// them. The clause is written before the semicolon following the closing quote of a specifier
+/// in the `clauses`, the source map is kept as the clause is written by the inner writer.
+pub struct ImportAttributesWriter<W: WriteJs> {
+	inner: W,
+	clauses: HashMap<BytePos, AttributesClause>,
+	/// the specifier being written, and if its closing quote is written.
+	specifier: Option<(BytePos, bool)>,
+}
+
+impl<W: WriteJs> ImportAttributesWriter<W> {
+	pub fn new(inner: W, clauses: HashMap<BytePos, AttributesClause>) -> Self {
+		ImportAttributesWriter {
+			inner,
+			clauses,
+			specifier: None,
+		}
+	}
+
+	// match: with { type: "css" }
+	fn write_clause(&mut self, pos: BytePos) -> Result {
+		let clause = match self.clauses.get(&pos) {
+			Some(clause) => clause,
+			None => return Ok(()),
+		};
+		self.inner.write_space()?;
+		self.inner.write_keyword(None, clause.keyword)?;
+		self.inner.write_space()?;
+		self.inner.write_punct(None, "{")?;
+		self.inner.write_space()?;
+		for (i, (key, value)) in clause.attributes.iter().enumerate() {
+			if i > 0 {
+				self.inner.write_punct(None, ",")?;
+				self.inner.write_space()?;
+			}
+			self.inner.write_str(key)?;
+			self.inner.write_punct(None, ":")?;
+			self.inner.write_space()?;
+			self.inner.write_str_lit(DUMMY_SP, value)?;
+		}
+		self.inner.write_space()?;
+		self.inner.write_punct(None, "}")
+	}
+}
+
+impl<W: WriteJs> WriteJs for ImportAttributesWriter<W> {
+	fn target(&self) -> JscTarget {
+		self.inner.target()
+	}
+
+	fn increase_indent(&mut self) -> Result {
+		self.inner.increase_indent()
+	}
+
+	fn decrease_indent(&mut self) -> Result {
+		self.inner.decrease_indent()
+	}
+
+	fn write_semi(&mut self, span: Option<Span>) -> Result {
+		if let Some((pos, true)) = self.specifier.take() {
+			self.write_clause(pos)?;
+		}
+		self.inner.write_semi(span)
+	}
+
+	fn write_space(&mut self) -> Result {
+		self.inner.write_space()
+	}
+
+	fn write_keyword(&mut self, span: Option<Span>, s: &'static str) -> Result {
+		self.inner.write_keyword(span, s)
+	}
+
+	fn write_operator(&mut self, span: Option<Span>, s: &str) -> Result {
+		self.inner.write_operator(span, s)
+	}
+
+	fn write_param(&mut self, s: &str) -> Result {
+		self.inner.write_param(s)
+	}
+
+	fn write_property(&mut self, s: &str) -> Result {
+		self.inner.write_property(s)
+	}
+
+	fn write_line(&mut self) -> Result {
+		self.inner.write_line()
+	}
+
+	fn write_lit(&mut self, span: Span, s: &str) -> Result {
+		self.inner.write_lit(span, s)
+	}
+
+	fn write_comment(&mut self, span: Span, s: &str) -> Result {
+		self.inner.write_comment(span, s)
+	}
+
+	fn write_str_lit(&mut self, span: Span, s: &str) -> Result {
+		self.inner.write_str_lit(span, s)
+	}
+
+	fn write_str(&mut self, s: &str) -> Result {
+		self.inner.write_str(s)
+	}
+
+	fn write_symbol(&mut self, span: Span, s: &str) -> Result {
+		self.inner.write_symbol(span, s)
+	}
+
+	fn write_punct(&mut self, span: Option<Span>, s: &'static str) -> Result {
+		// match: "./s.css", './s.css'
+		if s == "\"" || s == "'" {
+			match (span, self.specifier) {
+				(Some(span), _) if self.clauses.contains_key(&span.lo) => {
+					self.specifier = Some((span.lo, false));
+				}
+				(None, Some((pos, false))) => self.specifier = Some((pos, true)),
+				_ => {}
+			}
+		}
+		self.inner.write_punct(span, s)
+	}
+
+	fn care_about_srcmap(&self) -> bool {
+		self.inner.care_about_srcmap()
+	}
+}
+
+fn get_key(key: &PropName) -> Option<&str> {
+	match key {
+		PropName::Ident(id) => Some(id.sym.as_ref()),
+		PropName::Str(s) => Some(s.value.as_ref()),
+		_ => None,
+	}
+}
+
+fn quote(s: &str) -> String {
+	format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
@@ -0,0 +1,263 @@
+use serde::Deserialize;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use swc_atoms::JsWord;
+use swc_common::{Mark, SyntaxContext, DUMMY_SP};
+use swc_ecma_ast::*;
+use swc_ecma_utils::{collect_decls_with_ctxt, ident::IdentLike, quote_ident, Id};
+use swc_ecma_visit::{noop_fold_type, Fold, FoldWith, Node, Visit, VisitWith};
+use swc_ecmascript::transforms::resolver_with_mark;
+
+/// An import injected at the top of the module, like the `inject` of esbuild:
+/// `{ specifier: "./jsx.ts", default: "React", names: ["h"] }` =>
+/// `import React, { h } from "./jsx.ts"`.
+/// The import without the bindings is a side-effect import: `import "./polyfill.ts"`.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub struct InjectImport {
+	pub specifier: String,
+	/// the local name of the default import.
+	#[serde(default)]
+	pub default: Option<String>,
+	/// the named imports.
+	#[serde(default)]
+	pub names: Vec<String>,
+}
+
+/// Prepend the `imports` to the module, the imports are resolved by the `resolve` pass like the
+/// others. The side-effect imports are always kept, the unused bindings of the TypeScript modules
+/// are elided by the `strip` pass.
+pub fn inject_imports_fold(imports: Vec<InjectImport>) -> impl Fold {
+	InjectImportsFold { imports }
+}
+
+struct InjectImportsFold {
+	imports: Vec<InjectImport>,
+}
+
+impl Fold for InjectImportsFold {
+	noop_fold_type!();
+
+	fn fold_module(&mut self, mut module: Module) -> Module {
+		let mut body: Vec<ModuleItem> = Vec::with_capacity(self.imports.len() + module.body.len());
+		for import in &self.imports {
+			let mut specifiers: Vec<ImportSpecifier> = vec![];
+			if let Some(local) = &import.default {
+				specifiers.push(ImportSpecifier::Default(ImportDefaultSpecifier {
+					span: DUMMY_SP,
+					local: quote_ident!(local.as_str()),
+				}));
+			}
+			for name in &import.names {
+				specifiers.push(ImportSpecifier::Named(ImportNamedSpecifier {
+					span: DUMMY_SP,
+					local: quote_ident!(name.as_str()),
+					imported: None,
+					is_type_only: false,
+				}));
+			}
+			body.push(ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
+				span: DUMMY_SP,
+				specifiers,
+				src: new_str(import.specifier.as_str()),
+				type_only: false,
+				asserts: None,
+			})));
+		}
+		body.append(&mut module.body);
+		module.body = body;
+		module
+	}
+}
+
+/// Import the free globals referenced by the module from the modules providing them, like the
+/// `ProvidePlugin` of webpack. The `provide` maps the global names to the specifiers and the
+/// bindings: `{ Buffer: ("./buffer.ts", "Buffer") }` => `import { Buffer } from "./buffer.ts"`.
+/// The references are resolved by the scopes of the module, the globals shadowed by the local
+/// bindings are provided only if they are referenced out of the scopes of the bindings.
+pub fn provide_fold(provide: HashMap<String, (String, String)>) -> impl Fold {
+	ProvideFold { provide }
+}
+
+struct ProvideFold {
+	provide: HashMap<String, (String, String)>,
+}
+
+impl Fold for ProvideFold {
+	noop_fold_type!();
+
+	fn fold_module(&mut self, mut module: Module) -> Module {
+		// the `resolver` pass marks the free references and the top-level bindings with the
+		// top-level mark, it runs on a copy since the module is resolved again by the later passes
+		let top_level_mark = Mark::fresh(Mark::root());
+		let resolved = module.clone().fold_with(&mut resolver_with_mark(top_level_mark));
+		let top_level = SyntaxContext::empty().apply_mark(top_level_mark);
+		let mut collector = FreeRefCollector {
+			names: &self.provide,
+			top_level,
+			decls: collect_decls_with_ctxt::<Id, _>(&resolved, top_level).into_iter().collect(),
+			refs: BTreeSet::new(),
+		};
+		resolved.visit_with(&Invalid { span: DUMMY_SP } as _, &mut collector);
+		let mut body: Vec<ModuleItem> = vec![];
+		for name in collector.refs {
+			let (specifier, binding) = &self.provide[name.as_ref()];
+			// match: import { default as process } from "node:process"
+			let specifier_item = if binding == "default" {
+				ImportSpecifier::Default(ImportDefaultSpecifier {
+					span: DUMMY_SP,
+					local: quote_ident!(name.clone()),
+				})
+			} else {
+				ImportSpecifier::Named(ImportNamedSpecifier {
+					span: DUMMY_SP,
+					local: quote_ident!(name.clone()),
+					imported: if *binding == *name {
+						None
+					} else {
+						Some(quote_ident!(binding.as_str()))
+					},
+					is_type_only: false,
+				})
+			};
+			body.push(ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
+				span: DUMMY_SP,
+				specifiers: vec![specifier_item],
+				src: new_str(specifier.as_str()),
+				type_only: false,
+				asserts: None,
+			})));
+		}
+		body.append(&mut module.body);
+		module.body = body;
+		module
+	}
+}
+
+/// collect the free references of the provided globals, in the order of the names.
+struct FreeRefCollector<'a> {
+	names: &'a HashMap<String, (String, String)>,
+	// the context of the top-level scope
+	top_level: SyntaxContext,
+	// the top-level bindings
+	decls: HashSet<Id>,
+	refs: BTreeSet<JsWord>,
+}
+
+impl FreeRefCollector<'_> {
+	fn is_free(&self, id: &Ident) -> bool {
+		id.span.ctxt == self.top_level
+			&& self.names.contains_key(id.sym.as_ref())
+			&& !self.decls.contains(&id.to_id())
+	}
+}
+
+impl Visit for FreeRefCollector<'_> {
+	fn visit_expr(&mut self, n: &Expr, _: &dyn Node) {
+		match n {
+			Expr::Ident(id) if self.is_free(id) => {
+				self.refs.insert(id.sym.clone());
+			}
+			_ => n.visit_children_with(self),
+		}
+	}
+
+	// match: { Buffer }
+	fn visit_prop(&mut self, n: &Prop, _: &dyn Node) {
+		match n {
+			Prop::Shorthand(id) if self.is_free(id) => {
+				self.refs.insert(id.sym.clone());
+			}
+			_ => n.visit_children_with(self),
+		}
+	}
+
+	fn visit_member_expr(&mut self, n: &MemberExpr, _: &dyn Node) {
+		n.obj.visit_with(n as _, self);
+		if n.computed {
+			n.prop.visit_with(n as _, self);
+		}
+	}
+}
+
+/// Import the polyfills of the methods called by the module, the `polyfills` maps the method
+/// names to the specifiers: `{ at: "core-js/modules/es.array.at" }` =>
+/// `import "core-js/modules/es.array.at"` if the module calls `arr.at(-1)`. The methods are
+/// detected by the names since the types of the receivers are unknown, each polyfill is imported
+/// once.
+pub fn polyfill_methods_fold(polyfills: HashMap<String, String>) -> impl Fold {
+	PolyfillMethodsFold { polyfills }
+}
+
+struct PolyfillMethodsFold {
+	polyfills: HashMap<String, String>,
+}
+
+impl Fold for PolyfillMethodsFold {
+	noop_fold_type!();
+
+	fn fold_module(&mut self, mut module: Module) -> Module {
+		let mut collector = MethodCallCollector {
+			names: &self.polyfills,
+			calls: BTreeSet::new(),
+		};
+		module.visit_with(&Invalid { span: DUMMY_SP } as _, &mut collector);
+		let mut specifiers: Vec<&str> = vec![];
+		for name in collector.calls {
+			let specifier = self.polyfills[name.as_ref()].as_str();
+			if !specifiers.contains(&specifier) {
+				specifiers.push(specifier);
+			}
+		}
+		let mut body: Vec<ModuleItem> = specifiers
+			.into_iter()
+			.map(|specifier| {
+				ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
+					span: DUMMY_SP,
+					specifiers: vec![],
+					src: new_str(specifier),
+					type_only: false,
+					asserts: None,
+				}))
+			})
+			.collect();
+		body.append(&mut module.body);
+		module.body = body;
+		module
+	}
+}
+
+/// collect the names of the polyfilled methods called by the module, in the order of the names.
+struct MethodCallCollector<'a> {
+	names: &'a HashMap<String, String>,
+	calls: BTreeSet<JsWord>,
+}
+
+impl Visit for MethodCallCollector<'_> {
+	fn visit_call_expr(&mut self, n: &CallExpr, _: &dyn Node) {
+		// match: arr.at(-1), arr["at"](-1)
+		if let ExprOrSuper::Expr(callee) = &n.callee {
+			if let Expr::Member(MemberExpr { prop, computed, .. }) = callee.as_ref() {
+				let name = match prop.as_ref() {
+					Expr::Ident(id) if !*computed => Some(&id.sym),
+					Expr::Lit(Lit::Str(Str { value, .. })) if *computed => Some(value),
+					_ => None,
+				};
+				if let Some(name) = name {
+					if self.names.contains_key(name.as_ref()) {
+						self.calls.insert(name.clone());
+					}
+				}
+			}
+		}
+		n.visit_children_with(self);
+	}
+}
+
+fn new_str(value: &str) -> Str {
+	Str {
+		span: DUMMY_SP,
+		value: value.into(),
+		has_escape: false,
+		kind: Default::default(),
+	}
+}
@@ -0,0 +1,174 @@
+use crate::resolver::Resolver;
+use crate::swc::SWC;
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use swc_ecma_ast::*;
+use swc_ecma_utils::{ident::IdentLike, Id};
+use swc_ecma_visit::{noop_fold_type, Fold, FoldWith};
+
+/// Inline the literal constants imported from the dependencies, the uses of
+/// `import { VERSION } from "./v.ts"` are replaced with the literal if the `v.ts` has
+/// `export const VERSION = "1.0"`, and the import is dropped. The dependency sources are
+/// fetched by the `source_fetcher` of the resolver. An import is kept as it is if any of its
+/// specifiers isn't a literal constant.
+///
+/// The idents are compared with the syntax contexts, so the `resolver` pass must run before.
+pub fn inline_constants_fold(resolver: Rc<RefCell<Resolver>>) -> impl Fold {
+	InlineConstantsFold {
+		resolver,
+		constants: HashMap::new(),
+	}
+}
+
+struct InlineConstantsFold {
+	resolver: Rc<RefCell<Resolver>>,
+	constants: HashMap<Id, Lit>,
+}
+
+impl InlineConstantsFold {
+	/// get the literal constants for the specifiers of the import declaration, returns `None` if
+	/// any of them can't be inlined.
+	fn get_constants(&self, decl: &ImportDecl, exported: &[Id]) -> Option<Vec<(Id, Lit)>> {
+		if decl.type_only || decl.specifiers.is_empty() {
+			return None;
+		}
+		let resolver = self.resolver.borrow();
+		let source = (resolver.source_fetcher.as_ref()?)(decl.src.value.as_ref())?;
+		let module = SWC::parse(decl.src.value.as_ref(), source.as_str(), None).ok()?;
+		let literals = get_exported_literals(&module.module);
+		decl
+			.specifiers
+			.iter()
+			.map(|specifier| match specifier {
+				// match: import { VERSION, VERSION as V } from "./v.ts"
+				ImportSpecifier::Named(ImportNamedSpecifier {
+					local, imported, ..
+				}) => {
+					let name = imported.as_ref().unwrap_or(local);
+					let lit = literals.get(name.sym.as_ref())?;
+					// the `export { VERSION }` needs the binding
+					if exported.contains(&local.to_id()) {
+						return None;
+					}
+					Some((local.to_id(), lit.clone()))
+				}
+				_ => None,
+			})
+			.collect()
+	}
+}
+
+impl Fold for InlineConstantsFold {
+	noop_fold_type!();
+
+	fn fold_module_items(&mut self, items: Vec<ModuleItem>) -> Vec<ModuleItem> {
+		let exported: Vec<Id> = items
+			.iter()
+			.filter_map(|item| match item {
+				ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(NamedExport {
+					src: None,
+					specifiers,
+					..
+				})) => Some(specifiers),
+				_ => None,
+			})
+			.flatten()
+			.filter_map(|specifier| match specifier {
+				ExportSpecifier::Named(ExportNamedSpecifier { orig, .. }) => Some(orig.to_id()),
+				_ => None,
+			})
+			.collect();
+		let mut output: Vec<ModuleItem> = Vec::with_capacity(items.len());
+		for item in items {
+			if let ModuleItem::ModuleDecl(ModuleDecl::Import(decl)) = &item {
+				if let Some(constants) = self.get_constants(decl, &exported) {
+					self.constants.extend(constants);
+					continue;
+				}
+			}
+			output.push(item);
+		}
+		output.fold_children_with(self)
+	}
+
+	fn fold_expr(&mut self, expr: Expr) -> Expr {
+		match expr {
+			Expr::Ident(id) => match self.constants.get(&id.to_id()) {
+				Some(lit) => Expr::Lit(with_span(lit.clone(), id.span)),
+				None => Expr::Ident(id),
+			},
+			_ => expr.fold_children_with(self),
+		}
+	}
+
+	// match: { VERSION }
+	fn fold_prop(&mut self, prop: Prop) -> Prop {
+		match prop {
+			Prop::Shorthand(id) => match self.constants.get(&id.to_id()) {
+				Some(lit) => Prop::KeyValue(KeyValueProp {
+					value: Box::new(Expr::Lit(with_span(lit.clone(), id.span))),
+					key: PropName::Ident(id),
+				}),
+				None => Prop::Shorthand(id),
+			},
+			_ => prop.fold_children_with(self),
+		}
+	}
+
+	// the property of `obj.VERSION` is not a reference
+	fn fold_member_expr(&mut self, expr: MemberExpr) -> MemberExpr {
+		MemberExpr {
+			obj: expr.obj.fold_with(self),
+			prop: if expr.computed {
+				expr.prop.fold_with(self)
+			} else {
+				expr.prop
+			},
+			..expr
+		}
+	}
+}
+
+/// get the `export const NAME = <literal>` of the module.
+fn get_exported_literals(module: &Module) -> HashMap<String, Lit> {
+	let mut literals = HashMap::new();
+	for item in &module.body {
+		if let ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl {
+			decl: Decl::Var(VarDecl {
+				kind: VarDeclKind::Const,
+				decls,
+				..
+			}),
+			..
+		})) = item
+		{
+			for decl in decls {
+				if let (Pat::Ident(BindingIdent { id, .. }), Some(init)) = (&decl.name, &decl.init) {
+					let init = match init.as_ref() {
+						// match: "1.0" as const
+						Expr::TsConstAssertion(TsConstAssertion { expr, .. }) => expr.as_ref(),
+						init => init,
+					};
+					if let Expr::Lit(lit @ (Lit::Str(_) | Lit::Num(_) | Lit::Bool(_) | Lit::Null(_))) = init {
+						literals.insert(id.sym.as_ref().into(), lit.clone());
+					}
+				}
+			}
+		}
+	}
+	literals
+}
+
+/// replace the span of the literal, the original span points to the dependency source.
+fn with_span(lit: Lit, span: swc_common::Span) -> Lit {
+	match lit {
+		Lit::Str(str) => Lit::Str(Str {
+			span,
+			kind: StrKind::Synthesized,
+			..str
+		}),
+		Lit::Num(num) => Lit::Num(Number { span, ..num }),
+		Lit::Bool(bool) => Lit::Bool(Bool { span, ..bool }),
+		Lit::Null(_) => Lit::Null(Null { span }),
+		lit => lit,
+	}
+}
@@ -0,0 +1,63 @@
+use indexmap::IndexSet;
+use serde::Serialize;
+use swc_common::DUMMY_SP;
+use swc_ecma_ast::*;
+use swc_ecma_visit::{Node, Visit, VisitWith};
+
+/// The JSX element names used in a module, in the order of the first use.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JsxComponents {
+	/// the component references: `<Foo />`, `<Foo.Bar />`
+	pub components: Vec<String>,
+	/// the intrinsic elements: `<div />`, `<svg:rect />`
+	pub intrinsics: Vec<String>,
+}
+
+/// collect the JSX element names of the module, the names starting with a lowercase letter and the
+/// namespaced names are intrinsic elements, as React does.
+pub fn get_jsx_components(module: &Module) -> JsxComponents {
+	let mut collector = JsxComponentsCollector::default();
+	module.visit_with(&Invalid { span: DUMMY_SP } as _, &mut collector);
+	JsxComponents {
+		components: collector.components.into_iter().collect(),
+		intrinsics: collector.intrinsics.into_iter().collect(),
+	}
+}
+
+#[derive(Default)]
+struct JsxComponentsCollector {
+	components: IndexSet<String>,
+	intrinsics: IndexSet<String>,
+}
+
+impl Visit for JsxComponentsCollector {
+	fn visit_jsx_opening_element(&mut self, n: &JSXOpeningElement, _: &dyn Node) {
+		match &n.name {
+			JSXElementName::Ident(id) => {
+				let name: String = id.sym.as_ref().into();
+				if name.starts_with(|c: char| c.is_ascii_lowercase()) {
+					self.intrinsics.insert(name);
+				} else {
+					self.components.insert(name);
+				}
+			}
+			JSXElementName::JSXMemberExpr(expr) => {
+				self.components.insert(member_name(expr));
+			}
+			JSXElementName::JSXNamespacedName(JSXNamespacedName { ns, name }) => {
+				self.intrinsics.insert(format!("{}:{}", ns.sym, name.sym));
+			}
+		}
+		n.visit_children_with(self);
+	}
+}
+
+// match: Foo.Bar.Baz
+fn member_name(expr: &JSXMemberExpr) -> String {
+	let obj = match &expr.obj {
+		JSXObject::Ident(id) => id.sym.as_ref().to_owned(),
+		JSXObject::JSXMemberExpr(expr) => member_name(expr),
+	};
+	format!("{}.{}", obj, expr.prop.sym)
+}
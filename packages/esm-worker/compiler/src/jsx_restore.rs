@@ -0,0 +1,237 @@
+use swc_common::DUMMY_SP;
+use swc_ecma_ast::*;
+use swc_ecma_visit::{noop_fold_type, Fold, FoldWith};
+
+/// Convert the classic `React.createElement(...)` calls back to JSX elements, the inverse of
+/// the jsx pass. A call that can't be converted confidently(spread arguments, computed props,
+/// etc.) is kept as it is.
+pub fn jsx_restore_fold(pragma: &str, pragma_frag: &str) -> impl Fold {
+	JsxRestoreFold {
+		pragma: pragma.split('.').map(|s| s.into()).collect(),
+		pragma_frag: pragma_frag.split('.').map(|s| s.into()).collect(),
+	}
+}
+
+struct JsxRestoreFold {
+	pragma: Vec<String>,
+	pragma_frag: Vec<String>,
+}
+
+impl Fold for JsxRestoreFold {
+	noop_fold_type!();
+
+	fn fold_expr(&mut self, expr: Expr) -> Expr {
+		let expr = expr.fold_children_with(self);
+		if let Expr::Call(call) = &expr {
+			if let ExprOrSuper::Expr(callee) = &call.callee {
+				if is_path(callee, &self.pragma) {
+					if let Some(el) = self.restore(&call.args) {
+						return el;
+					}
+				}
+			}
+		}
+		expr
+	}
+}
+
+impl JsxRestoreFold {
+	fn restore(&self, args: &[ExprOrSpread]) -> Option<Expr> {
+		if args.is_empty() || args.iter().any(|arg| arg.spread.is_some()) {
+			return None;
+		}
+		let attrs = match args.get(1).map(|arg| arg.expr.as_ref()) {
+			None | Some(Expr::Lit(Lit::Null(_))) => vec![],
+			Some(Expr::Object(obj)) => restore_attrs(obj)?,
+			_ => return None,
+		};
+		let mut children: Vec<JSXElementChild> = vec![];
+		for arg in args.iter().skip(2) {
+			children.push(restore_child(arg.expr.as_ref()));
+		}
+
+		// match: React.createElement(React.Fragment, null, ...children)
+		if is_path(&args[0].expr, &self.pragma_frag) {
+			if !attrs.is_empty() {
+				return None;
+			}
+			return Some(Expr::JSXFragment(JSXFragment {
+				span: DUMMY_SP,
+				opening: JSXOpeningFragment { span: DUMMY_SP },
+				children,
+				closing: JSXClosingFragment { span: DUMMY_SP },
+			}));
+		}
+
+		let name = restore_element_name(&args[0].expr)?;
+		let self_closing = children.is_empty();
+		Some(Expr::JSXElement(Box::new(JSXElement {
+			span: DUMMY_SP,
+			opening: JSXOpeningElement {
+				name: name.clone(),
+				span: DUMMY_SP,
+				attrs,
+				self_closing,
+				type_args: None,
+			},
+			children,
+			closing: if self_closing {
+				None
+			} else {
+				Some(JSXClosingElement {
+					span: DUMMY_SP,
+					name,
+				})
+			},
+		})))
+	}
+}
+
+// match: "div", Foo, Foo.Bar
+fn restore_element_name(expr: &Expr) -> Option<JSXElementName> {
+	match expr {
+		Expr::Lit(Lit::Str(Str { value, .. })) => {
+			let name = value.as_ref();
+			// a capitalized name is a component reference in JSX
+			if !name.starts_with(|c: char| c.is_ascii_lowercase())
+				|| !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+			{
+				return None;
+			}
+			Some(JSXElementName::Ident(Ident::new(value.clone(), DUMMY_SP)))
+		}
+		Expr::Ident(id) => {
+			if !id.sym.starts_with(|c: char| c.is_ascii_uppercase()) {
+				return None;
+			}
+			Some(JSXElementName::Ident(id.clone()))
+		}
+		Expr::Member(_) => match restore_object(expr)? {
+			JSXObject::JSXMemberExpr(member) => Some(JSXElementName::JSXMemberExpr(*member)),
+			JSXObject::Ident(_) => None,
+		},
+		_ => None,
+	}
+}
+
+fn restore_object(expr: &Expr) -> Option<JSXObject> {
+	match expr {
+		Expr::Ident(id) => Some(JSXObject::Ident(id.clone())),
+		Expr::Member(MemberExpr {
+			obj: ExprOrSuper::Expr(obj),
+			prop,
+			computed: false,
+			..
+		}) => {
+			if let Expr::Ident(prop) = prop.as_ref() {
+				return Some(JSXObject::JSXMemberExpr(Box::new(JSXMemberExpr {
+					obj: restore_object(obj)?,
+					prop: prop.clone(),
+				})));
+			}
+			None
+		}
+		_ => None,
+	}
+}
+
+// match: { id: "a", onClick, ...props }
+fn restore_attrs(obj: &ObjectLit) -> Option<Vec<JSXAttrOrSpread>> {
+	let mut attrs: Vec<JSXAttrOrSpread> = vec![];
+	for prop in &obj.props {
+		match prop {
+			PropOrSpread::Spread(SpreadElement { expr, .. }) => {
+				attrs.push(JSXAttrOrSpread::SpreadElement(SpreadElement {
+					dot3_token: DUMMY_SP,
+					expr: expr.clone(),
+				}))
+			}
+			PropOrSpread::Prop(prop) => {
+				let (name, value) = match prop.as_ref() {
+					Prop::Shorthand(id) => (id.clone(), Expr::Ident(id.clone())),
+					Prop::KeyValue(KeyValueProp { key, value }) => {
+						let name = match key {
+							PropName::Ident(id) => id.clone(),
+							PropName::Str(Str { value, .. })
+								if value.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') =>
+							{
+								Ident::new(value.clone(), DUMMY_SP)
+							}
+							_ => return None,
+						};
+						(name, value.as_ref().clone())
+					}
+					_ => return None,
+				};
+				let value = match value {
+					Expr::Lit(Lit::Str(s)) if is_jsx_attr_str(&s.value) => JSXAttrValue::Lit(Lit::Str(s)),
+					Expr::JSXElement(el) => JSXAttrValue::JSXElement(el),
+					value => JSXAttrValue::JSXExprContainer(JSXExprContainer {
+						span: DUMMY_SP,
+						expr: JSXExpr::Expr(Box::new(value)),
+					}),
+				};
+				attrs.push(JSXAttrOrSpread::JSXAttr(JSXAttr {
+					span: DUMMY_SP,
+					name: JSXAttrName::Ident(name),
+					value: Some(value),
+				}));
+			}
+		}
+	}
+	Some(attrs)
+}
+
+fn restore_child(expr: &Expr) -> JSXElementChild {
+	match expr {
+		Expr::Lit(Lit::Str(Str { value, .. })) if is_jsx_text(value) => {
+			JSXElementChild::JSXText(JSXText {
+				span: DUMMY_SP,
+				value: value.clone(),
+				raw: value.clone(),
+			})
+		}
+		Expr::JSXElement(el) => JSXElementChild::JSXElement(el.clone()),
+		Expr::JSXFragment(frag) => JSXElementChild::JSXFragment(frag.clone()),
+		_ => JSXElementChild::JSXExprContainer(JSXExprContainer {
+			span: DUMMY_SP,
+			expr: JSXExpr::Expr(Box::new(expr.clone())),
+		}),
+	}
+}
+
+// JSX strings don't support escapes
+fn is_jsx_attr_str(s: &str) -> bool {
+	s.chars().all(|c| c.is_ascii() && !c.is_ascii_control() && c != '"' && c != '\\')
+}
+
+// JSX text trims the whitespaces at the line boundaries
+fn is_jsx_text(s: &str) -> bool {
+	!s.is_empty()
+		&& s.trim() == s
+		&& s.chars().all(|c| {
+			c.is_ascii()
+				&& !c.is_ascii_control()
+				&& !matches!(c, '{' | '}' | '<' | '>' | '&' | '"' | '\\')
+		})
+}
+
+// match: React.createElement
+fn is_path(expr: &Expr, path: &[String]) -> bool {
+	match (expr, path) {
+		(Expr::Ident(id), [name]) => id.sym.as_ref() == name,
+		(
+			Expr::Member(MemberExpr {
+				obj: ExprOrSuper::Expr(obj),
+				prop,
+				computed: false,
+				..
+			}),
+			[rest @ .., name],
+		) if !rest.is_empty() => match prop.as_ref() {
+			Expr::Ident(prop) => prop.sym.as_ref() == name && is_path(obj, rest),
+			_ => false,
+		},
+		_ => false,
+	}
+}
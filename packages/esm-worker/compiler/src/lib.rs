@@ -1,18 +1,71 @@
+mod analysis;
+mod ast_dump;
+mod ast_utils;
+mod auto_accessor;
+mod cjs_to_esm;
+mod comment_filter;
+mod constant_fold;
+mod css_extract;
+mod default_export_name;
+mod directives;
+mod dynamic_imports;
+mod emit_options;
 mod error;
+mod error_overlay;
 mod export_names;
+mod export_shape;
+mod facade;
+mod forbidden;
+mod glob_import;
+mod gzip_size;
+mod import_attributes;
 mod import_map;
+mod inject;
+mod inline_constants;
+mod jsx_components;
+mod jsx_restore;
+mod output_format;
+mod pure_calls;
+mod reexports;
+mod require_in_esm;
 mod resolve_fold;
 mod resolver;
+mod scope_prefix;
+mod shared_helpers;
+mod side_effects;
 mod source_type;
+mod star_exports;
+mod strip_ambient;
+mod strip_hook;
 mod swc;
+mod target;
+mod target_query;
+mod text_writer;
+mod translation_strings;
+mod tree_shake_locals;
+mod types_only;
+mod types_stub;
+mod unused_imports;
+mod using_decl;
 
+use comment_filter::CommentFilter;
+use error::Warning;
+use facade::{build_facade, ExportName};
+use gzip_size::gzip_size;
 use import_map::ImportHashMap;
-use resolver::{DependencyDescriptor, InlineStyle, ReactOptions, Resolver};
+use resolver::{
+	BundleOutput, Chunk, DependencyDescriptor, ExportShape, ExtractedCss, ExtractedString,
+	GeneratedImportMap, InlineStyle, ReactOptions, ResolveMode, Resolver, UnresolvedPolicy,
+};
 use serde::{Deserialize, Serialize};
+use shared_helpers::build_shared_helpers;
 use source_type::SourceType;
 use std::collections::HashMap;
 use std::{cell::RefCell, rc::Rc};
 use swc::{EmitOptions, SWC};
+use swc_ecmascript::parser::JscTarget;
+use text_writer::Indent;
+use types_stub::generate_types_stub;
 use wasm_bindgen::prelude::{wasm_bindgen, JsValue};
 
 #[derive(Deserialize)]
@@ -31,13 +84,71 @@ pub struct Options {
 	pub bundle_externals: Vec<String>,
 
 	#[serde(default)]
-	pub is_dev: bool,
+	pub code_splitting: bool,
+
+	#[serde(flatten)]
+	pub emit: EmitOptions,
 
 	#[serde(default)]
-	pub source_map: bool,
+	pub keep_comments: Option<Vec<String>>,
 
 	#[serde(default)]
 	pub react: Option<ReactOptions>,
+
+	#[serde(default)]
+	pub cjs_modules: Vec<String>,
+
+	#[serde(default)]
+	pub extra_query: Option<String>,
+
+	#[serde(default)]
+	pub css_query: Option<String>,
+
+	#[serde(default)]
+	pub output_extensions: HashMap<String, String>,
+
+	#[serde(default)]
+	pub remote_mirrors: Vec<(String, String)>,
+
+	#[serde(default)]
+	pub resolve_mode: ResolveMode,
+
+	#[serde(default)]
+	pub on_unresolved: Option<UnresolvedPolicy>,
+}
+
+impl Options {
+	/// create the resolver of the module with the options.
+	fn resolver(&self, specifier: &str) -> Resolver {
+		let mut resolver = Resolver::new(
+			specifier,
+			self.import_map.clone(),
+			self.bundle_mode,
+			self.bundle_externals.clone(),
+			self.react.clone(),
+		);
+		resolver.code_splitting = self.code_splitting;
+		resolver.cjs_modules = self.cjs_modules.clone().into_iter().collect();
+		resolver.extra_query = self.extra_query.clone();
+		resolver.css_query = self.css_query.clone();
+		resolver.output_extensions = self.output_extensions.clone();
+		resolver.remote_mirrors = self.remote_mirrors.clone();
+		resolver.resolve_mode = self.resolve_mode;
+		resolver.on_unresolved = self.on_unresolved;
+		resolver
+	}
+
+	/// get the emit options of the module with the options.
+	fn emit_options(&self) -> EmitOptions {
+		EmitOptions {
+			jsx_factory: self.swc_options.jsx_factory.clone(),
+			jsx_fragment_factory: self.swc_options.jsx_fragment_factory.clone(),
+			comment_filter: self.keep_comments.clone().map(|markers| {
+				CommentFilter::new(move |text| markers.iter().any(|marker| text.contains(marker)))
+			}),
+			..self.emit.clone()
+		}
+	}
 }
 
 #[derive(Deserialize)]
@@ -51,6 +162,9 @@ pub struct SWCOptions {
 
 	#[serde(default = "default_pragma_frag")]
 	pub jsx_fragment_factory: String,
+
+	#[serde(default)]
+	pub sort_exports: bool,
 }
 
 impl Default for SWCOptions {
@@ -59,6 +173,7 @@ impl Default for SWCOptions {
 			source_type: SourceType::default(),
 			jsx_factory: default_pragma(),
 			jsx_fragment_factory: default_pragma_frag(),
+			sort_exports: false,
 		}
 	}
 }
@@ -83,14 +198,49 @@ pub struct TransformOutput {
 	#[serde(skip_serializing_if = "Vec::is_empty")]
 	pub star_exports: Vec<String>,
 
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub bundle: Option<BundleOutput>,
+
 	#[serde(skip_serializing_if = "HashMap::is_empty")]
 	pub jsx_inline_styles: HashMap<String, InlineStyle>,
 
 	#[serde(skip_serializing_if = "Vec::is_empty")]
 	pub jsx_static_class_names: Vec<String>,
 
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	pub extracted_css: Vec<ExtractedCss>,
+
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	pub extracted_strings: Vec<ExtractedString>,
+
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	pub export_shape: Vec<ExportShape>,
+
 	#[serde(skip_serializing_if = "Option::is_none")]
 	pub map: Option<String>,
+
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	pub warnings: Vec<Warning>,
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	pub ast_dumps: Vec<(String, String)>,
+
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	pub helpers: Vec<String>,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub import_map: Option<GeneratedImportMap>,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub imports: Option<String>,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub body: Option<String>,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub gzip_size: Option<usize>,
+
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	pub chunks: Vec<Chunk>,
 }
 
 #[wasm_bindgen(js_name = "parseModuleExportsSync")]
@@ -107,11 +257,163 @@ pub fn parse_module_exports_sync(
     .unwrap();
   let module =
     SWC::parse(specifier, code, Some(options.source_type)).expect("could not parse module");
-  let export_names = module.parse_export_names().unwrap();
+  let export_names = if options.sort_exports {
+    module.parse_sorted_export_names().unwrap()
+  } else {
+    module.parse_export_names().unwrap()
+  };
 
   Ok(JsValue::from_serde(&export_names).unwrap())
 }
+
+#[wasm_bindgen(js_name = "parseReexportsSync")]
+pub fn parse_reexports_sync(
+  specifier: &str,
+  code: &str,
+  options: JsValue,
+) -> Result<JsValue, JsValue> {
+  console_error_panic_hook::set_once();
+
+  let options: SWCOptions = options
+    .into_serde()
+    .map_err(|err| format!("failed to parse options: {}", err))
+    .unwrap();
+  let module =
+    SWC::parse(specifier, code, Some(options.source_type)).expect("could not parse module");
+  let reexports = module.parse_reexports().unwrap();
+
+  Ok(JsValue::from_serde(&reexports).unwrap())
+}
+
+#[wasm_bindgen(js_name = "generateTypesStubSync")]
+pub fn generate_types_stub_sync(
+  specifier: &str,
+  code: &str,
+  options: JsValue,
+) -> Result<JsValue, JsValue> {
+  console_error_panic_hook::set_once();
+
+  let options: SWCOptions = options
+    .into_serde()
+    .map_err(|err| format!("failed to parse options: {}", err))
+    .unwrap();
+  let module =
+    SWC::parse(specifier, code, Some(options.source_type)).expect("could not parse module");
+  let (names, types) = module.parse_export_names_with_types().unwrap();
+
+  Ok(JsValue::from_str(&generate_types_stub(specifier, &names, &types)))
+}
+
+#[wasm_bindgen(js_name = "buildFacadeSync")]
+pub fn build_facade_sync(target: &str, names: JsValue) -> Result<JsValue, JsValue> {
+  console_error_panic_hook::set_once();
+
+  let names: Vec<ExportName> = names
+    .into_serde()
+    .map_err(|err| format!("failed to parse names: {}", err))
+    .unwrap();
+
+  Ok(JsValue::from_str(&build_facade(target, &names)))
+}
+
+#[wasm_bindgen(js_name = "buildSharedHelpersSync")]
+pub fn build_shared_helpers_sync(names: JsValue) -> Result<JsValue, JsValue> {
+  console_error_panic_hook::set_once();
+
+  let names: Vec<String> = names
+    .into_serde()
+    .map_err(|err| format!("failed to parse names: {}", err))
+    .unwrap();
+
+  Ok(JsValue::from_str(&build_shared_helpers(&names, Indent::default())))
+}
+
+#[wasm_bindgen(js_name = "dumpAstSync")]
+pub fn dump_ast_sync(specifier: &str, code: &str, options: JsValue) -> Result<JsValue, JsValue> {
+  console_error_panic_hook::set_once();
+
+  let options: SWCOptions = options
+    .into_serde()
+    .map_err(|err| format!("failed to parse options: {}", err))
+    .unwrap();
+  let module =
+    SWC::parse(specifier, code, Some(options.source_type)).expect("could not parse module");
+
+  Ok(JsValue::from_str(&module.dump_ast()))
+}
  
+#[wasm_bindgen(js_name = "parseJsxComponentsSync")]
+pub fn parse_jsx_components_sync(
+  specifier: &str,
+  code: &str,
+  options: JsValue,
+) -> Result<JsValue, JsValue> {
+  console_error_panic_hook::set_once();
+
+  let options: SWCOptions = options
+    .into_serde()
+    .map_err(|err| format!("failed to parse options: {}", err))
+    .unwrap();
+  let module =
+    SWC::parse(specifier, code, Some(options.source_type)).expect("could not parse module");
+
+  Ok(JsValue::from_serde(&module.jsx_components()).unwrap())
+}
+
+#[wasm_bindgen(js_name = "parseDynamicImportsSync")]
+pub fn parse_dynamic_imports_sync(
+  specifier: &str,
+  code: &str,
+  options: JsValue,
+) -> Result<JsValue, JsValue> {
+  console_error_panic_hook::set_once();
+
+  let options: SWCOptions = options
+    .into_serde()
+    .map_err(|err| format!("failed to parse options: {}", err))
+    .unwrap();
+  let module =
+    SWC::parse(specifier, code, Some(options.source_type)).expect("could not parse module");
+
+  Ok(JsValue::from_serde(&module.dynamic_imports()).unwrap())
+}
+
+#[wasm_bindgen(js_name = "isSideEffectFreeSync")]
+pub fn is_side_effect_free_sync(
+  specifier: &str,
+  code: &str,
+  options: JsValue,
+) -> Result<JsValue, JsValue> {
+  console_error_panic_hook::set_once();
+
+  let options: SWCOptions = options
+    .into_serde()
+    .map_err(|err| format!("failed to parse options: {}", err))
+    .unwrap();
+  let module =
+    SWC::parse(specifier, code, Some(options.source_type)).expect("could not parse module");
+
+  Ok(JsValue::from_bool(module.is_side_effect_free()))
+}
+
+#[wasm_bindgen(js_name = "hasTopLevelAwaitSync")]
+pub fn has_top_level_await_sync(
+  specifier: &str,
+  code: &str,
+  options: JsValue,
+) -> Result<JsValue, JsValue> {
+  console_error_panic_hook::set_once();
+
+  let options: SWCOptions = options
+    .into_serde()
+    .map_err(|err| format!("failed to parse options: {}", err))
+    .unwrap();
+  let module =
+    SWC::parse(specifier, code, Some(options.source_type)).expect("could not parse module");
+
+  Ok(JsValue::from_bool(module.has_top_level_await()))
+}
+
 #[wasm_bindgen(js_name = "transformSync")]
 pub fn transform_sync(specifier: &str, code: &str, options: JsValue) -> Result<JsValue, JsValue> {
 	console_error_panic_hook::set_once();
@@ -120,37 +422,85 @@ pub fn transform_sync(specifier: &str, code: &str, options: JsValue) -> Result<J
 		.into_serde()
 		.map_err(|err| format!("failed to parse options: {}", err))
 		.unwrap();
-	let resolver = Rc::new(RefCell::new(Resolver::new(
-		specifier,
-		options.import_map,
-		options.bundle_mode,
-		options.bundle_externals,
-		options.react,
-	)));
-	let module = SWC::parse(specifier, code, Some(options.swc_options.source_type))
+	let resolver = Rc::new(RefCell::new(options.resolver(specifier)));
+	let module = SWC::parse(specifier, code, Some(options.swc_options.source_type.clone()))
 		.expect("could not parse the module");
 	let (code, map) = module
-		.transform(
-			resolver.clone(),
-			&EmitOptions {
-				jsx_factory: options.swc_options.jsx_factory.clone(),
-				jsx_fragment_factory: options.swc_options.jsx_fragment_factory.clone(),
-				source_map: options.source_map,
-				is_dev: options.is_dev,
-			},
-		)
+		.transform(resolver.clone(), &options.emit_options())
 		.expect("could not transform the module");
 	let r = resolver.borrow();
+	let gzip_size = options.emit.gzip_size.then(|| gzip_size(&code));
 
 	Ok(
 		JsValue::from_serde(&TransformOutput {
 			code,
 			deps: r.deps.clone(),
 			star_exports: r.star_exports.clone(),
+			bundle: r.bundle_mode.then(|| r.bundle_output.clone()),
 			jsx_inline_styles: r.jsx_inline_styles.clone(),
 			jsx_static_class_names: r.jsx_static_class_names.clone().into_iter().collect(),
+			extracted_css: r.extracted_css.clone(),
+			extracted_strings: r.extracted_strings.clone(),
+			export_shape: r.export_shape.clone(),
 			map,
+			warnings: r.warnings.clone(),
+			ast_dumps: r.ast_dumps.clone(),
+			helpers: r.helpers.clone().into_iter().collect(),
+			import_map: options
+				.emit
+				.generate_import_map
+				.then(|| r.build_import_map())
+				.filter(|map| !map.imports.is_empty()),
+			imports: r.split_imports.as_ref().map(|(imports, _)| imports.clone()),
+			body: r.split_imports.as_ref().map(|(_, body)| body.clone()),
+			gzip_size,
+			chunks: r.chunks.clone(),
 		})
 		.unwrap(),
 	)
 }
+
+#[wasm_bindgen(js_name = "transformTargetsSync")]
+pub fn transform_targets_sync(
+	specifier: &str,
+	code: &str,
+	options: JsValue,
+	targets: JsValue,
+) -> Result<JsValue, JsValue> {
+	console_error_panic_hook::set_once();
+
+	let options: Options = options
+		.into_serde()
+		.map_err(|err| format!("failed to parse options: {}", err))
+		.unwrap();
+	let targets: Vec<JscTarget> = targets
+		.into_serde()
+		.map_err(|err| format!("failed to parse targets: {}", err))
+		.unwrap();
+	let resolver = Rc::new(RefCell::new(options.resolver(specifier)));
+	let module = SWC::parse(specifier, code, Some(options.swc_options.source_type.clone()))
+		.expect("could not parse the module");
+	let outputs = module
+		.transform_targets(resolver, &options.emit_options(), targets)
+		.expect("could not transform the module");
+
+	Ok(JsValue::from_serde(&outputs).unwrap())
+}
+
+#[wasm_bindgen(js_name = "analyzeSync")]
+pub fn analyze_sync(specifier: &str, code: &str, options: JsValue) -> Result<JsValue, JsValue> {
+	console_error_panic_hook::set_once();
+
+	let options: Options = options
+		.into_serde()
+		.map_err(|err| format!("failed to parse options: {}", err))
+		.unwrap();
+	let resolver = Rc::new(RefCell::new(options.resolver(specifier)));
+	let module = SWC::parse(specifier, code, Some(options.swc_options.source_type.clone()))
+		.expect("could not parse the module");
+	let report = module
+		.analyze(resolver, &options.emit_options())
+		.expect("could not analyze the module");
+
+	Ok(JsValue::from_serde(&report).unwrap())
+}
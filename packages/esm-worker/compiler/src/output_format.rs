@@ -0,0 +1,755 @@
+use crate::forbidden::has_top_level_await;
+use serde::{Deserialize, Deserializer};
+use std::{cell::RefCell, rc::Rc};
+use swc_common::{chain, Mark, DUMMY_SP};
+use swc_ecma_ast::*;
+use swc_ecma_utils::{find_ids, ident::IdentLike, quote_ident, IsDirective};
+use swc_ecma_visit::{noop_fold_type, Fold, FoldWith, Node, Visit, VisitWith};
+use swc_ecmascript::transforms::{
+	modules::{common_js, import_analysis::import_analyzer, util::Scope},
+	pass::Optional,
+};
+
+/// The module format of the emitted code.
+#[derive(Debug, Clone, Default, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum OutputFormat {
+	/// ES module.
+	#[default]
+	Esm,
+	/// CommonJS module, imports are loaded with `require`. The body of the module with the
+	/// top-level await is wrapped in an async function, `module.exports` is the promise of the
+	/// exports object.
+	Cjs,
+	/// A function expression invoked immediately, returns the exports object. The function is
+	/// async if the module has the top-level await, it returns the promise of the exports object.
+	Iife,
+	/// A module registered with the custom function of the host runtime, like SystemJS:
+	/// ```js
+	/// __register__("id", ["./dep.js"], function(require, exports) { ... });
+	/// ```
+	/// The dependencies are the specifiers loaded with `require` in the CommonJS body, the
+	/// `register_fn` can be a member expression like `runtime.register`. The factory is async if
+	/// the module has the top-level await.
+	#[serde(rename_all = "camelCase")]
+	CustomRegister {
+		#[serde(deserialize_with = "deserialize_register_fn")]
+		register_fn: String,
+		module_id: String,
+	},
+}
+
+// the empty names of `register_fn` would be emitted as the empty identifiers: `runtime.()`
+fn deserialize_register_fn<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+	let register_fn = String::deserialize(deserializer)?;
+	if register_fn.split('.').any(|name| name.trim().is_empty()) {
+		return Err(serde::de::Error::custom(format!(
+			"invalid registerFn \"{}\": expected a function name like \"runtime.register\"",
+			register_fn
+		)));
+	}
+	Ok(register_fn)
+}
+
+/// Convert the ES module into the module system of the `format`, this pass requires the
+/// `resolver` pass with the `top_level_mark` for non-ESM formats. The converted module is
+/// wrapped by the `output_wrapper_fold` after the helpers are injected, so the helpers are
+/// defined inside the wrappers.
+/// If `live_bindings` is set, the `let`/`var` exports of non-ESM formats are defined as getters
+/// on the exports object, so the reassignments are seen by the importers like ESM.
+/// If `esmodule_interop` is set, the exports object of non-ESM formats is marked with the
+/// `__esModule` property for the default-interop helpers of the CommonJS consumers.
+pub fn output_format_fold(
+	format: &OutputFormat,
+	live_bindings: bool,
+	esmodule_interop: bool,
+	top_level_mark: Mark,
+) -> impl Fold {
+	let scope = Rc::new(RefCell::new(Scope::default()));
+	let non_esm = *format != OutputFormat::Esm;
+	let bindings = Rc::new(RefCell::new(vec![]));
+	chain!(
+		Optional::new(DefaultClassFold {}, non_esm),
+		Optional::new(
+			LiveBindingsFold {
+				bindings: bindings.clone()
+			},
+			non_esm && live_bindings
+		),
+		Optional::new(import_analyzer(scope.clone()), non_esm),
+		Optional::new(
+			common_js(
+				top_level_mark,
+				common_js::Config {
+					// the directive is emitted by the `OutputFold`
+					strict_mode: false,
+					// the `strict` mode omits the `__esModule` marker
+					strict: !esmodule_interop,
+					..Default::default()
+				},
+				Some(scope)
+			),
+			non_esm
+		),
+		Optional::new(
+			LiveBindingGettersFold {
+				bindings,
+				esmodule_interop
+			},
+			non_esm && live_bindings
+		)
+	)
+}
+
+/// Wrap the module converted by the `output_format_fold` for the `format`, like the function of
+/// the IIFE output. The `import.meta` is shimmed for non-ESM formats, the `import_meta_url` is
+/// used as the `import.meta.url` of the IIFE or the custom-register output. The `"use strict"`
+/// directive is emitted by default for non-ESM formats.
+/// If `freeze_exports` is set, the exports object of non-ESM formats is frozen after it's
+/// populated, the `let`/`var` exports must be converted with the `live_bindings` since the
+/// frozen object can't be reassigned.
+pub fn output_wrapper_fold(
+	format: OutputFormat,
+	global_name: Option<String>,
+	use_strict: Option<bool>,
+	import_meta_url: Option<String>,
+	freeze_exports: bool,
+) -> impl Fold {
+	// ESM is always strict, omit the directive by default
+	let use_strict = use_strict.unwrap_or(format != OutputFormat::Esm);
+	OutputFold {
+		format,
+		global_name,
+		use_strict,
+		import_meta_url,
+		freeze_exports,
+	}
+}
+
+struct OutputFold {
+	format: OutputFormat,
+	global_name: Option<String>,
+	use_strict: bool,
+	import_meta_url: Option<String>,
+	freeze_exports: bool,
+}
+
+impl Fold for OutputFold {
+	noop_fold_type!();
+
+	fn fold_module(&mut self, module: Module) -> Module {
+		// the wrappers of the module with the top-level await are async functions
+		let is_async = self.format != OutputFormat::Esm && has_top_level_await(&module);
+		let mut body: Vec<ModuleItem> = module
+			.body
+			.into_iter()
+			.filter(|item| !matches!(item, ModuleItem::Stmt(stmt) if stmt.is_use_strict()))
+			.collect();
+		if self.format != OutputFormat::Esm {
+			body = body.fold_with(&mut ImportMetaFold {
+				url: self.import_meta_url_expr(),
+			});
+			if self.freeze_exports {
+				body.push(ModuleItem::Stmt(freeze_exports()));
+			}
+		}
+		match &self.format {
+			OutputFormat::Cjs if is_async => {
+				// module.exports = (async function() { ...; return exports; })()
+				let mut stmts: Vec<Stmt> = body
+					.into_iter()
+					.filter_map(|item| match item {
+						ModuleItem::Stmt(stmt) => Some(stmt),
+						_ => None,
+					})
+					.collect();
+				stmts.push(Stmt::Return(ReturnStmt {
+					span: DUMMY_SP,
+					arg: Some(Box::new(Expr::Ident(quote_ident!("exports")))),
+				}));
+				let mut body = vec![];
+				if self.use_strict {
+					body.push(ModuleItem::Stmt(use_strict()));
+				}
+				body.push(ModuleItem::Stmt(Stmt::Expr(ExprStmt {
+					span: DUMMY_SP,
+					expr: Box::new(Expr::Assign(AssignExpr {
+						span: DUMMY_SP,
+						op: AssignOp::Assign,
+						left: PatOrExpr::Expr(Box::new(Expr::Member(MemberExpr {
+							span: DUMMY_SP,
+							obj: ExprOrSuper::Expr(Box::new(Expr::Ident(quote_ident!("module")))),
+							prop: Box::new(Expr::Ident(quote_ident!("exports"))),
+							computed: false,
+						}))),
+						right: Box::new(invoke(fn_expr(vec![], stmts, true))),
+					})),
+				})));
+				Module { body, ..module }
+			}
+			OutputFormat::Esm | OutputFormat::Cjs => {
+				if self.use_strict {
+					body.insert(0, ModuleItem::Stmt(use_strict()));
+				}
+				Module { body, ..module }
+			}
+			OutputFormat::Iife => {
+				// (function() { "use strict"; var exports = {}; ...; return exports; })()
+				let mut stmts: Vec<Stmt> = vec![];
+				if self.use_strict {
+					stmts.push(use_strict());
+				}
+				stmts.push(Stmt::Decl(Decl::Var(VarDecl {
+					span: DUMMY_SP,
+					kind: VarDeclKind::Var,
+					declare: false,
+					decls: vec![VarDeclarator {
+						span: DUMMY_SP,
+						name: Pat::Ident(BindingIdent {
+							id: quote_ident!("exports"),
+							type_ann: None,
+						}),
+						init: Some(Box::new(Expr::Object(ObjectLit {
+							span: DUMMY_SP,
+							props: vec![],
+						}))),
+						definite: false,
+					}],
+				})));
+				for item in body {
+					if let ModuleItem::Stmt(stmt) = item {
+						stmts.push(stmt);
+					}
+				}
+				stmts.push(Stmt::Return(ReturnStmt {
+					span: DUMMY_SP,
+					arg: Some(Box::new(Expr::Ident(quote_ident!("exports")))),
+				}));
+				let iife = invoke(fn_expr(vec![], stmts, is_async));
+				let stmt = match &self.global_name {
+					Some(name) => Stmt::Decl(Decl::Var(VarDecl {
+						span: DUMMY_SP,
+						kind: VarDeclKind::Var,
+						declare: false,
+						decls: vec![VarDeclarator {
+							span: DUMMY_SP,
+							name: Pat::Ident(BindingIdent {
+								id: quote_ident!(name.as_str()),
+								type_ann: None,
+							}),
+							init: Some(Box::new(iife)),
+							definite: false,
+						}],
+					})),
+					None => Stmt::Expr(ExprStmt {
+						span: DUMMY_SP,
+						expr: Box::new(iife),
+					}),
+				};
+				Module {
+					body: vec![ModuleItem::Stmt(stmt)],
+					..module
+				}
+			}
+			OutputFormat::CustomRegister {
+				register_fn,
+				module_id,
+			} => {
+				// __register__("id", ["./dep.js"], function(require, exports) { "use strict"; ... })
+				let mut stmts: Vec<Stmt> = vec![];
+				if self.use_strict {
+					stmts.push(use_strict());
+				}
+				for item in body {
+					if let ModuleItem::Stmt(stmt) = item {
+						stmts.push(stmt);
+					}
+				}
+				let mut collector = RequireCollector {
+					require: None,
+					deps: vec![],
+				};
+				stmts.visit_with(&Invalid { span: DUMMY_SP } as _, &mut collector);
+				let callee = register_fn
+					.split('.')
+					.map(|name| Expr::Ident(quote_ident!(name)))
+					.reduce(|obj, prop| {
+						Expr::Member(MemberExpr {
+							span: DUMMY_SP,
+							obj: ExprOrSuper::Expr(Box::new(obj)),
+							prop: Box::new(prop),
+							computed: false,
+						})
+					})
+					.unwrap();
+				let deps = Expr::Array(ArrayLit {
+					span: DUMMY_SP,
+					elems: collector
+						.deps
+						.iter()
+						.map(|dep| {
+							Some(ExprOrSpread {
+								spread: None,
+								expr: Box::new(Expr::Lit(Lit::Str(quote_str(dep)))),
+							})
+						})
+						.collect(),
+				});
+				// the `require` parameter binds the references of the `common_js` pass
+				let require = collector.require.unwrap_or_else(|| quote_ident!("require"));
+				let factory = fn_expr(
+					vec![require, quote_ident!("exports")]
+						.into_iter()
+						.map(|id| Param {
+							span: DUMMY_SP,
+							decorators: vec![],
+							pat: Pat::Ident(id.into()),
+						})
+						.collect(),
+					stmts,
+					is_async,
+				);
+				let stmt = Stmt::Expr(ExprStmt {
+					span: DUMMY_SP,
+					expr: Box::new(Expr::Call(CallExpr {
+						span: DUMMY_SP,
+						callee: ExprOrSuper::Expr(Box::new(callee)),
+						args: vec![Expr::Lit(Lit::Str(quote_str(module_id))), deps, factory]
+							.into_iter()
+							.map(|arg| ExprOrSpread {
+								spread: None,
+								expr: Box::new(arg),
+							})
+							.collect(),
+						type_args: None,
+					})),
+				});
+				Module {
+					body: vec![ModuleItem::Stmt(stmt)],
+					..module
+				}
+			}
+		}
+	}
+}
+
+/// collect the specifiers loaded with `require("...")`, in the order of the first occurrence.
+struct RequireCollector {
+	// the first `require` reference
+	require: Option<Ident>,
+	deps: Vec<String>,
+}
+
+impl Visit for RequireCollector {
+	fn visit_call_expr(&mut self, n: &CallExpr, _: &dyn Node) {
+		n.visit_children_with(self);
+		if let (ExprOrSuper::Expr(callee), [arg]) = (&n.callee, n.args.as_slice()) {
+			if let (Expr::Ident(id), Expr::Lit(Lit::Str(Str { value, .. }))) =
+				(callee.as_ref(), arg.expr.as_ref())
+			{
+				let is_new = !self.deps.iter().any(|dep| dep == value.as_ref());
+				if id.sym.as_ref() == "require" && is_new {
+					self.require.get_or_insert_with(|| id.clone());
+					self.deps.push(value.as_ref().into());
+				}
+			}
+		}
+	}
+}
+
+impl OutputFold {
+	fn import_meta_url_expr(&self) -> Expr {
+		// the scripts loaded in the browser
+		let is_script = matches!(
+			self.format,
+			OutputFormat::Iife | OutputFormat::CustomRegister { .. }
+		);
+		match (is_script, &self.import_meta_url) {
+			(true, Some(url)) => Expr::Lit(Lit::Str(quote_str(url))),
+			// location.href
+			(true, None) => Expr::Member(MemberExpr {
+				span: DUMMY_SP,
+				obj: ExprOrSuper::Expr(Box::new(Expr::Ident(quote_ident!("location")))),
+				prop: Box::new(Expr::Ident(quote_ident!("href"))),
+				computed: false,
+			}),
+			// require("url").pathToFileURL(__filename).href
+			_ => {
+				let require_url = Expr::Call(CallExpr {
+					span: DUMMY_SP,
+					callee: ExprOrSuper::Expr(Box::new(Expr::Ident(quote_ident!("require")))),
+					args: vec![ExprOrSpread {
+						spread: None,
+						expr: Box::new(Expr::Lit(Lit::Str(quote_str("url")))),
+					}],
+					type_args: None,
+				});
+				let file_url = Expr::Call(CallExpr {
+					span: DUMMY_SP,
+					callee: ExprOrSuper::Expr(Box::new(Expr::Member(MemberExpr {
+						span: DUMMY_SP,
+						obj: ExprOrSuper::Expr(Box::new(require_url)),
+						prop: Box::new(Expr::Ident(quote_ident!("pathToFileURL"))),
+						computed: false,
+					}))),
+					args: vec![ExprOrSpread {
+						spread: None,
+						expr: Box::new(Expr::Ident(quote_ident!("__filename"))),
+					}],
+					type_args: None,
+				});
+				Expr::Member(MemberExpr {
+					span: DUMMY_SP,
+					obj: ExprOrSuper::Expr(Box::new(file_url)),
+					prop: Box::new(Expr::Ident(quote_ident!("href"))),
+					computed: false,
+				})
+			}
+		}
+	}
+}
+
+/// Split the named default class(`export default class Foo {}`) into the declaration and the
+/// export before the `common_js` pass, so the local `Foo` binding is kept in the module scope:
+/// ```js
+/// class Foo {}
+/// exports.default = Foo;
+/// ```
+/// The default function declarations are hoisted by the `common_js` pass with the local binding.
+struct DefaultClassFold {}
+
+impl Fold for DefaultClassFold {
+	noop_fold_type!();
+
+	fn fold_module_items(&mut self, items: Vec<ModuleItem>) -> Vec<ModuleItem> {
+		let mut output: Vec<ModuleItem> = Vec::with_capacity(items.len());
+		for item in items {
+			match item {
+				ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultDecl(ExportDefaultDecl {
+					span,
+					decl: DefaultDecl::Class(ClassExpr {
+						ident: Some(ident),
+						class,
+					}),
+				})) => {
+					output.push(ModuleItem::Stmt(Stmt::Decl(Decl::Class(ClassDecl {
+						ident: ident.clone(),
+						declare: false,
+						class,
+					}))));
+					// match: export { Foo as default }
+					output.push(ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(NamedExport {
+						span,
+						specifiers: vec![ExportSpecifier::Named(ExportNamedSpecifier {
+							span: DUMMY_SP,
+							orig: ident,
+							exported: Some(quote_ident!("default")),
+							is_type_only: false,
+						})],
+						src: None,
+						type_only: false,
+						asserts: None,
+					})));
+				}
+				_ => output.push(item),
+			}
+		}
+		output
+	}
+}
+
+/// Remove the exports of the `let`/`var` bindings before the `common_js` pass, the bindings are
+/// recorded as `(exported name, local)` for the `LiveBindingGettersFold`.
+struct LiveBindingsFold {
+	bindings: Rc<RefCell<Vec<(String, Ident)>>>,
+}
+
+impl Fold for LiveBindingsFold {
+	noop_fold_type!();
+
+	fn fold_module_items(&mut self, items: Vec<ModuleItem>) -> Vec<ModuleItem> {
+		// the top-level `let`/`var` bindings
+		let mut mutable_ids: Vec<Ident> = vec![];
+		for item in &items {
+			match item {
+				ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl {
+					decl: Decl::Var(var), ..
+				}))
+				| ModuleItem::Stmt(Stmt::Decl(Decl::Var(var))) => {
+					if var.kind != VarDeclKind::Const {
+						mutable_ids.extend(find_ids::<_, Ident>(&var.decls));
+					}
+				}
+				_ => {}
+			}
+		}
+		let is_mutable = |id: &Ident| mutable_ids.iter().any(|m| m.to_id() == id.to_id());
+
+		let mut bindings = self.bindings.borrow_mut();
+		let mut output: Vec<ModuleItem> = Vec::with_capacity(items.len());
+		for item in items {
+			match item {
+				// match: export let x = 1
+				ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl {
+					decl: Decl::Var(var), ..
+				})) if var.kind != VarDeclKind::Const => {
+					for id in find_ids::<_, Ident>(&var.decls) {
+						bindings.push((id.sym.as_ref().into(), id));
+					}
+					output.push(ModuleItem::Stmt(Stmt::Decl(Decl::Var(var))));
+				}
+				// match: export { x, y as z }
+				ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(NamedExport {
+					span,
+					specifiers,
+					src: None,
+					type_only: false,
+					asserts,
+				})) => {
+					let specifiers: Vec<ExportSpecifier> = specifiers
+						.into_iter()
+						.filter(|specifier| match specifier {
+							ExportSpecifier::Named(ExportNamedSpecifier { orig, exported, .. })
+								if is_mutable(orig) =>
+							{
+								let name = exported.as_ref().unwrap_or(orig).sym.as_ref().into();
+								bindings.push((name, orig.clone()));
+								false
+							}
+							_ => true,
+						})
+						.collect();
+					if !specifiers.is_empty() {
+						output.push(ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(NamedExport {
+							span,
+							specifiers,
+							src: None,
+							type_only: false,
+							asserts,
+						})));
+					}
+				}
+				_ => output.push(item),
+			}
+		}
+		output
+	}
+}
+
+/// Define the getters of the bindings recorded by the `LiveBindingsFold` after the `__esModule`
+/// marker added by the `common_js` pass, the marker is added if the module has no other exports
+/// and the `esmodule_interop` is set.
+/// ```js
+/// Object.defineProperty(exports, "x", { enumerable: true, get: () => x });
+/// ```
+struct LiveBindingGettersFold {
+	bindings: Rc<RefCell<Vec<(String, Ident)>>>,
+	esmodule_interop: bool,
+}
+
+impl Fold for LiveBindingGettersFold {
+	noop_fold_type!();
+
+	fn fold_module_items(&mut self, mut items: Vec<ModuleItem>) -> Vec<ModuleItem> {
+		let bindings = self.bindings.take();
+		if bindings.is_empty() {
+			return items;
+		}
+		let mut getters: Vec<ModuleItem> = vec![];
+		let pos = match items.iter().position(is_es_module_marker) {
+			Some(pos) => pos + 1,
+			None => {
+				if self.esmodule_interop {
+					getters.push(ModuleItem::Stmt(define_exports_property(
+						"__esModule",
+						vec![("value", Expr::Lit(Lit::Bool(Bool { span: DUMMY_SP, value: true })))],
+					)));
+				}
+				0
+			}
+		};
+		for (name, local) in bindings {
+			let getter = Expr::Arrow(ArrowExpr {
+				span: DUMMY_SP,
+				params: vec![],
+				body: BlockStmtOrExpr::Expr(Box::new(Expr::Ident(local))),
+				is_async: false,
+				is_generator: false,
+				type_params: None,
+				return_type: None,
+			});
+			getters.push(ModuleItem::Stmt(define_exports_property(
+				name.as_str(),
+				vec![
+					("enumerable", Expr::Lit(Lit::Bool(Bool { span: DUMMY_SP, value: true }))),
+					("get", getter),
+				],
+			)));
+		}
+		items.splice(pos..pos, getters);
+		items
+	}
+}
+
+// match: Object.defineProperty(exports, "__esModule", { value: true })
+fn is_es_module_marker(item: &ModuleItem) -> bool {
+	match item {
+		ModuleItem::Stmt(Stmt::Expr(ExprStmt { expr, .. })) => match expr.as_ref() {
+			Expr::Call(CallExpr { args, .. }) if args.len() == 3 => matches!(
+				args[1].expr.as_ref(),
+				Expr::Lit(Lit::Str(Str { value, .. })) if value.as_ref() == "__esModule"
+			),
+			_ => false,
+		},
+		_ => false,
+	}
+}
+
+// match: Object.defineProperty(exports, "name", { ...props })
+fn define_exports_property(name: &str, props: Vec<(&str, Expr)>) -> Stmt {
+	let args = vec![
+		Expr::Ident(quote_ident!("exports")),
+		Expr::Lit(Lit::Str(quote_str(name))),
+		Expr::Object(ObjectLit {
+			span: DUMMY_SP,
+			props: props
+				.into_iter()
+				.map(|(key, value)| {
+					PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+						key: PropName::Ident(quote_ident!(key)),
+						value: Box::new(value),
+					})))
+				})
+				.collect(),
+		}),
+	];
+	Stmt::Expr(ExprStmt {
+		span: DUMMY_SP,
+		expr: Box::new(Expr::Call(CallExpr {
+			span: DUMMY_SP,
+			callee: ExprOrSuper::Expr(Box::new(Expr::Member(MemberExpr {
+				span: DUMMY_SP,
+				obj: ExprOrSuper::Expr(Box::new(Expr::Ident(quote_ident!("Object")))),
+				prop: Box::new(Expr::Ident(quote_ident!("defineProperty"))),
+				computed: false,
+			}))),
+			args: args
+				.into_iter()
+				.map(|arg| ExprOrSpread {
+					spread: None,
+					expr: Box::new(arg),
+				})
+				.collect(),
+			type_args: None,
+		})),
+	})
+}
+
+/// Replace the `import.meta.url` with the `url` expression, and the other usages of the
+/// `import.meta` with the `{ url }` object.
+struct ImportMetaFold {
+	url: Expr,
+}
+
+impl Fold for ImportMetaFold {
+	noop_fold_type!();
+
+	fn fold_expr(&mut self, expr: Expr) -> Expr {
+		match expr {
+			// match: import.meta.url
+			Expr::Member(MemberExpr {
+				obj: ExprOrSuper::Expr(obj),
+				prop,
+				computed: false,
+				..
+			}) if is_import_meta(&obj)
+				&& matches!(prop.as_ref(), Expr::Ident(id) if id.sym.as_ref() == "url") =>
+			{
+				self.url.clone()
+			}
+			// match: import.meta
+			expr if is_import_meta(&expr) => Expr::Object(ObjectLit {
+				span: DUMMY_SP,
+				props: vec![PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+					key: PropName::Ident(quote_ident!("url")),
+					value: Box::new(self.url.clone()),
+				})))],
+			}),
+			_ => expr.fold_children_with(self),
+		}
+	}
+}
+
+fn is_import_meta(expr: &Expr) -> bool {
+	matches!(expr, Expr::MetaProp(MetaPropExpr { meta, prop })
+		if meta.sym.as_ref() == "import" && prop.sym.as_ref() == "meta")
+}
+
+fn quote_str(s: &str) -> Str {
+	Str {
+		span: DUMMY_SP,
+		value: s.into(),
+		has_escape: false,
+		kind: Default::default(),
+	}
+}
+
+// match: Object.freeze(exports)
+fn freeze_exports() -> Stmt {
+	Stmt::Expr(ExprStmt {
+		span: DUMMY_SP,
+		expr: Box::new(Expr::Call(CallExpr {
+			span: DUMMY_SP,
+			callee: ExprOrSuper::Expr(Box::new(Expr::Member(MemberExpr {
+				span: DUMMY_SP,
+				obj: ExprOrSuper::Expr(Box::new(Expr::Ident(quote_ident!("Object")))),
+				prop: Box::new(Expr::Ident(quote_ident!("freeze"))),
+				computed: false,
+			}))),
+			args: vec![ExprOrSpread {
+				spread: None,
+				expr: Box::new(Expr::Ident(quote_ident!("exports"))),
+			}],
+			type_args: None,
+		})),
+	})
+}
+
+fn fn_expr(params: Vec<Param>, stmts: Vec<Stmt>, is_async: bool) -> Expr {
+	Expr::Fn(FnExpr {
+		ident: None,
+		function: Function {
+			params,
+			decorators: vec![],
+			span: DUMMY_SP,
+			body: Some(BlockStmt {
+				span: DUMMY_SP,
+				stmts,
+			}),
+			is_generator: false,
+			is_async,
+			type_params: None,
+			return_type: None,
+		},
+	})
+}
+
+// match: (function() {})()
+fn invoke(function: Expr) -> Expr {
+	Expr::Call(CallExpr {
+		span: DUMMY_SP,
+		callee: ExprOrSuper::Expr(Box::new(Expr::Paren(ParenExpr {
+			span: DUMMY_SP,
+			expr: Box::new(function),
+		}))),
+		args: vec![],
+		type_args: None,
+	})
+}
+
+fn use_strict() -> Stmt {
+	Stmt::Expr(ExprStmt {
+		span: DUMMY_SP,
+		expr: Box::new(Expr::Lit(Lit::Str(quote_str("use strict")))),
+	})
+}
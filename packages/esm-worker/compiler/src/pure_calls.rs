@@ -0,0 +1,89 @@
+use crate::ast_utils::get_callee_name;
+use swc_common::DUMMY_SP;
+use swc_ecma_ast::*;
+use swc_ecma_utils::ExprExt;
+use swc_ecma_visit::{noop_fold_type, Fold, FoldWith};
+
+/// Drop the expression statements calling the known-pure functions, like `Object.freeze({})` with
+/// `Object.freeze` listed in the `names`, the results of the calls are unused. The calls are kept
+/// if any of the arguments may have side effects, the nested calls of the pure functions are
+/// side-effect free. This pass runs with the `dce` pass which removes the other unused code.
+///
+/// Only the expression statements are dropped, the `dce` pass turns the unused declarations like
+/// `const frozen = Object.freeze({})` into the expression statements of their initializers, so
+/// this pass runs again after it.
+pub fn pure_calls_fold(names: Vec<String>) -> impl Fold {
+	PureCallsFold { names }
+}
+
+struct PureCallsFold {
+	names: Vec<String>,
+}
+
+impl PureCallsFold {
+	fn is_pure_call(&self, expr: &Expr) -> bool {
+		match expr {
+			Expr::Call(CallExpr {
+				callee: ExprOrSuper::Expr(callee),
+				args,
+				..
+			}) => match get_callee_name(callee) {
+				Some(name) if self.names.contains(&name) => args
+					.iter()
+					.all(|arg| arg.spread.is_none() && !self.may_have_side_effects(&arg.expr)),
+				_ => false,
+			},
+			_ => false,
+		}
+	}
+
+	fn may_have_side_effects(&self, expr: &Expr) -> bool {
+		match expr {
+			Expr::Call(_) => !self.is_pure_call(expr),
+			Expr::Paren(ParenExpr { expr, .. }) => self.may_have_side_effects(expr),
+			Expr::Array(ArrayLit { elems, .. }) => elems
+				.iter()
+				.flatten()
+				.any(|elem| self.may_have_side_effects(&elem.expr)),
+			Expr::Object(ObjectLit { props, .. }) => props.iter().any(|prop| match prop {
+				PropOrSpread::Prop(prop) => match prop.as_ref() {
+					Prop::KeyValue(KeyValueProp {
+						key: PropName::Computed(_),
+						..
+					}) => true,
+					Prop::KeyValue(KeyValueProp { value, .. }) => self.may_have_side_effects(value),
+					Prop::Shorthand(_) => false,
+					_ => true,
+				},
+				PropOrSpread::Spread(_) => true,
+			}),
+			expr => expr.may_have_side_effects(),
+		}
+	}
+}
+
+impl Fold for PureCallsFold {
+	noop_fold_type!();
+
+	fn fold_stmt(&mut self, stmt: Stmt) -> Stmt {
+		let stmt = stmt.fold_children_with(self);
+		match &stmt {
+			Stmt::Expr(ExprStmt { expr, .. }) if self.is_pure_call(expr) => {
+				Stmt::Empty(EmptyStmt { span: DUMMY_SP })
+			}
+			_ => stmt,
+		}
+	}
+
+	fn fold_stmts(&mut self, stmts: Vec<Stmt>) -> Vec<Stmt> {
+		let mut stmts = stmts.fold_children_with(self);
+		stmts.retain(|stmt| !matches!(stmt, Stmt::Empty(_)));
+		stmts
+	}
+
+	fn fold_module_items(&mut self, items: Vec<ModuleItem>) -> Vec<ModuleItem> {
+		let mut items = items.fold_children_with(self);
+		items.retain(|item| !matches!(item, ModuleItem::Stmt(Stmt::Empty(_))));
+		items
+	}
+}
@@ -0,0 +1,212 @@
+use std::collections::{HashMap, HashSet};
+use swc_atoms::JsWord;
+use swc_common::DUMMY_SP;
+use swc_ecma_ast::*;
+use swc_ecma_visit::{noop_fold_type, Fold, Node, Visit, VisitWith};
+
+/// Collapse the imported bindings that are only re-exported into the direct re-exports:
+/// ```js
+/// import { x } from "./a.ts"
+/// export { x }
+/// // =>
+/// export { x } from "./a.ts"
+/// ```
+/// The bindings referenced anywhere else in the module, even in the types, are kept. The
+/// re-exports take the place of the imports, the modules are evaluated in the same order.
+pub fn optimize_reexports_fold() -> impl Fold {
+	OptimizeReexportsFold {}
+}
+
+struct OptimizeReexportsFold {}
+
+/// how a binding is imported
+enum Imported {
+	Named(Ident),
+	Default,
+	Namespace,
+}
+
+impl Fold for OptimizeReexportsFold {
+	noop_fold_type!();
+
+	fn fold_module(&mut self, mut module: Module) -> Module {
+		let mut imports: HashMap<JsWord, (Str, Imported)> = HashMap::new();
+		for item in &module.body {
+			if let ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
+				specifiers,
+				src,
+				type_only: false,
+				asserts: None,
+				..
+			})) = item
+			{
+				for specifier in specifiers {
+					let (local, imported) = match specifier {
+						ImportSpecifier::Named(ImportNamedSpecifier {
+							local,
+							imported,
+							is_type_only: false,
+							..
+						}) => {
+							let imported = imported.clone().unwrap_or_else(|| local.clone());
+							(local, Imported::Named(imported))
+						}
+						ImportSpecifier::Named(_) => continue,
+						ImportSpecifier::Default(ImportDefaultSpecifier { local, .. }) => {
+							(local, Imported::Default)
+						}
+						ImportSpecifier::Namespace(ImportStarAsSpecifier { local, .. }) => {
+							(local, Imported::Namespace)
+						}
+					};
+					imports.insert(local.sym.clone(), (src.clone(), imported));
+				}
+			}
+		}
+		if imports.is_empty() {
+			return module;
+		}
+
+		let mut collector = RefCollector {
+			refs: HashSet::new(),
+			exports: HashSet::new(),
+		};
+		module.visit_with(&Invalid { span: DUMMY_SP } as _, &mut collector);
+		// the bindings that are only re-exported
+		let collapsed: HashMap<JsWord, (Str, Imported)> = imports
+			.into_iter()
+			.filter(|(sym, _)| collector.exports.contains(sym) && !collector.refs.contains(sym))
+			.collect();
+		if collapsed.is_empty() {
+			return module;
+		}
+
+		// the exported names of the collapsed bindings: `export { x as y }`
+		let mut exported_names: HashMap<JsWord, Vec<Ident>> = HashMap::new();
+		for item in &module.body {
+			if let ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(NamedExport {
+				specifiers,
+				src: None,
+				..
+			})) = item
+			{
+				for specifier in specifiers {
+					match specifier {
+						ExportSpecifier::Named(ExportNamedSpecifier { orig, exported, .. })
+							if collapsed.contains_key(&orig.sym) =>
+						{
+							let exported = exported.clone().unwrap_or_else(|| orig.clone());
+							exported_names.entry(orig.sym.clone()).or_default().push(exported);
+						}
+						_ => {}
+					}
+				}
+			}
+		}
+
+		let mut body: Vec<ModuleItem> = Vec::with_capacity(module.body.len());
+		for item in module.body {
+			match item {
+				ModuleItem::ModuleDecl(ModuleDecl::Import(mut import)) => {
+					let had_specifiers = !import.specifiers.is_empty();
+					let mut reexports: Vec<ModuleItem> = vec![];
+					import.specifiers.retain(|specifier| {
+						let local = match specifier {
+							ImportSpecifier::Named(ImportNamedSpecifier { local, .. }) => local,
+							ImportSpecifier::Default(ImportDefaultSpecifier { local, .. }) => local,
+							ImportSpecifier::Namespace(ImportStarAsSpecifier { local, .. }) => local,
+						};
+						match collapsed.get(&local.sym) {
+							Some((src, imported)) => {
+								let names = exported_names.remove(&local.sym).unwrap_or_default();
+								for exported in names {
+									reexports.push(create_reexport(src.clone(), imported, exported));
+								}
+								false
+							}
+							None => true,
+						}
+					});
+					// the import is removed if all the bindings are re-exported
+					if !had_specifiers || !import.specifiers.is_empty() {
+						body.push(ModuleItem::ModuleDecl(ModuleDecl::Import(import)));
+					}
+					body.extend(reexports);
+				}
+				// match: export { x, y as z }
+				ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(mut export)) if export.src.is_none() => {
+					export.specifiers.retain(|specifier| match specifier {
+						ExportSpecifier::Named(ExportNamedSpecifier { orig, .. }) => {
+							!collapsed.contains_key(&orig.sym)
+						}
+						_ => true,
+					});
+					if !export.specifiers.is_empty() {
+						body.push(ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(export)));
+					}
+				}
+				_ => body.push(item),
+			}
+		}
+		module.body = body;
+		module
+	}
+}
+
+// match: export { x } from "./a.ts", export * as x from "./a.ts"
+fn create_reexport(src: Str, imported: &Imported, exported: Ident) -> ModuleItem {
+	let specifier = match imported {
+		Imported::Named(name) => ExportSpecifier::Named(ExportNamedSpecifier {
+			span: DUMMY_SP,
+			orig: name.clone(),
+			exported: if name.sym == exported.sym {
+				None
+			} else {
+				Some(exported)
+			},
+			is_type_only: false,
+		}),
+		Imported::Default => ExportSpecifier::Named(ExportNamedSpecifier {
+			span: DUMMY_SP,
+			orig: Ident::new("default".into(), DUMMY_SP),
+			exported: Some(exported),
+			is_type_only: false,
+		}),
+		Imported::Namespace => ExportSpecifier::Namespace(ExportNamespaceSpecifier {
+			span: DUMMY_SP,
+			name: exported,
+		}),
+	};
+	ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(NamedExport {
+		span: DUMMY_SP,
+		specifiers: vec![specifier],
+		src: Some(src),
+		type_only: false,
+		asserts: None,
+	}))
+}
+
+/// collect the referenced names, the local exports(`export { x }`) are collected separately.
+struct RefCollector {
+	refs: HashSet<JsWord>,
+	exports: HashSet<JsWord>,
+}
+
+impl Visit for RefCollector {
+	fn visit_import_decl(&mut self, _: &ImportDecl, _: &dyn Node) {}
+
+	fn visit_named_export(&mut self, n: &NamedExport, _: &dyn Node) {
+		if n.src.is_some() {
+			return;
+		}
+		for specifier in &n.specifiers {
+			if let ExportSpecifier::Named(ExportNamedSpecifier { orig, .. }) = specifier {
+				self.exports.insert(orig.sym.clone());
+			}
+		}
+	}
+
+	fn visit_ident(&mut self, n: &Ident, _: &dyn Node) {
+		self.refs.insert(n.sym.clone());
+	}
+}
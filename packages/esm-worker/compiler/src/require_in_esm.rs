@@ -0,0 +1,326 @@
+use crate::resolve_fold::is_call_expr_by_name;
+use serde::Deserialize;
+use swc_common::{Span, DUMMY_SP};
+use swc_ecma_ast::*;
+use swc_ecma_utils::{collect_decls, private_ident, quote_ident, Id};
+use swc_ecma_visit::{noop_fold_type, Fold, FoldWith, Node, Visit, VisitWith};
+
+/// How the stray `require("x")` calls of an ES module are handled, the CommonJS modules and the
+/// modules declaring a `require` binding (like `createRequire(import.meta.url)`) are kept.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum RequireMode {
+	/// keep the calls.
+	#[default]
+	Leave,
+	/// fail the transform.
+	Error,
+	/// import the modules with the default-or-namespace interop of the CommonJS modules, the
+	/// calls in the async functions are awaited dynamic imports and the top-level
+	/// `const x = require("x")` declarations are replaced with the namespaces of the hoisted static
+	/// imports. The other calls fail the transform, since the static imports would evaluate the
+	/// conditional requires eagerly.
+	DynamicImport,
+}
+
+/// Find the `require("x")` calls of the ES module that fail the transform in the `mode`.
+pub fn find_require_calls(module: &Module, mode: RequireMode) -> Vec<(String, Span)> {
+	if mode == RequireMode::Leave || !is_es_module(module) || has_require_binding(module) {
+		return vec![];
+	}
+	let mut collector = RequireCollector {
+		mode,
+		in_async: false,
+		calls: vec![],
+	};
+	module.visit_with(&Invalid { span: DUMMY_SP } as _, &mut collector);
+	collector.calls
+}
+
+struct RequireCollector {
+	mode: RequireMode,
+	in_async: bool,
+	calls: Vec<(String, Span)>,
+}
+
+impl Visit for RequireCollector {
+	fn visit_module_items(&mut self, items: &[ModuleItem], _: &dyn Node) {
+		for item in items {
+			match get_var_decl(item) {
+				Some(decl) if self.mode == RequireMode::DynamicImport => {
+					for decl in decl.decls.iter() {
+						match decl.init.as_deref() {
+							Some(Expr::Call(call)) if get_require_src(call).is_some() => {}
+							_ => decl.visit_with(&Invalid { span: DUMMY_SP } as _, self),
+						}
+					}
+				}
+				_ => item.visit_with(&Invalid { span: DUMMY_SP } as _, self),
+			}
+		}
+	}
+
+	fn visit_function(&mut self, n: &Function, _: &dyn Node) {
+		let in_async = self.in_async;
+		self.in_async = n.is_async;
+		n.visit_children_with(self);
+		self.in_async = in_async;
+	}
+
+	fn visit_arrow_expr(&mut self, n: &ArrowExpr, _: &dyn Node) {
+		let in_async = self.in_async;
+		self.in_async = n.is_async;
+		n.visit_children_with(self);
+		self.in_async = in_async;
+	}
+
+	fn visit_call_expr(&mut self, n: &CallExpr, _: &dyn Node) {
+		if let Some(src) = get_require_src(n) {
+			match self.mode {
+				RequireMode::DynamicImport if self.in_async => {}
+				RequireMode::DynamicImport => {
+					let message = format!(
+						"`require(\"{}\")` can't be rewritten to an import, {}",
+						src.value, "it's not a top-level `const` declaration or in an async function"
+					);
+					self.calls.push((message, n.span));
+				}
+				_ => {
+					let message =
+						format!("`require(\"{}\")` is not allowed in an ES module", src.value);
+					self.calls.push((message, n.span));
+				}
+			}
+		}
+		n.visit_children_with(self);
+	}
+}
+
+/// Rewrite the `require("x")` calls of the ES module for the `DynamicImport` mode:
+/// ```js
+/// // in the async functions
+/// const x = await import("x").then((m) => m.default !== void 0 ? m.default : m);
+/// // the top-level declarations
+/// import * as _x from "x";
+/// const x = _x.default !== void 0 ? _x.default : _x;
+/// ```
+/// The other calls are reported by `find_require_calls` and kept.
+pub fn require_in_esm_fold() -> impl Fold {
+	RequireInEsmFold {
+		in_async: false,
+		imports: vec![],
+	}
+}
+
+struct RequireInEsmFold {
+	in_async: bool,
+	// the hoisted namespace imports
+	imports: Vec<(Str, Ident)>,
+}
+
+impl RequireInEsmFold {
+	fn get_namespace(&mut self, src: Str) -> Ident {
+		if let Some((_, ns)) = self.imports.iter().find(|(s, _)| s.value == src.value) {
+			return ns.clone();
+		}
+		let ns = private_ident!(get_namespace_name(src.value.as_ref()));
+		self.imports.push((src, ns.clone()));
+		ns
+	}
+}
+
+impl Fold for RequireInEsmFold {
+	noop_fold_type!();
+
+	fn fold_module(&mut self, module: Module) -> Module {
+		if !is_es_module(&module) || has_require_binding(&module) {
+			return module;
+		}
+		let mut module = module.fold_children_with(self);
+		let imports = self.imports.drain(..).map(|(src, ns)| {
+			// match: import * as _x from "x"
+			ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
+				span: DUMMY_SP,
+				specifiers: vec![ImportSpecifier::Namespace(ImportStarAsSpecifier {
+					span: DUMMY_SP,
+					local: ns,
+				})],
+				src,
+				type_only: false,
+				asserts: None,
+			}))
+		});
+		module.body.splice(0..0, imports);
+		module
+	}
+
+	// match: const x = require("x")
+	fn fold_module_items(&mut self, items: Vec<ModuleItem>) -> Vec<ModuleItem> {
+		let mut items = items.fold_children_with(self);
+		for item in items.iter_mut() {
+			if let Some(decl) = get_var_decl_mut(item) {
+				for decl in decl.decls.iter_mut() {
+					let src = match decl.init.as_deref() {
+						Some(Expr::Call(call)) => get_require_src(call),
+						_ => None,
+					};
+					if let Some(src) = src {
+						let ns = self.get_namespace(src);
+						decl.init = Some(Box::new(default_or_namespace(ns)));
+					}
+				}
+			}
+		}
+		items
+	}
+
+	fn fold_function(&mut self, function: Function) -> Function {
+		let in_async = self.in_async;
+		self.in_async = function.is_async;
+		let function = function.fold_children_with(self);
+		self.in_async = in_async;
+		function
+	}
+
+	fn fold_arrow_expr(&mut self, arrow: ArrowExpr) -> ArrowExpr {
+		let in_async = self.in_async;
+		self.in_async = arrow.is_async;
+		let arrow = arrow.fold_children_with(self);
+		self.in_async = in_async;
+		arrow
+	}
+
+	fn fold_expr(&mut self, expr: Expr) -> Expr {
+		let expr = expr.fold_children_with(self);
+		let src = match &expr {
+			Expr::Call(call) => match get_require_src(call) {
+				Some(src) => src,
+				None => return expr,
+			},
+			_ => return expr,
+		};
+		if !self.in_async {
+			return expr;
+		}
+		// match: await import("x").then((m) => ...)
+		let m = private_ident!("m");
+		let import = Expr::Call(CallExpr {
+			span: DUMMY_SP,
+			callee: ExprOrSuper::Expr(Box::new(Expr::Ident(quote_ident!("import")))),
+			args: vec![ExprOrSpread {
+				spread: None,
+				expr: Box::new(Expr::Lit(Lit::Str(src))),
+			}],
+			type_args: None,
+		});
+		let then = Expr::Call(CallExpr {
+			span: DUMMY_SP,
+			callee: ExprOrSuper::Expr(Box::new(Expr::Member(MemberExpr {
+				span: DUMMY_SP,
+				obj: ExprOrSuper::Expr(Box::new(import)),
+				prop: Box::new(Expr::Ident(quote_ident!("then"))),
+				computed: false,
+			}))),
+			args: vec![ExprOrSpread {
+				spread: None,
+				expr: Box::new(Expr::Arrow(ArrowExpr {
+					span: DUMMY_SP,
+					params: vec![Pat::Ident(m.clone().into())],
+					body: BlockStmtOrExpr::Expr(Box::new(default_or_namespace(m))),
+					is_async: false,
+					is_generator: false,
+					type_params: None,
+					return_type: None,
+				})),
+			}],
+			type_args: None,
+		});
+		Expr::Await(AwaitExpr {
+			span: DUMMY_SP,
+			arg: Box::new(then),
+		})
+	}
+}
+
+// match: const x = 1, export const x = 1
+fn get_var_decl(item: &ModuleItem) -> Option<&VarDecl> {
+	match item {
+		ModuleItem::Stmt(Stmt::Decl(Decl::Var(decl)))
+		| ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl {
+			decl: Decl::Var(decl), ..
+		})) if decl.kind == VarDeclKind::Const => Some(decl),
+		_ => None,
+	}
+}
+
+fn get_var_decl_mut(item: &mut ModuleItem) -> Option<&mut VarDecl> {
+	match item {
+		ModuleItem::Stmt(Stmt::Decl(Decl::Var(decl)))
+		| ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl {
+			decl: Decl::Var(decl), ..
+		})) if decl.kind == VarDeclKind::Const => Some(decl),
+		_ => None,
+	}
+}
+
+// match: require("x")
+fn get_require_src(call: &CallExpr) -> Option<Str> {
+	if !is_call_expr_by_name(call, "require") || call.args.len() != 1 {
+		return None;
+	}
+	match &call.args[0] {
+		ExprOrSpread { spread: None, expr } => match expr.as_ref() {
+			Expr::Lit(Lit::Str(src)) => Some(src.clone()),
+			_ => None,
+		},
+		_ => None,
+	}
+}
+
+// match: m.default !== void 0 ? m.default : m
+fn default_or_namespace(ns: Ident) -> Expr {
+	let default = Expr::Member(MemberExpr {
+		span: DUMMY_SP,
+		obj: ExprOrSuper::Expr(Box::new(Expr::Ident(ns.clone()))),
+		prop: Box::new(Expr::Ident(quote_ident!("default"))),
+		computed: false,
+	});
+	Expr::Cond(CondExpr {
+		span: DUMMY_SP,
+		test: Box::new(Expr::Bin(BinExpr {
+			span: DUMMY_SP,
+			op: BinaryOp::NotEqEq,
+			left: Box::new(default.clone()),
+			right: Box::new(Expr::Unary(UnaryExpr {
+				span: DUMMY_SP,
+				op: UnaryOp::Void,
+				arg: Box::new(Expr::Lit(Lit::Num(Number { span: DUMMY_SP, value: 0.0 }))),
+			})),
+		})),
+		cons: Box::new(default),
+		alt: Box::new(Expr::Ident(ns)),
+	})
+}
+
+/// get the name of the namespace from the specifier, like `_path` for `node:path`.
+fn get_namespace_name(specifier: &str) -> String {
+	let name = specifier
+		.trim_end_matches('/')
+		.rsplit(|c| c == '/' || c == ':')
+		.next()
+		.unwrap_or_default();
+	let name = name.split('.').next().unwrap_or_default();
+	let name: String = name
+		.chars()
+		.map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+		.collect();
+	format!("_{}", name)
+}
+
+fn is_es_module(module: &Module) -> bool {
+	module.body.iter().any(|item| matches!(item, ModuleItem::ModuleDecl(_)))
+}
+
+fn has_require_binding(module: &Module) -> bool {
+	collect_decls::<Id, _>(module).iter().any(|(sym, _)| sym.as_ref() == "require")
+}
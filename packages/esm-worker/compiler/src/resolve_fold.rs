@@ -1,17 +1,35 @@
-use crate::resolver::Resolver;
+use crate::import_attributes::{get_import_type, ImportAttributes};
+use crate::resolver::{CjsInterop, DependencyKind, Resolver};
 use std::{cell::RefCell, rc::Rc};
-use swc_common::DUMMY_SP;
+use swc_common::{BytePos, Mark, Span, DUMMY_SP};
 use swc_ecma_ast::*;
-use swc_ecma_utils::quote_ident;
+use swc_ecma_utils::{find_ids, private_ident, quote_ident};
 use swc_ecma_visit::{noop_fold_type, Fold, FoldWith};
 
-pub fn resolve_fold(resolver: Rc<RefCell<Resolver>>, is_dev: bool) -> impl Fold {
-	ResolveFold { resolver, is_dev }
+pub fn resolve_fold(
+	resolver: Rc<RefCell<Resolver>>,
+	is_dev: bool,
+	deferred_imports: Vec<BytePos>,
+	glob_mark: Mark,
+	import_attributes: ImportAttributes,
+) -> impl Fold {
+	ResolveFold {
+		resolver,
+		is_dev,
+		deferred_imports,
+		glob_mark,
+		import_attributes,
+	}
 }
 
 pub struct ResolveFold {
 	resolver: Rc<RefCell<Resolver>>,
 	is_dev: bool,
+	deferred_imports: Vec<BytePos>,
+	// the mark of the specifiers of the lazy globs
+	glob_mark: Mark,
+	// how the attributes of the dynamic imports are emitted
+	import_attributes: ImportAttributes,
 }
 
 impl Fold for ResolveFold {
@@ -22,8 +40,11 @@ impl Fold for ResolveFold {
 		let mut items = Vec::<ModuleItem>::new();
 
 		for item in module_items {
+			let mut interop_items: Vec<ModuleItem> = vec![];
 			match item {
 				ModuleItem::ModuleDecl(decl) => {
+					self.record_local_exports(&decl);
+					let attributes_src_span = get_attributes_src_span(&decl);
 					let item: ModuleItem = match decl {
 						// match: import React, { useState } from "https://esm.sh/react"
 						ModuleDecl::Import(import_decl) => {
@@ -32,7 +53,25 @@ impl Fold for ResolveFold {
 								ModuleItem::ModuleDecl(ModuleDecl::Import(import_decl))
 							} else {
 								let mut resolver = self.resolver.borrow_mut();
-								let fixed_url = resolver.resolve(import_decl.src.value.as_ref(), false);
+								let src = &import_decl.src;
+								let fixed_url =
+									resolver.resolve_at(src.value.as_ref(), false, src.span);
+								// match: import defer * as ns from "x"
+								if self.deferred_imports.contains(&import_decl.span.lo) {
+									if let Some(dep) = resolver.deps.last_mut() {
+										dep.is_deferred = true;
+									}
+								}
+								// match: import sheet from "./s.css" with { type: "css" }
+								if let Some(asserts) = &import_decl.asserts {
+									set_import_type(&mut resolver, asserts);
+								}
+								// match: import "./polyfill.js"
+								if import_decl.specifiers.is_empty() {
+									if let Some(dep) = resolver.deps.last_mut() {
+										dep.is_side_effect = true;
+									}
+								}
 								if resolver.bundle_mode && resolver.bundle_externals.contains(fixed_url.as_str()) {
 									let mut names: Vec<(Ident, Option<String>)> = vec![];
 									let mut ns: Option<Ident> = None;
@@ -76,6 +115,63 @@ impl Fold for ResolveFold {
 									} else {
 										ModuleItem::Stmt(Stmt::Empty(EmptyStmt { span: DUMMY_SP }))
 									}
+								} else if resolver.is_cjs_module(import_decl.src.value.as_ref(), fixed_url.as_str()) {
+									// match: import pkg, * as ns from "cjs"
+									// the default export of a CommonJS-origin module may be `undefined`(`__esModule`),
+									// falls back to the namespace: `const pkg = ns.default !== void 0 ? ns.default : ns`
+									// match: import pkg, { a } from "cjs"
+									// the namespace is imported separately as `import * as _pkg from "cjs"`
+									let default_local = import_decl.specifiers.iter().find_map(|s| match s {
+										ImportSpecifier::Default(ImportDefaultSpecifier { local, .. }) => Some(local.clone()),
+										_ => None,
+									});
+									let ns_local = import_decl.specifiers.iter().find_map(|s| match s {
+										ImportSpecifier::Namespace(ImportStarAsSpecifier { local, .. }) => Some(local.clone()),
+										_ => None,
+									});
+									if let Some(default_local) = default_local {
+										if let Some(dep) = resolver.deps.last_mut() {
+											dep.cjs_interop = Some(CjsInterop::DefaultOrNamespace);
+										}
+										let mut specifiers: Vec<ImportSpecifier> = import_decl
+											.specifiers
+											.into_iter()
+											.filter(|s| !matches!(s, ImportSpecifier::Default(_)))
+											.collect();
+										let ns_local = match ns_local {
+											Some(ns_local) => ns_local,
+											None => {
+												let ns_local = private_ident!(format!("_{}", default_local.sym));
+												let ns_specifier = ImportSpecifier::Namespace(ImportStarAsSpecifier {
+													span: DUMMY_SP,
+													local: ns_local.clone(),
+												});
+												if specifiers.is_empty() {
+													specifiers.push(ns_specifier);
+												} else {
+													interop_items.push(ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
+														span: DUMMY_SP,
+														specifiers: vec![ns_specifier],
+														src: new_str(fixed_url.clone()),
+														type_only: false,
+														asserts: None,
+													})));
+												}
+												ns_local
+											}
+										};
+										interop_items.push(create_default_or_namespace_decl(default_local, ns_local));
+										ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
+											src: new_str(fixed_url),
+											specifiers,
+											..import_decl
+										}))
+									} else {
+										ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
+											src: new_str(fixed_url),
+											..import_decl
+										}))
+									}
 								} else {
 									ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
 										src: new_str(fixed_url),
@@ -90,6 +186,7 @@ impl Fold for ResolveFold {
 							type_only,
 							specifiers,
 							src: Some(src),
+							asserts,
 							..
 						}) => {
 							if type_only {
@@ -103,7 +200,14 @@ impl Fold for ResolveFold {
 								}))
 							} else {
 								let mut resolver = self.resolver.borrow_mut();
-								let fixed_url = resolver.resolve(src.value.as_ref(), false);
+								let fixed_url =
+									resolver.resolve_at(src.value.as_ref(), false, src.span);
+								if let Some(asserts) = &asserts {
+									set_import_type(&mut resolver, asserts);
+								}
+								for name in specifiers.iter().filter_map(get_export_name) {
+									resolver.record_export(name, fixed_url.as_str());
+								}
 								if resolver.bundle_mode && resolver.bundle_externals.contains(fixed_url.as_str()) {
 									let mut names: Vec<(Ident, Option<String>)> = vec![];
 									let mut ns: Option<Ident> = None;
@@ -155,15 +259,20 @@ impl Fold for ResolveFold {
 										specifiers,
 										src: Some(new_str(fixed_url)),
 										type_only: false,
-										asserts: None,
+										asserts,
 									}))
 								}
 							}
 						}
 						// match: export * from "https://esm.sh/react"
-						ModuleDecl::ExportAll(ExportAll { src, .. }) => {
+						ModuleDecl::ExportAll(ExportAll { src, asserts, .. }) => {
 							let mut resolver = self.resolver.borrow_mut();
-							let fixed_url = resolver.resolve(src.value.as_ref(), false);
+							let fixed_url =
+								resolver.resolve_at(src.value.as_ref(), false, src.span);
+							if let Some(asserts) = &asserts {
+								set_import_type(&mut resolver, asserts);
+							}
+							resolver.record_export(format!("{{{}}}", fixed_url), fixed_url.as_str());
 							if resolver.bundle_mode && resolver.bundle_externals.contains(fixed_url.as_str()) {
 								resolver.star_exports.push(fixed_url.clone());
 								ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl {
@@ -183,7 +292,7 @@ impl Fold for ResolveFold {
 									ModuleItem::ModuleDecl(ModuleDecl::ExportAll(ExportAll {
 										span: DUMMY_SP,
 										src: new_str(fixed_url.into()),
-										asserts: None,
+										asserts,
 									}))
 								} else {
 									let mut src = "".to_owned();
@@ -196,14 +305,19 @@ impl Fold for ResolveFold {
 									ModuleItem::ModuleDecl(ModuleDecl::ExportAll(ExportAll {
 										span: DUMMY_SP,
 										src: new_str(src.into()),
-										asserts: None,
+										asserts,
 									}))
 								}
 							}
 						}
 						_ => ModuleItem::ModuleDecl(decl),
 					};
+					let item = match attributes_src_span {
+						Some(span) => with_attributes_src_span(item, span),
+						None => item,
+					};
 					items.push(item.fold_children_with(self));
+					items.extend(interop_items);
 				}
 				_ => {
 					items.push(item.fold_children_with(self));
@@ -217,10 +331,10 @@ impl Fold for ResolveFold {
 	// resolve dynamic import url
 	fn fold_call_expr(&mut self, mut call: CallExpr) -> CallExpr {
 		if is_call_expr_by_name(&call, "import") {
-			let url = match call.args.first() {
+			let (url, span) = match call.args.first() {
 				Some(ExprOrSpread { expr, .. }) => match expr.as_ref() {
 					Expr::Lit(lit) => match lit {
-						Lit::Str(s) => s.value.as_ref(),
+						Lit::Str(s) => (s.value.as_ref(), s.span),
 						_ => return call,
 					},
 					_ => return call,
@@ -234,15 +348,230 @@ impl Fold for ResolveFold {
 					prop: quote_ident!("import"),
 				})))
 			}
-			let fixed_url = resolver.resolve(url, true);
-			call.args = vec![ExprOrSpread {
+			let fixed_url = resolver.resolve_at(url, true, span);
+			// match: () => import("./pages/a.ts") of `import.meta.glob("./pages/*.ts")`
+			if span.ctxt.outer() == self.glob_mark {
+				if let Some(dep) = resolver.deps.last_mut() {
+					dep.kind = DependencyKind::Glob;
+				}
+			}
+			resolver.add_chunk(fixed_url.as_str());
+			// match: import("./s.css", { with: { type: "css" } })
+			if let Some(ExprOrSpread { expr, .. }) = call.args.get(1) {
+				if let Expr::Object(options) = expr.as_ref() {
+					if let Some(asserts) = get_import_options_attributes(options) {
+						set_import_type(&mut resolver, asserts);
+					}
+				}
+			}
+			let url = ExprOrSpread {
 				spread: None,
 				expr: Box::new(Expr::Lit(Lit::Str(new_str(fixed_url)))),
-			}];
+			};
+			match self.import_attributes {
+				ImportAttributes::Strip => call.args = vec![url],
+				ImportAttributes::Preserve => call.args[0] = url,
+				ImportAttributes::With => {
+					call.args[0] = url;
+					rename_import_options_attributes(&mut call.args, "with");
+				}
+				ImportAttributes::Assert => {
+					call.args[0] = url;
+					rename_import_options_attributes(&mut call.args, "assert");
+				}
+			}
+		} else if is_service_worker_register(&call) {
+			// match: navigator.serviceWorker.register("./sw.ts", { scope: "/" })
+			self.resolve_url_arg(&mut call.args, DependencyKind::ServiceWorker);
+		} else if is_worklet_add_module(&call) {
+			// match: audioWorklet.addModule("./processor.ts")
+			self.resolve_module_url_arg(&mut call.args, DependencyKind::Worklet);
+		} else if is_call_expr_by_name(&call, "fetch") {
+			// match: fetch(new URL("./data.json", import.meta.url))
+			if let Some(ExprOrSpread { spread: None, expr }) = call.args.first_mut() {
+				if let Expr::New(NewExpr {
+					callee,
+					args: Some(args),
+					..
+				}) = expr.as_mut()
+				{
+					if is_import_meta_url_ctor(callee, args) {
+						self.resolve_url_arg(args, DependencyKind::Asset);
+					}
+				}
+			}
 		}
 
 		call.fold_children_with(self)
 	}
+
+	// resolve worker url
+	fn fold_new_expr(&mut self, mut new: NewExpr) -> NewExpr {
+		// match: new Worker("./worker.ts", { type: "module" })
+		// match: new SharedWorker(new URL("./worker.ts", import.meta.url))
+		if let (Expr::Ident(id), Some(args)) = (new.callee.as_ref(), &mut new.args) {
+			if matches!(id.sym.as_ref(), "Worker" | "SharedWorker") {
+				self.resolve_module_url_arg(args, DependencyKind::Worker);
+			}
+		}
+
+		new.fold_children_with(self)
+	}
+}
+
+impl ResolveFold {
+	/// record the local exports of the bundled module in the `export_manifest`, the
+	/// re-exports are recorded with the resolved urls when they are resolved.
+	fn record_local_exports(&self, decl: &ModuleDecl) {
+		let names: Vec<String> = match decl {
+			// match: export const a = 1, { b } = obj
+			ModuleDecl::ExportDecl(ExportDecl { decl, .. }) => match decl {
+				Decl::Var(VarDecl { decls, .. }) => find_ids::<_, Ident>(decls)
+					.into_iter()
+					.map(|id| id.sym.as_ref().into())
+					.collect(),
+				Decl::Fn(FnDecl { ident, .. }) | Decl::Class(ClassDecl { ident, .. }) => {
+					vec![ident.sym.as_ref().into()]
+				}
+				Decl::TsEnum(TsEnumDecl { id, .. }) => vec![id.sym.as_ref().into()],
+				_ => vec![],
+			},
+			ModuleDecl::ExportDefaultDecl(_) | ModuleDecl::ExportDefaultExpr(_) => {
+				vec!["default".into()]
+			}
+			// match: export { a, b as c }
+			ModuleDecl::ExportNamed(NamedExport {
+				specifiers,
+				src: None,
+				type_only: false,
+				..
+			}) => specifiers.iter().filter_map(get_export_name).collect(),
+			_ => vec![],
+		};
+		if !names.is_empty() {
+			let mut resolver = self.resolver.borrow_mut();
+			let specifier = resolver.specifier.clone();
+			for name in names {
+				resolver.record_export(name, specifier.as_str());
+			}
+		}
+	}
+
+	/// resolve the url in the first argument, like the worker scripts and the fetched files, they
+	/// are loaded separately like the dynamic imports.
+	fn resolve_url_arg(&mut self, args: &mut [ExprOrSpread], kind: DependencyKind) {
+		if let Some(ExprOrSpread { spread: None, expr }) = args.first_mut() {
+			if let Expr::Lit(Lit::Str(s)) = expr.as_ref() {
+				let mut resolver = self.resolver.borrow_mut();
+				let fixed_url = resolver.resolve_at(s.value.as_ref(), true, s.span);
+				if let Some(dep) = resolver.deps.last_mut() {
+					if dep.kind != DependencyKind::Opaque {
+						dep.kind = kind;
+					}
+				}
+				*expr = Box::new(Expr::Lit(Lit::Str(new_str(fixed_url))));
+			}
+		}
+	}
+
+	/// resolve the url in the first argument of the module scripts, the string literals and the
+	/// `new URL("./worker.ts", import.meta.url)` urls.
+	fn resolve_module_url_arg(&mut self, args: &mut [ExprOrSpread], kind: DependencyKind) {
+		if let Some(ExprOrSpread { spread: None, expr }) = args.first_mut() {
+			if let Expr::New(NewExpr {
+				callee,
+				args: Some(args),
+				..
+			}) = expr.as_mut()
+			{
+				if is_import_meta_url_ctor(callee, args) {
+					self.resolve_url_arg(args, kind);
+				}
+				return;
+			}
+		}
+		self.resolve_url_arg(args, kind);
+	}
+}
+
+// match: audioWorklet.addModule, CSS.paintWorklet.addModule, ctx.audioWorklet.addModule
+fn is_worklet_add_module(call: &CallExpr) -> bool {
+	let (obj, prop) = match &call.callee {
+		ExprOrSuper::Expr(callee) => match callee.as_ref() {
+			Expr::Member(MemberExpr {
+				obj: ExprOrSuper::Expr(obj),
+				prop,
+				computed: false,
+				..
+			}) => (obj, prop),
+			_ => return false,
+		},
+		_ => return false,
+	};
+	if !matches!(prop.as_ref(), Expr::Ident(id) if id.sym.as_ref() == "addModule") {
+		return false;
+	}
+	let worklet = match obj.as_ref() {
+		Expr::Ident(id) => id,
+		Expr::Member(MemberExpr {
+			prop,
+			computed: false,
+			..
+		}) => match prop.as_ref() {
+			Expr::Ident(id) => id,
+			_ => return false,
+		},
+		_ => return false,
+	};
+	worklet.sym.as_ref().ends_with("Worklet")
+}
+
+// match: new URL("./data.json", import.meta.url)
+fn is_import_meta_url_ctor(callee: &Expr, args: &[ExprOrSpread]) -> bool {
+	let is_url = matches!(callee, Expr::Ident(id) if id.sym.as_ref() == "URL");
+	let is_import_meta_url = match args.get(1) {
+		Some(ExprOrSpread { spread: None, expr }) => match expr.as_ref() {
+			Expr::Member(MemberExpr {
+				obj: ExprOrSuper::Expr(obj),
+				prop,
+				computed: false,
+				..
+			}) => {
+				matches!(obj.as_ref(), Expr::MetaProp(MetaPropExpr { meta, prop })
+					if meta.sym.as_ref() == "import" && prop.sym.as_ref() == "meta")
+					&& matches!(prop.as_ref(), Expr::Ident(id) if id.sym.as_ref() == "url")
+			}
+			_ => false,
+		},
+		_ => false,
+	};
+	is_url && args.len() == 2 && is_import_meta_url
+}
+
+// match: serviceWorker.register, navigator.serviceWorker.register
+fn is_service_worker_register(call: &CallExpr) -> bool {
+	match &call.callee {
+		ExprOrSuper::Expr(callee) => match callee.as_ref() {
+			Expr::Member(MemberExpr {
+				obj: ExprOrSuper::Expr(obj),
+				prop,
+				computed: false,
+				..
+			}) if matches!(prop.as_ref(), Expr::Ident(id) if id.sym.as_ref() == "register") => {
+				match obj.as_ref() {
+					Expr::Ident(id) => id.sym.as_ref() == "serviceWorker",
+					Expr::Member(MemberExpr {
+						prop,
+						computed: false,
+						..
+					}) => matches!(prop.as_ref(), Expr::Ident(id) if id.sym.as_ref() == "serviceWorker"),
+					_ => false,
+				}
+			}
+			_ => false,
+		},
+		ExprOrSuper::Super(_) => false,
+	}
 }
 
 pub fn is_call_expr_by_name(call: &CallExpr, name: &str) -> bool {
@@ -319,6 +648,150 @@ pub fn create_aleph_pack_var_decl_member(
 	}
 }
 
+fn create_default_or_namespace_decl(default_local: Ident, ns_local: Ident) -> ModuleItem {
+	let ns_default = || {
+		Box::new(Expr::Member(MemberExpr {
+			span: DUMMY_SP,
+			obj: ExprOrSuper::Expr(Box::new(Expr::Ident(ns_local.clone()))),
+			prop: Box::new(Expr::Ident(quote_ident!("default"))),
+			computed: false,
+		}))
+	};
+	ModuleItem::Stmt(Stmt::Decl(Decl::Var(VarDecl {
+		span: DUMMY_SP,
+		kind: VarDeclKind::Const,
+		declare: false,
+		decls: vec![VarDeclarator {
+			span: DUMMY_SP,
+			name: Pat::Ident(BindingIdent {
+				id: default_local,
+				type_ann: None,
+			}),
+			init: Some(Box::new(Expr::Cond(CondExpr {
+				span: DUMMY_SP,
+				test: Box::new(Expr::Bin(BinExpr {
+					span: DUMMY_SP,
+					op: BinaryOp::NotEqEq,
+					left: ns_default(),
+					right: Box::new(Expr::Unary(UnaryExpr {
+						span: DUMMY_SP,
+						op: UnaryOp::Void,
+						arg: Box::new(Expr::Lit(Lit::Num(Number {
+							span: DUMMY_SP,
+							value: 0.0,
+						}))),
+					})),
+				})),
+				cons: ns_default(),
+				alt: Box::new(Expr::Ident(ns_local.clone())),
+			}))),
+			definite: false,
+		}],
+	})))
+}
+
+/// record the `type` of the import attributes on the last dependency.
+fn set_import_type(resolver: &mut Resolver, asserts: &ObjectLit) {
+	if let (Some(import_type), Some(dep)) = (get_import_type(asserts), resolver.deps.last_mut()) {
+		dep.import_type = Some(import_type);
+	}
+}
+
+// match: { with: { type: "css" } }, { assert: { type: "json" } }
+fn get_import_options_attributes(options: &ObjectLit) -> Option<&ObjectLit> {
+	options.props.iter().find_map(|prop| match prop {
+		PropOrSpread::Prop(prop) => match prop.as_ref() {
+			Prop::KeyValue(KeyValueProp {
+				key: PropName::Ident(key),
+				value,
+			}) if key.sym.as_ref() == "with" || key.sym.as_ref() == "assert" => {
+				match value.as_ref() {
+					Expr::Object(attributes) => Some(attributes),
+					_ => None,
+				}
+			}
+			_ => None,
+		},
+		_ => None,
+	})
+}
+
+/// get the span of the specifier of the import/export with the attributes.
+fn get_attributes_src_span(decl: &ModuleDecl) -> Option<Span> {
+	match decl {
+		ModuleDecl::Import(ImportDecl {
+			src,
+			asserts: Some(_),
+			..
+		})
+		| ModuleDecl::ExportAll(ExportAll {
+			src,
+			asserts: Some(_),
+			..
+		})
+		| ModuleDecl::ExportNamed(NamedExport {
+			src: Some(src),
+			asserts: Some(_),
+			..
+		}) => Some(src.span),
+		_ => None,
+	}
+}
+
+/// keep the span of the resolved specifier, the attributes are emitted after the specifier by the
+/// `ImportAttributesWriter`.
+fn with_attributes_src_span(item: ModuleItem, span: Span) -> ModuleItem {
+	match item {
+		ModuleItem::ModuleDecl(ModuleDecl::Import(decl)) => {
+			ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
+				src: Str { span, ..decl.src },
+				..decl
+			}))
+		}
+		ModuleItem::ModuleDecl(ModuleDecl::ExportAll(decl)) => {
+			ModuleItem::ModuleDecl(ModuleDecl::ExportAll(ExportAll {
+				src: Str { span, ..decl.src },
+				..decl
+			}))
+		}
+		ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(NamedExport {
+			src: Some(src),
+			span: decl_span,
+			specifiers,
+			type_only,
+			asserts,
+		})) => ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(NamedExport {
+			src: Some(Str { span, ..src }),
+			span: decl_span,
+			specifiers,
+			type_only,
+			asserts,
+		})),
+		_ => item,
+	}
+}
+
+// match: { assert: { type: "json" } } => { with: { type: "json" } }
+fn rename_import_options_attributes(args: &mut [ExprOrSpread], keyword: &str) {
+	if let Some(ExprOrSpread { expr, .. }) = args.get_mut(1) {
+		if let Expr::Object(options) = expr.as_mut() {
+			for prop in options.props.iter_mut() {
+				if let PropOrSpread::Prop(prop) = prop {
+					if let Prop::KeyValue(KeyValueProp {
+						key: PropName::Ident(key),
+						..
+					}) = prop.as_mut()
+					{
+						if key.sym.as_ref() == "with" || key.sym.as_ref() == "assert" {
+							key.sym = keyword.into();
+						}
+					}
+				}
+			}
+		}
+	}
+}
+
 fn new_str(str: String) -> Str {
 	Str {
 		span: DUMMY_SP,
@@ -327,3 +800,21 @@ fn new_str(str: String) -> Str {
 		kind: Default::default(),
 	}
 }
+
+/// get the exported name of the export specifier, returns `None` for the type-only specifiers.
+fn get_export_name(specifier: &ExportSpecifier) -> Option<String> {
+	match specifier {
+		ExportSpecifier::Named(ExportNamedSpecifier {
+			is_type_only: true, ..
+		}) => None,
+		ExportSpecifier::Named(ExportNamedSpecifier { orig, exported, .. }) => {
+			Some(exported.as_ref().unwrap_or(orig).sym.as_ref().into())
+		}
+		ExportSpecifier::Default(ExportDefaultSpecifier { exported }) => {
+			Some(exported.sym.as_ref().into())
+		}
+		ExportSpecifier::Namespace(ExportNamespaceSpecifier { name, .. }) => {
+			Some(name.sym.as_ref().into())
+		}
+	}
+}
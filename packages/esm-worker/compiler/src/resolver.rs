@@ -1,10 +1,16 @@
+use crate::error::Warning;
 use crate::import_map::{ImportHashMap, ImportMap};
-use indexmap::IndexSet;
+use indexmap::{IndexMap, IndexSet};
 use path_slash::PathBufExt;
 use regex::Regex;
 use relative_path::RelativePath;
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, path::PathBuf, str::FromStr};
+use std::{
+	collections::{BTreeMap, HashMap},
+	path::PathBuf,
+	str::FromStr,
+};
+use swc_common::Span;
 use url::Url;
 
 #[derive(Clone, Debug, Eq, PartialEq, Serialize)]
@@ -12,8 +18,145 @@ use url::Url;
 pub struct DependencyDescriptor {
 	pub specifier: String,
 	pub is_dynamic: bool,
+	pub is_deferred: bool,
+	/// the dependency is imported for side effects only: `import "./polyfill.js"`
+	pub is_side_effect: bool,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub cjs_interop: Option<CjsInterop>,
+	/// the `type` of the import attributes: `import sheet from "./s.css" with { type: "css" }`
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub import_type: Option<String>,
+	pub kind: DependencyKind,
 }
 
+impl DependencyDescriptor {
+	/// merge the flags of a duplicate dependency, a static import wins over the dynamic/deferred
+	/// imports since the dependency is loaded eagerly anyway.
+	pub fn merge(&mut self, other: &DependencyDescriptor) {
+		self.is_dynamic = self.is_dynamic && other.is_dynamic;
+		self.is_deferred = self.is_deferred && other.is_deferred;
+		self.is_side_effect = self.is_side_effect || other.is_side_effect;
+		if self.cjs_interop.is_none() {
+			self.cjs_interop = other.cjs_interop.clone();
+		}
+		if self.import_type.is_none() {
+			self.import_type = other.import_type.clone();
+		}
+	}
+}
+
+/// The kind of a dependency.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DependencyKind {
+	/// `import`/`export from` statements and the dynamic `import()` calls.
+	Import,
+	/// the triple-slash references: `/// <reference types="node" />`, the specifier is kept as written.
+	TypeReference,
+	/// the worker scripts: `new Worker("./worker.ts")` and
+	/// `new SharedWorker(new URL("./worker.ts", import.meta.url))`
+	Worker,
+	/// the service worker registrations: `navigator.serviceWorker.register("./sw.ts")`
+	ServiceWorker,
+	/// the worklet modules: `audioWorklet.addModule("./processor.ts")`
+	Worklet,
+	/// the files matched by the lazy globs, they are loaded by the dynamic imports:
+	/// `import.meta.glob("./pages/*.ts")`
+	Glob,
+	/// the fetched files relative to the module: `fetch(new URL("./data.json", import.meta.url))`
+	Asset,
+	/// the `data:`, `blob:` and `file:` urls, they are kept as written and can't be fetched.
+	Opaque,
+}
+
+/// How the import bindings of a CommonJS-origin dependency are synthesized.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CjsInterop {
+	/// `import pkg, * as ns from "x"` is rewritten to `import * as ns from "x"`,
+	/// the `pkg` falls back to the namespace when the module has no default export. The namespace
+	/// of `import pkg from "x"` is synthesized: `import * as _pkg from "x"`.
+	DefaultOrNamespace,
+}
+
+/// The kind of an import/export specifier.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ImportKind {
+	/// `import` declarations and `export ... from` declarations.
+	Static,
+	/// `import()` calls.
+	Dynamic,
+}
+
+/// How the relative specifiers are resolved.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ResolveMode {
+	/// resolve the relative specifiers to the absolute paths/urls: `./a.ts` => `/src/a.ts`
+	#[default]
+	Absolute,
+	/// keep the relative specifiers, only the extensions are rewritten: `./a.ts` => `./a.js`.
+	/// for the output served from the same origin with a known layout.
+	RelativePreserve,
+}
+
+/// How the bare specifiers that can't be resolved by the import map are handled.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum UnresolvedPolicy {
+	/// keep the specifier as it is: `react` => `react`
+	LeaveAsIs,
+	/// fail the transform with the specifier and its position, like the `require_all_resolved`
+	/// option of the transform
+	Error,
+	/// rewrite the specifier to the esm.sh CDN: `react` => `https://esm.sh/react`
+	RewriteToCdn,
+}
+
+/// A callback to rewrite the specifier, returns `None` to apply the default rewriting.
+pub type RewriteFn = Box<dyn Fn(&str, ImportKind) -> Option<String>>;
+
+/// A callback to fetch the source code of a dependency by the specifier as written in the import.
+pub type SourceFetcher = Box<dyn Fn(&str) -> Option<String>>;
+
+/// A string literal passed to a translation function, extracted by the `extract_strings` option.
+/// The `line` is 1-based and the `col` is 0-based.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtractedString {
+	pub key: String,
+	pub line: usize,
+	pub col: usize,
+}
+
+/// An export of the transformed module, recorded with the `export_shape` option. The `name` of
+/// a star export(`export * from "./a.ts"`) is the source as emitted, like `[/a.ts]:/a.ts`.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportShape {
+	pub name: String,
+	pub kind: ExportKind,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ExportKind {
+	Function,
+	Class,
+	Variable,
+	/// the re-exported binding: `export { x } from "./a.ts"`, or an imported binding.
+	Reexport,
+	/// match: `export * as ns from "./a.ts"`
+	Namespace,
+	/// match: `export * from "./a.ts"`
+	Star,
+}
+
+/// A callback to list the files matched by a glob pattern, like `./pages/*.ts`, the files are
+/// relative to the module as the pattern.
+pub type FileLister = Box<dyn Fn(&str) -> Vec<String>>;
+
 #[derive(Clone, Debug, Eq, PartialEq, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct InlineStyle {
@@ -22,6 +165,62 @@ pub struct InlineStyle {
 	pub exprs: Vec<String>,
 }
 
+/// The CSS of a static tagged template extracted by the `extract_css` option.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtractedCss {
+	/// the tag of the template, like `css`, `styled.div` or `styled()`.
+	pub tag: String,
+	pub css: String,
+	/// the class name that replaced the template, if it's replaced.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub class_name: Option<String>,
+}
+
+/// A module split from the bundle by a dynamic import with the `code_splitting` option, it's
+/// loaded separately instead of being packed in the bundle. The chunk is not built by the
+/// transform, the caller builds the module of the specifier.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Chunk {
+	/// the name derived from the specifier, like `heavy` for `/pages/heavy.ts`, the names are
+	/// unique in the module.
+	pub name: String,
+	pub specifier: String,
+}
+
+/// The outputs of a module transformed in the bundle mode.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleOutput {
+	/// the export names mapping to the specifiers of the modules they come from, the local
+	/// exports come from the module itself. The names of the star exports are not known, they're
+	/// recorded like `parse_export_names`: `{/b.ts}` => `/b.ts`.
+	#[serde(skip_serializing_if = "HashMap::is_empty")]
+	pub export_manifest: HashMap<String, String>,
+}
+
+/// An import map of the resolved bare specifiers, see `Resolver::build_import_map`. The
+/// specifiers are sorted for the stable output.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+pub struct GeneratedImportMap {
+	pub imports: BTreeMap<String, String>,
+}
+
+impl GeneratedImportMap {
+	/// build the import map of the bare specifiers mapping to the resolved urls, the specifiers
+	/// kept as written are skipped.
+	pub fn from_resolved(resolved_bare_specifiers: &IndexMap<String, String>) -> Self {
+		GeneratedImportMap {
+			imports: resolved_bare_specifiers
+				.iter()
+				.filter(|(specifier, url)| specifier != url)
+				.map(|(specifier, url)| (specifier.clone(), url.clone()))
+				.collect(),
+		}
+	}
+}
+
 #[derive(Clone, Debug, Deserialize)]
 #[serde(deny_unknown_fields, rename_all = "camelCase")]
 pub struct ReactOptions {
@@ -41,14 +240,65 @@ pub struct Resolver {
 	pub deps: Vec<DependencyDescriptor>,
 	/// all star exports of the module
 	pub star_exports: Vec<String>,
+	/// the outputs of the bundle mode
+	pub bundle_output: BundleOutput,
 	/// bundle mode
 	pub bundle_mode: bool,
 	/// externals for bundle mode
 	pub bundle_externals: IndexSet<String>,
+	/// record the dynamically imported modules that are not packed in the bundle as the `chunks`,
+	/// only the metadata is recorded, the chunks are built by the caller
+	pub code_splitting: bool,
+	/// the chunks of the dynamic imports with the `code_splitting` option, in the order of the
+	/// first imports
+	pub chunks: Vec<Chunk>,
 	/// parsed jsx inline styles
 	pub jsx_inline_styles: HashMap<String, InlineStyle>,
 	/// jsx static class names
 	pub jsx_static_class_names: IndexSet<String>,
+	/// the CSS extracted from the tagged templates with the `extract_css` option
+	pub extracted_css: Vec<ExtractedCss>,
+	/// the strings of the translation function calls extracted with the `extract_strings` option
+	pub extracted_strings: Vec<ExtractedString>,
+	/// the export shape of the transformed module with the `export_shape` option
+	pub export_shape: Vec<ExportShape>,
+	/// the CommonJS-origin modules, imports of them need interop
+	pub cjs_modules: IndexSet<String>,
+	/// a custom callback to rewrite specifiers, invoked for every specifier
+	pub rewrite_fn: Option<RewriteFn>,
+	/// a callback to fetch the dependency sources, for the `inline_constants` option
+	pub source_fetcher: Option<SourceFetcher>,
+	/// a callback to list the files matched by a glob, for the `glob_imports` option
+	pub file_lister: Option<FileLister>,
+	/// the query parameters appended to the rewritten dependency urls, like `v=123&target=es2020`
+	pub extra_query: Option<String>,
+	/// the query parameters appended to the CSS imports, like `module` for the CDN to serve a JS
+	/// module injecting the styles: `import "./a.css"` => `import "/a.css?module"`
+	pub css_query: Option<String>,
+	/// the extensions mapping of the relative specifiers, like `.ts` => `.js`
+	pub output_extensions: HashMap<String, String>,
+	/// the `(from_prefix, to_prefix)` rules to rewrite the remote specifiers to the mirrors, like
+	/// `https://deno.land/std/` => `https://mirror.internal/deno_std/`
+	pub remote_mirrors: Vec<(String, String)>,
+	/// how the relative specifiers are resolved
+	pub resolve_mode: ResolveMode,
+	/// the helpers used by the module with the `shared_helpers` option, like `classCallCheck`
+	pub helpers: IndexSet<String>,
+	/// how the unresolved bare specifiers are handled, they are resolved as paths if it's not set
+	pub on_unresolved: Option<UnresolvedPolicy>,
+	/// the bare specifiers mapping to the rewritten urls, in the order of the first resolving
+	pub resolved_bare_specifiers: IndexMap<String, String>,
+	/// the specifiers that can't be resolved by the `resolve_fold` with the spans of the imports,
+	/// the errors of the `UnresolvedPolicy::Error` and the `require_all_resolved` option. It's
+	/// cleared by each transform.
+	pub unresolved_specifiers: Vec<(String, Span)>,
+	/// warnings of the transform
+	pub warnings: Vec<Warning>,
+	/// the AST dumps after each transform pass, in the order of `(pass name, dump)`
+	pub ast_dumps: Vec<(String, String)>,
+	/// the top-level import statements and the rest of the emitted code, with the
+	/// `split_imports` option
+	pub split_imports: Option<(String, String)>,
 
 	// internal
 	import_map: ImportMap,
@@ -72,20 +322,160 @@ impl Resolver {
 			specifier_is_remote: is_remote_url(specifier),
 			deps: Vec::new(),
 			star_exports: Vec::new(),
+			bundle_output: BundleOutput::default(),
 			bundle_mode,
 			bundle_externals: tmp,
+			code_splitting: false,
+			chunks: Vec::new(),
 			jsx_inline_styles: HashMap::new(),
 			jsx_static_class_names: IndexSet::new(),
+			extracted_css: Vec::new(),
+			extracted_strings: Vec::new(),
+			export_shape: Vec::new(),
+			cjs_modules: IndexSet::new(),
+			rewrite_fn: None,
+			source_fetcher: None,
+			file_lister: None,
+			extra_query: None,
+			css_query: None,
+			output_extensions: HashMap::new(),
+			remote_mirrors: Vec::new(),
+			resolve_mode: ResolveMode::Absolute,
+			on_unresolved: None,
+			resolved_bare_specifiers: IndexMap::new(),
+			unresolved_specifiers: Vec::new(),
+			helpers: IndexSet::new(),
+			warnings: Vec::new(),
+			ast_dumps: Vec::new(),
+			split_imports: None,
 			import_map: ImportMap::from_hashmap(import_map),
 			react,
 		}
 	}
 
+	/// build the import map of the resolved bare specifiers, like `react` =>
+	/// `https://esm.sh/react@18.2.0`, the specifiers kept as written are skipped.
+	pub fn build_import_map(&self) -> GeneratedImportMap {
+		GeneratedImportMap::from_resolved(&self.resolved_bare_specifiers)
+	}
+
+	/// record the export of the bundled module in the `export_manifest`, with the specifier of
+	/// the module it comes from.
+	pub fn record_export(&mut self, name: String, specifier: &str) {
+		if self.bundle_mode {
+			self.bundle_output.export_manifest.insert(name, specifier.into());
+		}
+	}
+
+	/// record the dynamically imported url as a chunk of the bundle with the `code_splitting`
+	/// option, the modules packed in the bundle are not split.
+	pub fn add_chunk(&mut self, url: &str) {
+		if !self.bundle_mode
+			|| !self.code_splitting
+			|| self.bundle_externals.contains(url)
+			|| self.chunks.iter().any(|chunk| chunk.specifier == url)
+		{
+			return;
+		}
+		let base = get_chunk_name(url);
+		let mut name = base.clone();
+		let mut i = 2;
+		while self.chunks.iter().any(|chunk| chunk.name == name) {
+			name = format!("{}-{}", base, i);
+			i += 1;
+		}
+		self.chunks.push(Chunk {
+			name,
+			specifier: url.into(),
+		});
+	}
+
 	/// resolve import/export url.
 	pub fn resolve(&mut self, url: &str, is_dynamic: bool) -> String {
+		let fixed_url = self.resolve_url(url, is_dynamic);
+		if is_bare_specifier(url) && !self.resolved_bare_specifiers.contains_key(url) {
+			self.resolved_bare_specifiers.insert(url.into(), fixed_url.clone());
+		}
+		fixed_url
+	}
+
+	/// resolve the import/export url at the span, the specifier is recorded in the
+	/// `unresolved_specifiers` if it can't be resolved.
+	pub fn resolve_at(&mut self, url: &str, is_dynamic: bool, span: Span) -> String {
+		let fixed_url = self.resolve(url, is_dynamic);
+		if self.is_unresolvable(url, fixed_url.as_str()) {
+			let message = format!("Unresolved specifier \"{}\"", url);
+			self.unresolved_specifiers.push((message, span));
+		}
+		fixed_url
+	}
+
+	/// check if the url is still a bare specifier after the resolving, or it's a relative
+	/// specifier without a base(the module specifier is neither a remote url nor an absolute
+	/// path), or it points outside of the root: `/app.ts` imports `../a.ts`.
+	fn is_unresolvable(&self, url: &str, fixed_url: &str) -> bool {
+		if is_opaque_url(url) || is_remote_url(fixed_url) {
+			return false;
+		}
+		let is_relative = url.starts_with("./") || url.starts_with("../");
+		if is_relative && self.resolve_mode == ResolveMode::RelativePreserve {
+			return false;
+		}
+		if is_relative && !self.specifier_is_remote && !self.specifier.starts_with('/') {
+			return true;
+		}
+		is_bare_specifier(fixed_url) || fixed_url == "/.." || fixed_url.starts_with("/../")
+	}
+
+	fn resolve_url(&mut self, url: &str, is_dynamic: bool) -> String {
+		// keep the inline modules
+		if is_opaque_url(url) {
+			self.deps.push(DependencyDescriptor {
+				specifier: url.into(),
+				is_dynamic,
+				is_deferred: false,
+				is_side_effect: false,
+				cjs_interop: None,
+				import_type: None,
+				kind: DependencyKind::Opaque,
+			});
+			return url.into();
+		}
+
+		// apply the custom rewriting
+		if let Some(rewrite_fn) = &self.rewrite_fn {
+			let kind = if is_dynamic {
+				ImportKind::Dynamic
+			} else {
+				ImportKind::Static
+			};
+			if let Some(fixed_url) = rewrite_fn(url, kind) {
+				self.deps.push(DependencyDescriptor {
+					specifier: fixed_url.clone(),
+					is_dynamic,
+					is_deferred: false,
+					is_side_effect: false,
+					cjs_interop: None,
+					import_type: None,
+					kind: DependencyKind::Import,
+				});
+				return fixed_url;
+			}
+		}
+
 		// apply import map
-		let url = self.import_map.resolve(self.specifier.as_str(), url);
-		let mut fixed_url: String = if is_remote_url(url.as_str()) {
+		let mapped_url = self.import_map.resolve(self.specifier.as_str(), url);
+		let is_unresolved = is_bare_specifier(url) && mapped_url == url;
+		let url = mapped_url;
+		let is_relative = url.starts_with("./") || url.starts_with("../");
+		let mut fixed_url: String = if let (true, Some(policy)) = (is_unresolved, self.on_unresolved) {
+			match policy {
+				UnresolvedPolicy::RewriteToCdn => format!("https://esm.sh/{}", url),
+				UnresolvedPolicy::LeaveAsIs | UnresolvedPolicy::Error => url,
+			}
+		} else if is_remote_url(url.as_str())
+			|| (is_relative && self.resolve_mode == ResolveMode::RelativePreserve)
+		{
 			url.into()
 		} else {
 			if self.specifier_is_remote {
@@ -165,12 +555,140 @@ impl Resolver {
 			}
 		}
 
+		// rewrite the remote specifiers to the mirrors
+		if is_remote_url(fixed_url.as_str()) {
+			fixed_url = apply_mirrors(fixed_url.as_str(), &self.remote_mirrors);
+		}
+
+		// point the relative specifiers to the compiled artifacts
+		if is_relative {
+			fixed_url = replace_extension(fixed_url.as_str(), &self.output_extensions);
+		}
+
+		// serve the CSS imports as the JS modules
+		if let Some(query) = &self.css_query {
+			if is_css_url(fixed_url.as_str()) {
+				fixed_url = append_query(fixed_url.as_str(), query);
+			}
+		}
+
+		// append the extra query
+		if let Some(query) = &self.extra_query {
+			fixed_url = append_query(fixed_url.as_str(), query);
+		}
+
 		self.deps.push(DependencyDescriptor {
 			specifier: fixed_url.clone(),
 			is_dynamic,
+			is_deferred: false,
+			is_side_effect: false,
+			cjs_interop: None,
+			import_type: None,
+			kind: DependencyKind::Import,
 		});
 		fixed_url
 	}
+
+	/// check if the import url (or the resolved url) points to a CommonJS-origin module.
+	pub fn is_cjs_module(&self, url: &str, fixed_url: &str) -> bool {
+		self.cjs_modules.contains(url) || self.cjs_modules.contains(fixed_url)
+	}
+}
+
+/// get the name of the chunk from the url, the stem of the file name or the directory name of the
+/// index files: `/pages/heavy.ts` => `heavy`, `/pages/about/index.tsx` => `about`.
+fn get_chunk_name(url: &str) -> String {
+	let end = url.find(|c| c == '?' || c == '#').unwrap_or(url.len());
+	let mut segments = url[..end].rsplit('/').filter(|s| !s.is_empty());
+	let file = segments.next().unwrap_or_default();
+	let stem = file.split('.').next().unwrap_or_default();
+	let stem = if stem == "index" {
+		segments.next().unwrap_or(stem)
+	} else {
+		stem
+	};
+	let name: String = stem
+		.chars()
+		.map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+		.collect();
+	if name.is_empty() {
+		"chunk".into()
+	} else {
+		name
+	}
+}
+
+/// check if the url points to a CSS file, the query and the hash are ignored.
+fn is_css_url(url: &str) -> bool {
+	let end = url.find(|c| c == '?' || c == '#').unwrap_or(url.len());
+	url[..end].ends_with(".css")
+}
+
+/// append the query parameters to the url, the parameters that already exist in the url are kept.
+pub fn append_query(url: &str, query: &str) -> String {
+	let (url, hash) = match url.find('#') {
+		Some(i) => url.split_at(i),
+		None => (url, ""),
+	};
+	let (path, search) = match url.find('?') {
+		Some(i) => (&url[..i], &url[i + 1..]),
+		None => (url, ""),
+	};
+	let mut params: Vec<&str> = search.split('&').filter(|p| !p.is_empty()).collect();
+	for param in query.trim_start_matches('?').split('&') {
+		let key = param.split('=').next().unwrap_or_default();
+		if !param.is_empty() && !params.iter().any(|p| p.split('=').next() == Some(key)) {
+			params.push(param);
+		}
+	}
+	if params.is_empty() {
+		return format!("{}{}", path, hash);
+	}
+	format!("{}?{}{}", path, params.join("&"), hash)
+}
+
+/// append the target to the remote url as `?target=es2017` for the CDN to serve the builds of the
+/// target, the target already in the url is kept. Returns `None` for the other urls.
+pub fn append_target_query(url: &str, target: &str) -> Option<String> {
+	if is_remote_url(url) {
+		Some(append_query(url, format!("target={}", target).as_str()))
+	} else {
+		None
+	}
+}
+
+/// rewrite the prefix of the url by the mirror rules, the longest matched prefix wins.
+pub fn apply_mirrors(url: &str, rules: &[(String, String)]) -> String {
+	rules
+		.iter()
+		.filter(|(from, _)| url.starts_with(from.as_str()))
+		.max_by_key(|(from, _)| from.len())
+		.map(|(from, to)| format!("{}{}", to, &url[from.len()..]))
+		.unwrap_or_else(|| url.into())
+}
+
+/// replace the extension of the url path by the mapping, the query and the hash are kept.
+pub fn replace_extension(url: &str, extensions: &HashMap<String, String>) -> String {
+	let i = url.find(|c| c == '?' || c == '#').unwrap_or(url.len());
+	let (path, rest) = url.split_at(i);
+	let name_start = path.rfind('/').map(|i| i + 1).unwrap_or(0);
+	if let Some(dot) = path[name_start..].rfind('.') {
+		let (stem, ext) = path.split_at(name_start + dot);
+		if let Some(new_ext) = extensions.get(ext) {
+			return format!("{}{}{}", stem, new_ext, rest);
+		}
+	}
+	url.into()
+}
+
+/// check if the specifier is bare, like `react` or `@scope/pkg/sub`.
+pub fn is_bare_specifier(url: &str) -> bool {
+	!url.starts_with('.') && !url.starts_with('/') && Url::parse(url).is_err()
+}
+
+/// check if the url is a `data:`, `blob:` or `file:` url.
+pub fn is_opaque_url(url: &str) -> bool {
+	url.starts_with("data:") || url.starts_with("blob:") || url.starts_with("file:")
 }
 
 pub fn is_remote_url(url: &str) -> bool {
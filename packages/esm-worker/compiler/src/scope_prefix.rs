@@ -0,0 +1,229 @@
+use std::collections::HashSet;
+use swc_common::DUMMY_SP;
+use swc_ecma_ast::*;
+use swc_ecma_utils::{find_ids, ident::IdentLike, Id};
+use swc_ecma_visit::{noop_fold_type, Fold, FoldWith};
+
+/// Rename the top-level bindings of the module with the `prefix`, like `const x` => `const m1_x`,
+/// to avoid the collisions of the modules sharing a scope. The exports keep the original names:
+/// ```js
+/// const m1_x = 1;
+/// export { m1_x as x };
+/// ```
+/// The references are matched by the syntax context of the `resolver` pass, the keys of the
+/// properties and the class members are kept.
+pub fn scope_prefix_fold(prefix: &str) -> impl Fold {
+	ScopePrefixFold {
+		prefix: prefix.into(),
+		names: HashSet::new(),
+	}
+}
+
+struct ScopePrefixFold {
+	prefix: String,
+	// the top-level bindings
+	names: HashSet<Id>,
+}
+
+impl ScopePrefixFold {
+	/// rename the identifier if it's a top-level binding, returns `true` if it's renamed.
+	fn rename(&self, ident: &mut Ident) -> bool {
+		let is_top_level = self.names.contains(&ident.to_id());
+		if is_top_level {
+			ident.sym = format!("{}{}", self.prefix, ident.sym).into();
+		}
+		is_top_level
+	}
+}
+
+impl Fold for ScopePrefixFold {
+	noop_fold_type!();
+
+	fn fold_module_items(&mut self, items: Vec<ModuleItem>) -> Vec<ModuleItem> {
+		for item in &items {
+			match item {
+				ModuleItem::Stmt(Stmt::Decl(decl))
+				| ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl { decl, .. })) => {
+					self.names.extend(get_decl_ids(decl).iter().map(|id| id.to_id()));
+				}
+				ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultDecl(ExportDefaultDecl {
+					decl, ..
+				})) => match decl {
+					DefaultDecl::Fn(FnExpr { ident: Some(id), .. })
+					| DefaultDecl::Class(ClassExpr { ident: Some(id), .. }) => {
+						self.names.insert(id.to_id());
+					}
+					_ => {}
+				},
+				ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl { specifiers, .. })) => {
+					self.names.extend(specifiers.iter().map(|specifier| match specifier {
+						ImportSpecifier::Named(ImportNamedSpecifier { local, .. })
+						| ImportSpecifier::Default(ImportDefaultSpecifier { local, .. })
+						| ImportSpecifier::Namespace(ImportStarAsSpecifier { local, .. }) => local.to_id(),
+					}));
+				}
+				_ => {}
+			}
+		}
+
+		let mut output: Vec<ModuleItem> = Vec::with_capacity(items.len());
+		for item in items {
+			match item {
+				// match: export const x = 1
+				ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl { decl, .. })) => {
+					let ids = get_decl_ids(&decl);
+					output.push(ModuleItem::Stmt(Stmt::Decl(decl.fold_with(self))));
+					// match: export { m1_x as x }
+					output.push(ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(NamedExport {
+						span: DUMMY_SP,
+						specifiers: ids
+							.into_iter()
+							.map(|id| {
+								let mut orig = id.clone();
+								self.rename(&mut orig);
+								ExportSpecifier::Named(ExportNamedSpecifier {
+									span: DUMMY_SP,
+									orig,
+									exported: Some(Ident::new(id.sym, DUMMY_SP)),
+									is_type_only: false,
+								})
+							})
+							.collect(),
+						src: None,
+						type_only: false,
+						asserts: None,
+					})));
+				}
+				// the re-exports don't reference the local bindings
+				ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(NamedExport { src: Some(_), .. }))
+				| ModuleItem::ModuleDecl(ModuleDecl::ExportAll(_)) => output.push(item),
+				_ => output.push(item.fold_with(self)),
+			}
+		}
+		output
+	}
+
+	fn fold_ident(&mut self, mut ident: Ident) -> Ident {
+		self.rename(&mut ident);
+		ident
+	}
+
+	// match: export { x }
+	fn fold_export_named_specifier(&mut self, mut n: ExportNamedSpecifier) -> ExportNamedSpecifier {
+		let orig = n.orig.clone();
+		if self.rename(&mut n.orig) && n.exported.is_none() {
+			n.exported = Some(Ident::new(orig.sym, DUMMY_SP));
+		}
+		n
+	}
+
+	// match: import { x } from "./x.ts"
+	fn fold_import_named_specifier(&mut self, mut n: ImportNamedSpecifier) -> ImportNamedSpecifier {
+		let local = n.local.clone();
+		if self.rename(&mut n.local) && n.imported.is_none() {
+			n.imported = Some(Ident::new(local.sym, DUMMY_SP));
+		}
+		n
+	}
+
+	// match: { x }
+	fn fold_prop(&mut self, prop: Prop) -> Prop {
+		match prop {
+			Prop::Shorthand(key) => {
+				let mut value = key.clone();
+				if self.rename(&mut value) {
+					Prop::KeyValue(KeyValueProp {
+						key: PropName::Ident(key),
+						value: Box::new(Expr::Ident(value)),
+					})
+				} else {
+					Prop::Shorthand(key)
+				}
+			}
+			_ => prop.fold_children_with(self),
+		}
+	}
+
+	// match: const { x = 1 } = obj
+	fn fold_object_pat_prop(&mut self, prop: ObjectPatProp) -> ObjectPatProp {
+		match prop {
+			ObjectPatProp::Assign(AssignPatProp { span, key, value }) => {
+				let value = value.fold_with(self);
+				let mut local = key.clone();
+				if self.rename(&mut local) {
+					let local = Pat::Ident(local.into());
+					ObjectPatProp::KeyValue(KeyValuePatProp {
+						key: PropName::Ident(key),
+						value: Box::new(match value {
+							Some(right) => Pat::Assign(AssignPat {
+								span,
+								left: Box::new(local),
+								right,
+								type_ann: None,
+							}),
+							None => local,
+						}),
+					})
+				} else {
+					ObjectPatProp::Assign(AssignPatProp { span, key, value })
+				}
+			}
+			_ => prop.fold_children_with(self),
+		}
+	}
+
+	fn fold_prop_name(&mut self, name: PropName) -> PropName {
+		match name {
+			PropName::Computed(_) => name.fold_children_with(self),
+			_ => name,
+		}
+	}
+
+	// match: class A { x = 1 }
+	fn fold_class_prop(&mut self, mut prop: ClassProp) -> ClassProp {
+		if prop.computed {
+			prop.key = prop.key.fold_with(self);
+		}
+		prop.value = prop.value.fold_with(self);
+		prop.decorators = prop.decorators.fold_with(self);
+		prop
+	}
+
+	fn fold_member_expr(&mut self, mut expr: MemberExpr) -> MemberExpr {
+		expr.obj = expr.obj.fold_with(self);
+		if expr.computed {
+			expr.prop = expr.prop.fold_with(self);
+		}
+		expr
+	}
+
+	fn fold_labeled_stmt(&mut self, mut stmt: LabeledStmt) -> LabeledStmt {
+		stmt.body = stmt.body.fold_with(self);
+		stmt
+	}
+
+	fn fold_break_stmt(&mut self, stmt: BreakStmt) -> BreakStmt {
+		stmt
+	}
+
+	fn fold_continue_stmt(&mut self, stmt: ContinueStmt) -> ContinueStmt {
+		stmt
+	}
+
+	fn fold_private_name(&mut self, name: PrivateName) -> PrivateName {
+		name
+	}
+
+	fn fold_meta_prop_expr(&mut self, expr: MetaPropExpr) -> MetaPropExpr {
+		expr
+	}
+}
+
+/// get the bindings of the declaration.
+fn get_decl_ids(decl: &Decl) -> Vec<Ident> {
+	match decl {
+		Decl::Var(VarDecl { decls, .. }) => find_ids(decls),
+		Decl::Fn(FnDecl { ident, .. }) | Decl::Class(ClassDecl { ident, .. }) => vec![ident.clone()],
+		_ => vec![],
+	}
+}
@@ -0,0 +1,240 @@
+use crate::text_writer::{Indent, IndentWriter};
+use indexmap::IndexSet;
+use std::{cell::RefCell, rc::Rc};
+use swc_common::{Globals, Mark, SourceMap, DUMMY_SP, GLOBALS};
+use swc_ecma_ast::*;
+use swc_ecma_transforms_base::{external_name, helper_expr};
+use swc_ecma_utils::quote_ident;
+use swc_ecma_visit::{noop_fold_type, Fold, FoldWith, Node, Visit, VisitWith};
+use swc_ecmascript::{
+	codegen::{text_writer::JsWriter, Node as _},
+	transforms::helpers::{inject_helpers, Helpers, HELPERS},
+};
+
+/// Replace the `import * as swcHelpers from "@swc/helpers"` injected by the external helpers
+/// with `const swcHelpers = __ALEPH__.helpers`, the helpers are defined once in the bundle by
+/// `build_shared_helpers`. The names of the used helpers are recorded in `used`.
+pub fn shared_helpers_fold(used: Rc<RefCell<IndexSet<String>>>) -> impl Fold {
+	SharedHelpersFold { used }
+}
+
+struct SharedHelpersFold {
+	used: Rc<RefCell<IndexSet<String>>>,
+}
+
+impl Fold for SharedHelpersFold {
+	noop_fold_type!();
+
+	fn fold_module_items(&mut self, items: Vec<ModuleItem>) -> Vec<ModuleItem> {
+		let mut helpers_ident: Option<Ident> = None;
+		let items: Vec<ModuleItem> = items
+			.into_iter()
+			.map(|item| match item {
+				// match: import * as swcHelpers from "@swc/helpers"
+				ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
+					specifiers, src, ..
+				})) if src.value.as_ref() == "@swc/helpers"
+					&& matches!(specifiers.as_slice(), [ImportSpecifier::Namespace(_)]) =>
+				{
+					let local = match specifiers.into_iter().next() {
+						Some(ImportSpecifier::Namespace(ImportStarAsSpecifier { local, .. })) => local,
+						_ => unreachable!(),
+					};
+					helpers_ident = Some(local.clone());
+					ModuleItem::Stmt(Stmt::Decl(Decl::Var(VarDecl {
+						span: DUMMY_SP,
+						kind: VarDeclKind::Const,
+						declare: false,
+						decls: vec![VarDeclarator {
+							span: DUMMY_SP,
+							name: Pat::Ident(local.into()),
+							init: Some(Box::new(Expr::Member(MemberExpr {
+								span: DUMMY_SP,
+								obj: ExprOrSuper::Expr(Box::new(Expr::Ident(quote_ident!("__ALEPH__")))),
+								prop: Box::new(Expr::Ident(quote_ident!("helpers"))),
+								computed: false,
+							}))),
+							definite: false,
+						}],
+					})))
+				}
+				_ => item,
+			})
+			.collect();
+		if let Some(helpers_ident) = helpers_ident {
+			let mut collector = HelperNameCollector {
+				helpers_ident,
+				names: vec![],
+			};
+			items.visit_with(&Invalid { span: DUMMY_SP } as _, &mut collector);
+			self.used.borrow_mut().extend(collector.names);
+		}
+		items.fold_children_with(self)
+	}
+}
+
+/// Move the helper identifiers from the mark of a `Helpers` to the mark of another one, the
+/// helpers enabled before the copy of a program keep referencing the injected helpers of the
+/// `Helpers` the copy is transformed with.
+pub fn rebind_helpers_fold(from: Mark, to: Mark) -> impl Fold {
+	RebindHelpersFold { from, to }
+}
+
+struct RebindHelpersFold {
+	from: Mark,
+	to: Mark,
+}
+
+impl Fold for RebindHelpersFold {
+	noop_fold_type!();
+
+	fn fold_ident(&mut self, mut ident: Ident) -> Ident {
+		if ident.span.ctxt.outer() == self.from {
+			let mut ctxt = ident.span.ctxt;
+			ctxt.remove_mark();
+			ident.span.ctxt = ctxt.apply_mark(self.to);
+		}
+		ident
+	}
+}
+
+// match: swcHelpers.applyDecoratedDescriptor
+struct HelperNameCollector {
+	helpers_ident: Ident,
+	names: Vec<String>,
+}
+
+impl Visit for HelperNameCollector {
+	fn visit_member_expr(&mut self, n: &MemberExpr, _: &dyn Node) {
+		if let (ExprOrSuper::Expr(obj), Expr::Ident(prop), false) = (&n.obj, n.prop.as_ref(), n.computed) {
+			if let Expr::Ident(obj) = obj.as_ref() {
+				if obj.sym == self.helpers_ident.sym && obj.span.ctxt == self.helpers_ident.span.ctxt {
+					self.names.push(prop.sym.as_ref().into());
+				}
+			}
+		}
+		n.visit_children_with(self);
+	}
+}
+
+/// Build the definitions of the shared helpers for a bundle, the `names` are the used helpers
+/// recorded by the resolvers of the bundled modules. The unknown names are ignored.
+/// ```js
+/// function _classCallCheck(instance, Constructor) { ... }
+/// __ALEPH__.helpers = { classCallCheck: _classCallCheck };
+/// ```
+pub fn build_shared_helpers(names: &[String], indent: Indent) -> String {
+	GLOBALS.set(&Globals::new(), || {
+		let helpers = Helpers::new(false);
+		let mut props: Vec<PropOrSpread> = vec![];
+		let mut enabled: Vec<&str> = vec![];
+		for name in names {
+			if enabled.contains(&name.as_str()) {
+				continue;
+			}
+			if let Some(local) = HELPERS.set(&helpers, || enable_helper(name.as_str())) {
+				enabled.push(name.as_str());
+				props.push(PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+					key: PropName::Ident(quote_ident!(name.as_str())),
+					value: Box::new(local),
+				}))));
+			}
+		}
+		let module = Module {
+			span: DUMMY_SP,
+			body: vec![ModuleItem::Stmt(Stmt::Expr(ExprStmt {
+				span: DUMMY_SP,
+				expr: Box::new(Expr::Assign(AssignExpr {
+					span: DUMMY_SP,
+					op: AssignOp::Assign,
+					left: PatOrExpr::Expr(Box::new(Expr::Member(MemberExpr {
+						span: DUMMY_SP,
+						obj: ExprOrSuper::Expr(Box::new(Expr::Ident(quote_ident!("__ALEPH__")))),
+						prop: Box::new(Expr::Ident(quote_ident!("helpers"))),
+						computed: false,
+					}))),
+					right: Box::new(Expr::Object(ObjectLit {
+						span: DUMMY_SP,
+						props,
+					})),
+				})),
+			}))],
+			shebang: None,
+		};
+		let module = HELPERS.set(&helpers, || module.fold_with(&mut inject_helpers()));
+		let cm = Rc::new(SourceMap::default());
+		let mut buf = Vec::new();
+		{
+			let mut emitter = swc_ecmascript::codegen::Emitter {
+				cfg: swc_ecmascript::codegen::Config { minify: false },
+				comments: None,
+				cm: cm.clone(),
+				wr: Box::new(IndentWriter::new(
+					JsWriter::new(cm, "\n", &mut buf, None),
+					indent,
+					None,
+				)),
+			};
+			module.emit_with(&mut emitter).unwrap();
+		}
+		String::from_utf8(buf).unwrap()
+	})
+}
+
+/// Define `enable_helper` with the helpers referenced by the swc passes, by the fields and the
+/// names of their `helper!` calls. The external names and the injected bindings are derived by
+/// swc: `type_of: "typeof"` is `swcHelpers.typeOf` and `_typeof`.
+macro_rules! define_helpers {
+	($($field:ident: $name:tt,)*) => {
+		/// enable the helper by the external name, like `applyDecoratedDescriptor`, returns the
+		/// reference of the injected helper.
+		fn enable_helper(name: &str) -> Option<Expr> {
+			match name {
+				$(external_name!($name) => Some(helper_expr!($field, $name)),)*
+				_ => None,
+			}
+		}
+	};
+}
+
+define_helpers! {
+	apply_decorated_descriptor: "applyDecoratedDescriptor",
+	assert_this_initialized: "assertThisInitialized",
+	async_iterator: "asyncIterator",
+	async_to_generator: "asyncToGenerator",
+	await_async_generator: "awaitAsyncGenerator",
+	class_call_check: "classCallCheck",
+	class_name_tdz_error: "classNameTDZError",
+	class_private_field_destructure: "classPrivateFieldDestructureSet",
+	class_private_field_get: "classPrivateFieldGet",
+	class_private_field_set: "classPrivateFieldSet",
+	class_private_method_get: "classPrivateMethodGet",
+	class_static_private_field_spec_get: "classStaticPrivateFieldSpecGet",
+	class_static_private_field_spec_set: "classStaticPrivateFieldSpecSet",
+	class_static_private_method_get: "classStaticPrivateMethodGet",
+	construct: "construct",
+	create_class: "createClass",
+	decorate: "decorate",
+	define_enumerable_properties: "defineEnumerableProperties",
+	define_property: "defineProperty",
+	extends: "extends",
+	get: "get",
+	get_prototype_of: "getPrototypeOf",
+	inherits: "inherits",
+	initializer_define_property: "initializerDefineProperty",
+	interop_require_default: "interopRequireDefault",
+	interop_require_wildcard: "interopRequireWildcard",
+	object_spread: "objectSpread",
+	object_without_properties: "objectWithoutProperties",
+	possible_constructor_return: "possibleConstructorReturn",
+	set: "set",
+	sliced_to_array: "slicedToArray",
+	tagged_template_literal: "taggedTemplateLiteral",
+	throw: "throw",
+	to_array: "toArray",
+	to_consumable_array: "toConsumableArray",
+	to_property_key: "toPropertyKey",
+	type_of: "typeof",
+	wrap_async_generator: "wrapAsyncGenerator",
+	wrap_native_super: "wrapNativeSuper",
+}
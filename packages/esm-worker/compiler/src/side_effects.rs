@@ -0,0 +1,101 @@
+use swc_ecma_ast::*;
+use swc_ecma_utils::ExprExt;
+
+/// Check if the module has no top-level side effects, only the pure declarations, for the
+/// `sideEffects: false` optimization. The check is conservative: any top-level call, `new`,
+/// assignment, or statement other than the declarations and the exports, is a side effect. The
+/// side-effect imports(`import "./polyfill.js"`) are side effects too.
+pub fn is_side_effect_free(module: &Module) -> bool {
+	module.body.iter().all(|item| match item {
+		ModuleItem::ModuleDecl(decl) => match decl {
+			ModuleDecl::Import(ImportDecl { specifiers, .. }) => !specifiers.is_empty(),
+			ModuleDecl::ExportDecl(ExportDecl { decl, .. }) => is_pure_decl(decl),
+			ModuleDecl::ExportNamed(_) | ModuleDecl::ExportAll(_) => true,
+			ModuleDecl::ExportDefaultDecl(ExportDefaultDecl { decl, .. }) => match decl {
+				DefaultDecl::Class(ClassExpr { class, .. }) => is_pure_class(class),
+				DefaultDecl::Fn(_) | DefaultDecl::TsInterfaceDecl(_) => true,
+			},
+			ModuleDecl::ExportDefaultExpr(ExportDefaultExpr { expr, .. }) => is_pure_expr(expr),
+			_ => false,
+		},
+		ModuleItem::Stmt(stmt) => match stmt {
+			Stmt::Decl(decl) => is_pure_decl(decl),
+			Stmt::Empty(_) => true,
+			// match: "use strict"
+			Stmt::Expr(ExprStmt { expr, .. }) => matches!(expr.as_ref(), Expr::Lit(Lit::Str(_))),
+			_ => false,
+		},
+	})
+}
+
+fn is_pure_decl(decl: &Decl) -> bool {
+	match decl {
+		Decl::Fn(_) | Decl::TsInterface(_) | Decl::TsTypeAlias(_) => true,
+		Decl::Class(ClassDecl { class, .. }) => is_pure_class(class),
+		// match: const a = 1, b = () => {}
+		// the destructuring may call the getters
+		Decl::Var(VarDecl { decls, .. }) => decls.iter().all(|decl| {
+			matches!(decl.name, Pat::Ident(_))
+				&& decl.init.as_ref().map(|init| is_pure_expr(init)).unwrap_or(true)
+		}),
+		Decl::TsEnum(TsEnumDecl { members, .. }) => members
+			.iter()
+			.all(|member| member.init.as_ref().map(|init| is_pure_expr(init)).unwrap_or(true)),
+		Decl::TsModule(TsModuleDecl { declare, .. }) => *declare,
+	}
+}
+
+fn is_pure_class(class: &Class) -> bool {
+	class.decorators.is_empty()
+		&& class.super_class.as_ref().map(|expr| is_pure_expr(expr)).unwrap_or(true)
+		&& class.body.iter().all(|member| match member {
+			ClassMember::Constructor(_)
+			| ClassMember::Empty(_)
+			| ClassMember::TsIndexSignature(_) => true,
+			ClassMember::Method(ClassMethod { key, function, .. }) => {
+				function.decorators.is_empty() && is_pure_prop_name(key)
+			}
+			ClassMember::PrivateMethod(PrivateMethod { function, .. }) => {
+				function.decorators.is_empty()
+			}
+			// the static properties are initialized with the class
+			ClassMember::ClassProp(ClassProp {
+				key,
+				value,
+				is_static,
+				computed,
+				decorators,
+				..
+			}) => {
+				decorators.is_empty()
+					&& (!*computed || !key.may_have_side_effects())
+					&& (!*is_static || value.as_ref().map(|v| is_pure_expr(v)).unwrap_or(true))
+			}
+			ClassMember::PrivateProp(PrivateProp {
+				value,
+				is_static,
+				decorators,
+				..
+			}) => {
+				decorators.is_empty()
+					&& (!*is_static || value.as_ref().map(|v| is_pure_expr(v)).unwrap_or(true))
+			}
+			ClassMember::StaticBlock(_) => false,
+		})
+}
+
+fn is_pure_prop_name(name: &PropName) -> bool {
+	match name {
+		PropName::Computed(ComputedPropName { expr, .. }) => is_pure_expr(expr),
+		_ => true,
+	}
+}
+
+fn is_pure_expr(expr: &Expr) -> bool {
+	match expr {
+		Expr::Class(ClassExpr { class, .. }) => is_pure_class(class),
+		// match: `text`
+		Expr::Tpl(Tpl { exprs, .. }) => exprs.is_empty(),
+		expr => !expr.may_have_side_effects(),
+	}
+}
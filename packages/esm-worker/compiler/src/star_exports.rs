@@ -0,0 +1,104 @@
+use crate::export_names::ExportParser;
+use crate::resolver::Resolver;
+use crate::swc::SWC;
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use swc_common::DUMMY_SP;
+use swc_ecma_ast::*;
+use swc_ecma_utils::quote_ident;
+use swc_ecma_visit::{noop_fold_type, Fold, FoldWith};
+
+/// Expand the star re-exports(`export * from "./a.ts"`) to the named re-exports
+/// (`export { x, y } from "./a.ts"`) by the export names of the dependencies, the sources are
+/// fetched by the `source_fetcher` of the resolver. The names follow the ESM precedence rules:
+/// - the `default` export is never re-exported by a star export.
+/// - the local exports shadow the star re-exported names.
+/// - the ambiguous names exported by more than one star export are excluded.
+///
+/// A star export is kept as it is if the dependency can't be fetched or it has star exports too.
+pub fn expand_star_exports_fold(resolver: Rc<RefCell<Resolver>>) -> impl Fold {
+	ExpandStarExportsFold { resolver }
+}
+
+struct ExpandStarExportsFold {
+	resolver: Rc<RefCell<Resolver>>,
+}
+
+impl ExpandStarExportsFold {
+	/// get the export names of the dependency, returns `None` if the names are unknown.
+	fn get_export_names(&self, specifier: &str) -> Option<Vec<String>> {
+		let resolver = self.resolver.borrow();
+		let source = (resolver.source_fetcher.as_ref()?)(specifier)?;
+		let names = SWC::parse(specifier, source.as_str(), None)
+			.ok()?
+			.parse_export_names()
+			.ok()?;
+		if names.iter().any(|name| name.starts_with('{')) {
+			return None;
+		}
+		Some(names.into_iter().filter(|name| name != "default").collect())
+	}
+}
+
+impl Fold for ExpandStarExportsFold {
+	noop_fold_type!();
+
+	fn fold_module_items(&mut self, items: Vec<ModuleItem>) -> Vec<ModuleItem> {
+		let mut parser = ExportParser::new();
+		let items = items.fold_with(&mut parser);
+		// the local exports and the named re-exports, the star exports are marked as `{src}`
+		let local_names: Vec<String> = parser
+			.names
+			.into_iter()
+			.filter(|name| !name.starts_with('{'))
+			.collect();
+
+		let mut star_names: HashMap<String, Vec<String>> = HashMap::new();
+		for item in &items {
+			if let ModuleItem::ModuleDecl(ModuleDecl::ExportAll(ExportAll { src, .. })) = item {
+				let specifier: String = src.value.as_ref().into();
+				if let Some(names) = self.get_export_names(specifier.as_str()) {
+					star_names.insert(specifier, names);
+				}
+			}
+		}
+		// count the star exports of each name, for the ambiguous names
+		let mut counts: HashMap<&str, usize> = HashMap::new();
+		for names in star_names.values() {
+			for name in names {
+				*counts.entry(name.as_str()).or_default() += 1;
+			}
+		}
+
+		let mut output: Vec<ModuleItem> = Vec::with_capacity(items.len());
+		for item in items {
+			if let ModuleItem::ModuleDecl(ModuleDecl::ExportAll(ExportAll { src, asserts, .. })) = &item {
+				if let Some(names) = star_names.get(src.value.as_ref()) {
+					let specifiers: Vec<ExportSpecifier> = names
+						.iter()
+						.filter(|name| !local_names.contains(name) && counts.get(name.as_str()) == Some(&1))
+						.map(|name| {
+							ExportSpecifier::Named(ExportNamedSpecifier {
+								span: DUMMY_SP,
+								orig: quote_ident!(name.as_str()),
+								exported: None,
+								is_type_only: false,
+							})
+						})
+						.collect();
+					if !specifiers.is_empty() {
+						output.push(ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(NamedExport {
+							span: DUMMY_SP,
+							specifiers,
+							src: Some(src.clone()),
+							type_only: false,
+							asserts: asserts.clone(),
+						})));
+					}
+					continue;
+				}
+			}
+			output.push(item);
+		}
+		output
+	}
+}
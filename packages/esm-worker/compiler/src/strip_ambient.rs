@@ -0,0 +1,83 @@
+use std::collections::HashSet;
+use swc_atoms::JsWord;
+use swc_common::DUMMY_SP;
+use swc_ecma_ast::*;
+use swc_ecma_visit::{Fold, FoldWith, Node, Visit, VisitWith};
+
+/// Remove the `declare` statements(ambient declarations) of TypeScript, they never produce
+/// runtime code. The strip pass of swc emits the code of `declare enum E {}`.
+///
+/// The enums that are not exported and only referenced in the type positions, like `let x: E`,
+/// are removed too. Any reference out of the types, like `E.A`, keeps the enum.
+pub fn strip_ambient_fold() -> impl Fold {
+	StripAmbientFold {}
+}
+
+struct StripAmbientFold {}
+
+// the namespace bodies are visited too, so the `noop_fold_type!` is not used
+impl Fold for StripAmbientFold {
+	fn fold_module_items(&mut self, items: Vec<ModuleItem>) -> Vec<ModuleItem> {
+		let mut collector = ValueRefCollector::default();
+		items.visit_with(&Invalid { span: DUMMY_SP } as _, &mut collector);
+		items
+			.into_iter()
+			.filter(|item| match item {
+				// match: enum E { A, B }
+				ModuleItem::Stmt(Stmt::Decl(Decl::TsEnum(TsEnumDecl { id, declare, .. }))) => {
+					!*declare && collector.refs.contains(&id.sym)
+				}
+				ModuleItem::Stmt(Stmt::Decl(decl)) => !is_ambient(decl),
+				// match: export declare const x: number
+				ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl { decl, .. })) => !is_ambient(decl),
+				_ => true,
+			})
+			.collect::<Vec<ModuleItem>>()
+			.fold_children_with(self)
+	}
+
+	fn fold_stmts(&mut self, stmts: Vec<Stmt>) -> Vec<Stmt> {
+		stmts
+			.into_iter()
+			.filter(|stmt| !matches!(stmt, Stmt::Decl(decl) if is_ambient(decl)))
+			.collect::<Vec<Stmt>>()
+			.fold_children_with(self)
+	}
+}
+
+fn is_ambient(decl: &Decl) -> bool {
+	match decl {
+		Decl::Class(ClassDecl { declare, .. }) => *declare,
+		Decl::Fn(FnDecl { declare, .. }) => *declare,
+		Decl::Var(VarDecl { declare, .. }) => *declare,
+		Decl::TsEnum(TsEnumDecl { declare, .. }) => *declare,
+		Decl::TsModule(TsModuleDecl { declare, .. }) => *declare,
+		Decl::TsInterface(_) | Decl::TsTypeAlias(_) => false,
+	}
+}
+
+/// collect the names referenced out of the type positions, the names of the declarations are
+/// collected too which only keeps more enums.
+#[derive(Default)]
+struct ValueRefCollector {
+	refs: HashSet<JsWord>,
+}
+
+impl Visit for ValueRefCollector {
+	fn visit_ident(&mut self, n: &Ident, _: &dyn Node) {
+		self.refs.insert(n.sym.clone());
+	}
+
+	// match: enum E { A = 1 }, the name of the enum is not a reference
+	fn visit_ts_enum_decl(&mut self, n: &TsEnumDecl, _: &dyn Node) {
+		n.members.visit_with(n as _, self);
+	}
+
+	fn visit_ts_type(&mut self, _: &TsType, _: &dyn Node) {}
+	fn visit_ts_type_ann(&mut self, _: &TsTypeAnn, _: &dyn Node) {}
+	fn visit_ts_type_param_decl(&mut self, _: &TsTypeParamDecl, _: &dyn Node) {}
+	fn visit_ts_type_param_instantiation(&mut self, _: &TsTypeParamInstantiation, _: &dyn Node) {}
+	fn visit_ts_expr_with_type_args(&mut self, _: &TsExprWithTypeArgs, _: &dyn Node) {}
+	fn visit_ts_interface_decl(&mut self, _: &TsInterfaceDecl, _: &dyn Node) {}
+	fn visit_ts_type_alias_decl(&mut self, _: &TsTypeAliasDecl, _: &dyn Node) {}
+}
@@ -0,0 +1,27 @@
+use std::{cell::RefCell, fmt, rc::Rc};
+use swc_ecma_ast::Module;
+use swc_ecma_visit::Fold;
+
+/// A custom pass invoked in the strip phase, right before the TypeScript syntax is stripped, so
+/// the type annotations are available, like to emit the runtime checks of the annotated params:
+/// `StripHook::new(AssertNumberHook {})`.
+#[derive(Clone)]
+pub struct StripHook(Rc<RefCell<dyn Fold>>);
+
+impl StripHook {
+	pub fn new(fold: impl Fold + 'static) -> Self {
+		StripHook(Rc::new(RefCell::new(fold)))
+	}
+}
+
+impl fmt::Debug for StripHook {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str("StripHook")
+	}
+}
+
+impl Fold for StripHook {
+	fn fold_module(&mut self, module: Module) -> Module {
+		self.0.borrow_mut().fold_module(module)
+	}
+}
@@ -1,53 +1,113 @@
-use crate::error::{DiagnosticBuffer, ErrorBuffer};
+use crate::analysis::{collect_string_literals, AnalysisReport};
+use crate::ast_dump::{dump_module, dump_pass, AstDumps};
+use crate::auto_accessor::{
+	auto_accessor_fold, find_unsupported_decorated_members, DecoratorsVersion,
+};
+use crate::cjs_to_esm::cjs_to_esm_fold;
+use crate::comment_filter::filter_comments;
+use crate::constant_fold::constant_fold;
+use crate::css_extract::extract_css_fold;
+use crate::default_export_name::default_export_name_fold;
+use crate::directives::{get_directives, Directives, Reference, ReferenceKind};
+use crate::dynamic_imports::{get_dynamic_imports, DynamicImportSite};
+pub use crate::emit_options::EmitOptions;
+use crate::error::{DiagnosticBuffer, ErrorBuffer, Warning};
+use crate::error_overlay::error_overlay_fold;
 use crate::export_names::ExportParser;
+use crate::export_shape::get_export_shape;
+use crate::facade::ExportName;
+use crate::forbidden::{
+	find_dynamic_code, find_forbidden_features, find_top_level_await, has_top_level_await,
+};
+use crate::glob_import::glob_import_fold;
+use crate::gzip_size::gzip_size;
+use crate::import_attributes::{
+	collect_attributes_clauses, strip_with_clauses, with_clause_to_object,
+	ImportAttributesWriter,
+};
+use crate::inject::{inject_imports_fold, polyfill_methods_fold, provide_fold};
+use crate::inline_constants::inline_constants_fold;
+use crate::jsx_components::{get_jsx_components, JsxComponents};
+use crate::jsx_restore::jsx_restore_fold;
+use crate::output_format::{output_format_fold, output_wrapper_fold, OutputFormat};
+use crate::pure_calls::pure_calls_fold;
+use crate::reexports::optimize_reexports_fold;
+use crate::require_in_esm::{find_require_calls, require_in_esm_fold, RequireMode};
 use crate::resolve_fold::resolve_fold;
-use crate::resolver::{DependencyDescriptor, Resolver};
+use crate::resolver::{
+	append_target_query, BundleOutput, Chunk, DependencyDescriptor, DependencyKind, ExtractedString,
+	GeneratedImportMap, Resolver, UnresolvedPolicy,
+};
+use crate::scope_prefix::scope_prefix_fold;
+use crate::shared_helpers::{rebind_helpers_fold, shared_helpers_fold};
+use crate::side_effects::is_side_effect_free;
 use crate::source_type::SourceType;
+use crate::star_exports::expand_star_exports_fold;
+use crate::strip_ambient::strip_ambient_fold;
+use crate::strip_hook::StripHook;
+use crate::target::{
+	downlevel_fold, find_unsupported_syntax, target_name, GeneratorRuntime,
+};
+use crate::target_query::target_query_fold;
+use crate::text_writer::{IndentWriter, Newline};
+use crate::translation_strings::find_translation_strings;
+use crate::tree_shake_locals::tree_shake_locals_fold;
+use crate::types_only::types_only_fold;
+use crate::unused_imports::find_unused_imports;
+use crate::using_decl::using_decl_fold;
+use crate::TransformOutput;
 
+use indexmap::{IndexMap, IndexSet};
+use std::collections::HashMap;
 use std::{cell::RefCell, path::Path, rc::Rc};
 use swc_common::{
 	chain,
 	comments::SingleThreadedComments,
 	errors::{Handler, HandlerFlags},
-	FileName, Globals, Mark, SourceMap,
+	pass::Repeat,
+	BytePos, FileName, Globals, Mark, SourceMap, Span, Spanned,
 };
 use swc_ecma_transforms_proposal::decorators;
+use swc_ecma_utils::drop_span;
 use swc_ecma_transforms_typescript::strip;
 use swc_ecmascript::{
-	ast::{Module, Program},
+	ast::{
+		AssignOp, ExportAll, ImportDecl, Module, ModuleDecl, ModuleItem, NamedExport, Program, Stmt,
+	},
 	codegen::{text_writer::JsWriter, Node},
-	parser::{lexer::Lexer, EsConfig, JscTarget, StringInput, Syntax, TsConfig},
-	transforms::{fixer, helpers, hygiene, pass::Optional, react, resolver_with_mark},
-	visit::{Fold, FoldWith},
+	parser::{
+		error::SyntaxError,
+		lexer::Lexer,
+		token::{BinOpToken, Keyword, Token, TokenAndSpan, Word},
+		EsConfig, JscTarget, StringInput, Syntax, TsConfig,
+	},
+	transforms::{
+		fixer, helpers, hygiene,
+		optimization::simplify::dce,
+		pass::{noop, Optional},
+		react, resolver_with_mark,
+	},
+	visit::FoldWith,
 };
 
-/// Options for transpiling a module.
-#[derive(Debug, Clone)]
-pub struct EmitOptions {
-	pub jsx_factory: String,
-	pub jsx_fragment_factory: String,
-	pub source_map: bool,
-	pub is_dev: bool,
-}
-
-impl Default for EmitOptions {
-	fn default() -> Self {
-		EmitOptions {
-			jsx_factory: "React.createElement".into(),
-			jsx_fragment_factory: "React.Fragment".into(),
-			is_dev: false,
-			source_map: false,
-		}
-	}
-}
-
+/// The parsed module, it's cheap to clone for transforming the same parse with the different
+/// options: the AST is shared by the clones and only copied by the transform.
 #[derive(Clone)]
 pub struct SWC {
 	pub specifier: String,
-	pub module: Module,
+	pub module: Rc<Module>,
 	pub source_type: SourceType,
 	pub source_map: Rc<SourceMap>,
 	pub comments: SingleThreadedComments,
+	/// the positions of `import defer * as ns from "x"` statements.
+	pub deferred_imports: Vec<BytePos>,
+	/// the positions of the keys of `accessor x = 1` class fields.
+	pub auto_accessors: Vec<BytePos>,
+	/// the positions of `using x = y` declarations, and if they are `await using`.
+	pub using_decls: Vec<(BytePos, bool)>,
+	/// the positions of the import attributes written with the `with` keyword, the attributes are
+	/// parsed as the `asserts`.
+	pub with_clauses: Vec<BytePos>,
 }
 
 impl SWC {
@@ -57,13 +117,6 @@ impl SWC {
 		source: &str,
 		source_type: Option<SourceType>,
 	) -> Result<Self, anyhow::Error> {
-		let source_map = SourceMap::default();
-		let source_file = source_map.new_source_file(
-			FileName::Real(Path::new(specifier).to_path_buf()),
-			source.into(),
-		);
-		let sm = &source_map;
-		let error_buffer = ErrorBuffer::new(specifier);
 		let source_type = match source_type {
 			Some(source_type) => match source_type {
 				SourceType::Unknown => SourceType::from(Path::new(specifier)),
@@ -72,6 +125,33 @@ impl SWC {
 			None => SourceType::from(Path::new(specifier)),
 		};
 		let syntax = get_syntax(&source_type);
+		// the syntax not supported by the parser is rewritten with the tokens of a single lexing,
+		// the rewrites keep the lengths so the tokens and the source map are still valid
+		let tokens = lex(source, syntax, &[DEFER, ACCESSOR, USING, WITH]);
+		let mut source = source.to_owned();
+		let import_defer_offsets = strip_import_defer(&mut source, &tokens);
+		let accessor_offsets = strip_accessor_keyword(&mut source, &tokens);
+		let using_offsets = replace_using_keyword(&mut source, &tokens);
+		let with_clauses = strip_with_clauses(&mut source, &tokens);
+		let source_map = SourceMap::default();
+		let source_file = source_map.new_source_file(
+			FileName::Real(Path::new(specifier).to_path_buf()),
+			source,
+		);
+		let deferred_imports = import_defer_offsets
+			.into_iter()
+			.map(|offset| source_file.start_pos + BytePos(offset))
+			.collect();
+		let auto_accessors = accessor_offsets
+			.into_iter()
+			.map(|offset| source_file.start_pos + BytePos(offset))
+			.collect();
+		let using_decls = using_offsets
+			.into_iter()
+			.map(|(offset, is_await)| (source_file.start_pos + BytePos(offset), is_await))
+			.collect();
+		let sm = &source_map;
+		let error_buffer = ErrorBuffer::new(specifier);
 		let input = StringInput::from(&*source_file);
 		let comments = SingleThreadedComments::default();
 		let lexer = Lexer::new(syntax, JscTarget::Es2020, input, Some(&comments));
@@ -84,48 +164,350 @@ impl SWC {
 				..HandlerFlags::default()
 			},
 		);
-		let module = parser
-			.parse_module()
-			.map_err(move |err| {
-				let mut diagnostic = err.into_diagnostic(&handler);
-				diagnostic.emit();
-				DiagnosticBuffer::from_error_buffer(error_buffer, |span| sm.lookup_char_pos(span.lo))
+		let mut module = match parser.parse_module() {
+			Ok(module) => module,
+			Err(err) => {
+				err.into_diagnostic(&handler).emit();
+				return Err(
+					DiagnosticBuffer::from_error_buffer(error_buffer, |span| sm.lookup_char_pos(span.lo))
+						.into(),
+				);
+			}
+		};
+		// the parser recovers from the sloppy-mode constructs, report them since ES modules are always strict.
+		let strict_mode_errors: Vec<(Span, &str)> = parser
+			.take_errors()
+			.into_iter()
+			.filter_map(|err| {
+				let message = match err.kind() {
+					SyntaxError::WithInStrict => "with statements are not allowed in module code",
+					SyntaxError::LegacyOctal => "legacy octal literals are not allowed in module code",
+					SyntaxError::LegacyDecimal => {
+						"decimals with leading zeros are not allowed in module code"
+					}
+					_ => return None,
+				};
+				Some((err.span(), message))
 			})
-			.unwrap();
+			.collect();
+		if !strict_mode_errors.is_empty() {
+			for (span, message) in strict_mode_errors {
+				handler.struct_span_err(span, message).emit();
+			}
+			return Err(
+				DiagnosticBuffer::from_error_buffer(error_buffer, |span| sm.lookup_char_pos(span.lo)).into(),
+			);
+		}
+
+		// match: import sheet from "./s.css" with { type: "css" }
+		for item in module.body.iter_mut() {
+			let (src, asserts) = match item {
+				ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl { src, asserts, .. }))
+				| ModuleItem::ModuleDecl(ModuleDecl::ExportAll(ExportAll { src, asserts, .. }))
+				| ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(NamedExport {
+					src: Some(src),
+					asserts,
+					..
+				})) => (src, asserts),
+				_ => continue,
+			};
+			let start_pos = source_file.start_pos;
+			if let Some(clause) =
+				with_clauses.iter().find(|clause| start_pos + BytePos(clause.src) == src.span.lo)
+			{
+				*asserts = Some(with_clause_to_object(clause, start_pos));
+			}
+		}
+		let with_clauses = with_clauses
+			.iter()
+			.map(|clause| source_file.start_pos + BytePos(clause.lo))
+			.collect();
 
 		Ok(SWC {
 			specifier: specifier.into(),
-			module,
+			module: Rc::new(module),
 			source_type,
 			source_map: Rc::new(source_map),
 			comments,
+			deferred_imports,
+			auto_accessors,
+			using_decls,
+			with_clauses,
 		})
 	}
 
 	/// parse export names in the module.
 	pub fn parse_export_names(&self) -> Result<Vec<String>, anyhow::Error> {
-		let program = Program::Module(self.module.clone());
-		let mut parser = ExportParser { names: vec![] };
+		let program = Program::Module(self.module.as_ref().clone());
+		let mut parser = ExportParser::new();
 		program.fold_with(&mut parser);
 		Ok(parser.names)
 	}
 
+	/// parse export names in the module in the case-insensitive alphabetical order, for the
+	/// generated modules to be deterministic. The star exports(`{https://esm.sh/react}`) are sorted
+	/// after the names.
+	pub fn parse_sorted_export_names(&self) -> Result<Vec<String>, anyhow::Error> {
+		let mut names = self.parse_export_names()?;
+		names.sort_by_cached_key(|name| (name.starts_with('{'), name.to_lowercase(), name.clone()));
+		Ok(names)
+	}
+
+	/// parse export names in the module, returns the value names and the type-only names. Unlike
+	/// `parse_export_names`, the enums are values and the types of `export { T }` are not.
+	pub fn parse_export_names_with_types(&self) -> Result<(Vec<String>, Vec<String>), anyhow::Error> {
+		let program = Program::Module(self.module.as_ref().clone());
+		let mut parser = ExportParser::new();
+		program.fold_with(&mut parser);
+		Ok((parser.values, parser.types))
+	}
+
+	/// parse the named value re-exports of the module with the sources, like
+	/// `export { default as Thing } from "./m"`.
+	pub fn parse_reexports(&self) -> Result<Vec<ExportName>, anyhow::Error> {
+		let program = Program::Module(self.module.as_ref().clone());
+		let mut parser = ExportParser::new();
+		program.fold_with(&mut parser);
+		Ok(parser.reexports)
+	}
+
+	/// print the module AST for debugging.
+	pub fn dump_ast(&self) -> String {
+		dump_module(&self.module)
+	}
+
+	/// extract the triple-slash references and the `@ts-*` directives from the comments.
+	pub fn directives(&self) -> Directives {
+		get_directives(&self.comments, &self.source_map)
+	}
+
+	/// collect the component names and the intrinsic element names used in the JSX.
+	pub fn jsx_components(&self) -> JsxComponents {
+		get_jsx_components(&self.module)
+	}
+
+	/// collect the dynamic `import()` call sites with the positions, for building the chunk map.
+	pub fn dynamic_imports(&self) -> Vec<DynamicImportSite> {
+		get_dynamic_imports(&self.module, &self.source_map)
+	}
+
+	/// check if the module has no top-level side effects, for the `sideEffects: false`
+	/// optimization.
+	pub fn is_side_effect_free(&self) -> bool {
+		is_side_effect_free(&self.module)
+	}
+
+	/// check if the module has the top-level await, the non-ESM output formats wrap it in an
+	/// async function.
+	pub fn has_top_level_await(&self) -> bool {
+		has_top_level_await(&self.module)
+	}
+
 	/// transform a JS/TS/JSX/TSX file into a JS file, based on the supplied options.
+	/// the AST dumps after each pass are stored in the `ast_dumps` of the resolver if the
+	/// `dump_ast` option is set.
 	pub fn transform(
-		self,
+		&self,
 		resolver: Rc<RefCell<Resolver>>,
 		options: &EmitOptions,
 	) -> Result<(String, Option<String>), anyhow::Error> {
-		swc_common::GLOBALS.set(&Globals::new(), || {
-			let top_level_mark = Mark::fresh(Mark::root());
-			let specifier_is_remote = resolver.borrow().specifier_is_remote;
-			let jsx = match self.source_type {
-				SourceType::JSX => true,
-				SourceType::TSX => true,
-				_ => false,
-			};
-			let passes = chain!(
-				Optional::new(
+		let (code, map) = self.transform_with_raw_map(resolver, options)?;
+		Ok((code, map.map(to_json_source_map)))
+	}
+
+	/// transform the module like `transform`, returns the raw source map instead of the JSON string
+	/// to allow callers to manipulate or compose the map.
+	pub fn transform_with_raw_map(
+		&self,
+		resolver: Rc<RefCell<Resolver>>,
+		options: &EmitOptions,
+	) -> Result<(String, Option<sourcemap::SourceMap>), anyhow::Error> {
+		let output = self
+			.transform_with_targets(resolver.clone(), options, &[options.target])?
+			.remove(0);
+		let mut resolver = resolver.borrow_mut();
+		resolver.split_imports = output.split_imports;
+		resolver.star_exports = output.star_exports;
+		resolver.chunks = output.chunks;
+		resolver.resolved_bare_specifiers = output.import_map;
+		Ok((output.code, output.map))
+	}
+
+	/// transform the module for each of the `targets`, the module is parsed once and the shared
+	/// passes (like `strip` and `jsx`) run once, only the lowering of the syntax and the code
+	/// generation run for each target. The outputs are returned in the order of the `targets`.
+	pub fn transform_targets(
+		&self,
+		resolver: Rc<RefCell<Resolver>>,
+		base_options: &EmitOptions,
+		targets: Vec<JscTarget>,
+	) -> Result<Vec<(JscTarget, TransformOutput)>, anyhow::Error> {
+		let outputs = self.transform_with_targets(resolver.clone(), base_options, &targets)?;
+		let resolver = resolver.borrow();
+		Ok(
+			targets
+				.into_iter()
+				.zip(outputs)
+				.map(|(target, output)| {
+					let gzip_size = base_options.gzip_size.then(|| gzip_size(&output.code));
+					let import_map = base_options
+						.generate_import_map
+						.then(|| GeneratedImportMap::from_resolved(&output.import_map))
+						.filter(|map| !map.imports.is_empty());
+					(
+						target,
+						TransformOutput {
+							code: output.code,
+							deps: output.deps,
+							star_exports: output.star_exports,
+							bundle: resolver.bundle_mode.then(|| resolver.bundle_output.clone()),
+							jsx_inline_styles: resolver.jsx_inline_styles.clone(),
+							jsx_static_class_names: resolver.jsx_static_class_names.clone().into_iter().collect(),
+							extracted_css: resolver.extracted_css.clone(),
+							extracted_strings: resolver.extracted_strings.clone(),
+							export_shape: resolver.export_shape.clone(),
+							map: output.map.map(to_json_source_map),
+							warnings: resolver.warnings.clone(),
+							ast_dumps: resolver.ast_dumps.clone(),
+							helpers: output.helpers.into_iter().collect(),
+							import_map,
+							imports: output.split_imports.as_ref().map(|split| split.0.clone()),
+							body: output.split_imports.map(|split| split.1),
+							gzip_size,
+							chunks: output.chunks,
+						},
+					)
+				})
+				.collect(),
+		)
+	}
+
+	/// format the errors with the positions, one per line: `{message} at {specifier}:{line}:{col}`.
+	fn format_errors(&self, errors: Vec<(String, Span)>) -> anyhow::Error {
+		let messages: Vec<String> = errors
+			.into_iter()
+			.map(|(message, span)| {
+				let loc = self.source_map.lookup_char_pos(span.lo);
+				format!(
+					"{} at {}:{}:{}",
+					message, self.specifier, loc.line, loc.col_display
+				)
+			})
+			.collect();
+		anyhow::anyhow!(messages.join("\n"))
+	}
+
+	/// run the shared passes of the targets, like `resolve` and `strip`, after recording the
+	/// warnings and checking the errors of the module. It runs in the `GLOBALS` and the `HELPERS`
+	/// of the transform.
+	fn transform_front(
+		&self,
+		resolver: Rc<RefCell<Resolver>>,
+		options: &EmitOptions,
+		targets: &[JscTarget],
+		directives: &Directives,
+		dumps: Option<AstDumps>,
+		top_level_mark: Mark,
+	) -> Result<Program, anyhow::Error> {
+		{
+			let mut resolver = resolver.borrow_mut();
+			resolver.unresolved_specifiers.clear();
+			resolver.warnings.clear();
+			resolver.extracted_strings.clear();
+			resolver.chunks.clear();
+			resolver.bundle_output = BundleOutput::default();
+		}
+		// the fragment factory of the `@jsxFrag` pragma overrides the option
+		let jsx_fragment_factory = directives
+			.jsx_fragment_factory
+			.clone()
+			.unwrap_or_else(|| options.jsx_fragment_factory.clone());
+		let unused_imports = find_unused_imports(
+			&self.module,
+			&[options.jsx_factory.clone(), jsx_fragment_factory.clone()],
+		);
+		for (name, span) in unused_imports {
+			resolver.borrow_mut().warnings.push(Warning::new(
+				format!("'{}' is imported but never used", name),
+				self.source_map.lookup_char_pos(span.lo),
+			));
+		}
+		if options.warn_on_dynamic_code {
+			for (message, span) in find_dynamic_code(&self.module) {
+				resolver.borrow_mut().warnings.push(Warning::new(
+					message,
+					self.source_map.lookup_char_pos(span.lo),
+				));
+			}
+		}
+		if options.extract_strings {
+			for (key, span) in find_translation_strings(&self.module, &options.translation_functions) {
+				let loc = self.source_map.lookup_char_pos(span.lo);
+				resolver.borrow_mut().extracted_strings.push(ExtractedString {
+					key,
+					line: loc.line,
+					col: loc.col_display,
+				});
+			}
+		}
+		let mut errors: Vec<(String, Span)> = vec![];
+		for target in targets {
+			for error in find_unsupported_syntax(&self.module, *target) {
+				if !errors.contains(&error) {
+					errors.push(error);
+				}
+			}
+		}
+		// the non-ESM formats wrap the module with the top-level await in an async function
+		if options.output_format != OutputFormat::Esm {
+			if let Some(span) = find_top_level_await(&self.module) {
+				for target in targets.iter().filter(|target| **target < JscTarget::Es2017) {
+					let message = format!(
+						"top-level await can't be wrapped in an async function for the {} target",
+						target_name(*target)
+					);
+					errors.push((message, span));
+				}
+			}
+		}
+		errors.extend(find_forbidden_features(&self.module, &options.forbidden));
+		errors.extend(find_require_calls(&self.module, options.require_in_esm));
+		errors.extend(find_unsupported_decorated_members(
+			&self.module,
+			options.decorators_version,
+			&self.auto_accessors,
+		));
+		if !errors.is_empty() {
+			return Err(self.format_errors(errors));
+		}
+
+		let glob_mark = Mark::fresh(Mark::root());
+		let specifier_is_remote = resolver.borrow().specifier_is_remote;
+		let jsx = match self.source_type {
+			SourceType::JSX => true,
+			SourceType::TSX => true,
+			_ => false,
+		};
+		let mut front = chain!(
+			Optional::new(
+				dump_pass("inject", inject_imports_fold(options.inject.clone()), dumps.clone()),
+				!options.inject.is_empty()
+			),
+			Optional::new(
+				dump_pass("provide", provide_fold(options.provide.clone()), dumps.clone()),
+				!options.provide.is_empty()
+			),
+			Optional::new(
+				dump_pass(
+					"polyfill_methods",
+					polyfill_methods_fold(options.polyfill_methods.clone()),
+					dumps.clone()
+				),
+				!options.polyfill_methods.is_empty()
+			),
+			Optional::new(
+				dump_pass(
+					"react_refresh",
 					react::refresh(
 						true,
 						Some(react::RefreshOptions {
@@ -136,66 +518,508 @@ impl SWC {
 						self.source_map.clone(),
 						Some(&self.comments),
 					),
-					options.is_dev && !specifier_is_remote
+					dumps.clone()
 				),
-				Optional::new(resolver_with_mark(top_level_mark), jsx),
-				Optional::new(
+				options.is_dev && !specifier_is_remote
+			),
+			Optional::new(
+				dump_pass("resolver", resolver_with_mark(top_level_mark), dumps.clone()),
+				jsx
+					|| options.output_format != OutputFormat::Esm
+					|| options.dce
+					|| options.inline_constants
+					|| options.scope_prefix.is_some()
+			),
+			Optional::new(
+				dump_pass(
+					"jsx",
 					react::jsx(
 						self.source_map.clone(),
 						Some(&self.comments),
 						react::Options {
 							pragma: options.jsx_factory.clone(),
-							pragma_frag: options.jsx_fragment_factory.clone(),
+							pragma_frag: jsx_fragment_factory.clone(),
 							// this will use `Object.assign()` instead of the `_extends` helper when spreading props.
 							use_builtins: true,
+							// emit the namespaced tags(`<svg:rect />`) as the string tags, the
+							// default throwing needs the error handler that isn't set.
+							throw_if_namespace: false,
 							..Default::default()
 						},
 						top_level_mark
 					),
-					jsx
+					dumps.clone()
+				),
+				jsx
+			),
+			Optional::new(
+				dump_pass("require_in_esm", require_in_esm_fold(), dumps.clone()),
+				options.require_in_esm == RequireMode::DynamicImport
+			),
+			Optional::new(
+				dump_pass("cjs_to_esm", cjs_to_esm_fold(), dumps.clone()),
+				options.cjs_to_esm
+			),
+			Optional::new(
+				dump_pass(
+					"inline_constants",
+					inline_constants_fold(resolver.clone()),
+					dumps.clone()
+				),
+				options.inline_constants
+			),
+			Optional::new(
+				dump_pass(
+					"expand_star_exports",
+					expand_star_exports_fold(resolver.clone()),
+					dumps.clone()
+				),
+				options.expand_star_exports
+			),
+			Optional::new(
+				dump_pass(
+					"glob_imports",
+					glob_import_fold(resolver.clone(), glob_mark),
+					dumps.clone()
+				),
+				options.glob_imports
+			),
+			Optional::new(
+				dump_pass(
+					"extract_css",
+					extract_css_fold(
+						resolver.clone(),
+						options.css_tags.clone(),
+						options.replace_extracted_css
+					),
+					dumps.clone()
+				),
+				options.extract_css
+			),
+			Optional::new(
+				dump_pass("optimize_reexports", optimize_reexports_fold(), dumps.clone()),
+				options.optimize_reexports
+			),
+			dump_pass(
+				"resolve",
+				resolve_fold(
+					resolver.clone(),
+					options.is_dev,
+					self.deferred_imports.clone(),
+					glob_mark,
+					options.import_attributes
+				),
+				dumps.clone()
+			),
+			Optional::new(
+				dump_pass(
+					"default_export_name",
+					default_export_name_fold(self.specifier.as_str()),
+					dumps.clone()
+				),
+				options.name_default_export
+			),
+			Optional::new(
+				dump_pass(
+					"auto_accessor",
+					auto_accessor_fold(self.auto_accessors.clone()),
+					dumps.clone()
 				),
-				resolve_fold(resolver.clone(), options.is_dev),
+				!self.auto_accessors.is_empty()
+			),
+			dump_pass(
+				"decorators",
 				decorators::decorators(decorators::Config {
-					legacy: true,
+					legacy: options.decorators_version == DecoratorsVersion::Legacy,
 					emit_metadata: false
 				}),
-				helpers::inject_helpers(),
+				dumps.clone()
+			),
+			dump_pass("strip_ambient", strip_ambient_fold(), dumps.clone()),
+			Optional::new(
+				dump_pass("types_only", types_only_fold(), dumps.clone()),
+				options.types_only
+			),
+			Optional::new(
+				dump_pass(
+					"strip_hook",
+					options.strip_hook.clone().unwrap_or_else(|| StripHook::new(noop())),
+					dumps.clone()
+				),
+				options.strip_hook.is_some()
+			),
+			dump_pass(
+				"strip",
 				strip::strip_with_config(strip::Config {
 					use_define_for_class_fields: true,
 					..Default::default()
 				}),
-				fixer(Some(&self.comments)),
-				hygiene()
-			);
+				dumps.clone()
+			),
+			Optional::new(
+				dump_pass("using_decl", using_decl_fold(self.using_decls.clone()), dumps.clone()),
+				!self.using_decls.is_empty()
+			),
+			Optional::new(
+				dump_pass(
+					"constant_fold",
+					constant_fold(),
+					dumps.clone()
+				),
+				options.constant_fold
+			),
+			Optional::new(
+				dump_pass("tree_shake_locals", tree_shake_locals_fold(), dumps.clone()),
+				options.tree_shake_locals
+			),
+			Optional::new(
+				dump_pass(
+					"dce",
+					chain!(
+						pure_calls_fold(options.pure_functions.clone()),
+						Repeat::new(dce::dce(Default::default())),
+						pure_calls_fold(options.pure_functions.clone())
+					),
+					dumps.clone()
+				),
+				options.dce
+			),
+			Optional::new(
+				dump_pass(
+					"scope_prefix",
+					scope_prefix_fold(options.scope_prefix.as_deref().unwrap_or_default()),
+					dumps.clone()
+				),
+				options.scope_prefix.is_some()
+			),
+			Optional::new(
+				dump_pass(
+					"jsx_restore",
+					jsx_restore_fold(options.jsx_factory.as_str(), jsx_fragment_factory.as_str()),
+					dumps.clone()
+				),
+				options.restore_jsx
+			),
+			Optional::new(
+				dump_pass(
+					"error_overlay",
+					error_overlay_fold(options.error_handler.clone(), options.catch_rejections),
+					dumps.clone()
+				),
+				options.is_dev && options.error_overlay
+			)
+		);
 
-			let (code, map) = self.apply_fold(passes, options.source_map).unwrap();
-			let mut resolver = resolver.borrow_mut();
+		let mut module = self.module.as_ref().clone();
+		// the parser records the shebang line in the module, the positions are kept.
+		if !options.emit_shebang {
+			module.shebang = None;
+		}
+		let program = Program::Module(module).fold_with(&mut front);
+		{
+			let resolver = resolver.borrow();
+			let is_error = resolver.on_unresolved == Some(UnresolvedPolicy::Error);
+			if (is_error || options.require_all_resolved)
+				&& !resolver.unresolved_specifiers.is_empty()
+			{
+				return Err(self.format_errors(resolver.unresolved_specifiers.clone()));
+			}
+		}
+		if let (true, Program::Module(module)) = (options.export_shape, &program) {
+			resolver.borrow_mut().export_shape = get_export_shape(module);
+		}
+		Ok(program)
+	}
 
-			// remove unused deps by tree-shaking
-			let mut deps: Vec<DependencyDescriptor> = Vec::new();
-			for dep in resolver.deps.clone() {
-				if resolver.star_exports.contains(&dep.specifier)
-					|| code.contains(to_str_lit(dep.specifier.as_str()).as_str())
-				{
-					deps.push(dep);
+	/// run the shared passes once and the passes of each target, the syntax newer than each
+	/// target is lowered by the `downlevel` pass of the target.
+	fn transform_with_targets(
+		&self,
+		resolver: Rc<RefCell<Resolver>>,
+		options: &EmitOptions,
+		targets: &[JscTarget],
+	) -> Result<Vec<TargetOutput>, anyhow::Error> {
+		let exports_comment = if options.exports_comment {
+			// the star exports(`{./util.ts}`) are not listed
+			let names: Vec<String> = self
+				.parse_export_names()?
+				.into_iter()
+				.filter(|name| !name.starts_with('{'))
+				.collect();
+			Some(format!("/* esm.sh exports: {} */", names.join(",")))
+		} else {
+			None
+		};
+		let directives = self.directives();
+		let dumps: Option<AstDumps> = if options.dump_ast {
+			Some(Rc::new(RefCell::new(vec![])))
+		} else {
+			None
+		};
+
+		swc_common::GLOBALS.set(&Globals::new(), || {
+			let top_level_mark = Mark::fresh(Mark::root());
+			// each target injects the helpers of the shared passes and the helpers of its lowering
+			let front_helpers = helpers::Helpers::new(options.shared_helpers);
+			let front = helpers::HELPERS.set(&front_helpers, || {
+				let program = self.transform_front(
+					resolver.clone(),
+					options,
+					targets,
+					&directives,
+					dumps.clone(),
+					top_level_mark,
+				)?;
+				// the imported generator runtime is a dependency of the targets lowering the generators,
+				// it's removed by the tree-shaking of the deps if it's not used.
+				let lower_async = targets.iter().any(|target| *target < JscTarget::Es2017);
+				let generator_runtime = match &options.generator_runtime {
+					GeneratorRuntime::Import(specifier) if lower_async => {
+						GeneratorRuntime::Import(resolver.borrow_mut().resolve(specifier, false))
+					}
+					runtime => runtime.clone(),
+				};
+				let inlined_runtime = match &options.generator_runtime {
+					GeneratorRuntime::Inline(code) if lower_async => {
+						Some(parse_inlined_runtime(code)?)
+					}
+					_ => None,
+				};
+				let all_deps = resolver.borrow().deps.clone();
+				Ok::<_, anyhow::Error>((program, generator_runtime, inlined_runtime, all_deps))
+			});
+			let (program, generator_runtime, inlined_runtime, all_deps) = front?;
+
+			let mut outputs: Vec<TargetOutput> = vec![];
+			for target in targets {
+				let target_helpers = helpers::Helpers::new(options.shared_helpers);
+				target_helpers.extend_from(&front_helpers);
+				let used_helpers = Rc::new(RefCell::new(IndexSet::new()));
+				// the remote deps request the builds of the target
+				let target_urls: HashMap<String, String> = all_deps
+					.iter()
+					.filter(|_| options.target_query)
+					.filter_map(|dep| {
+						let url = append_target_query(dep.specifier.as_str(), target_name(*target))?;
+						Some((dep.specifier.clone(), url))
+					})
+					.collect();
+				let mut passes = chain!(
+					rebind_helpers_fold(front_helpers.mark(), target_helpers.mark()),
+					Optional::new(
+						dump_pass(
+							"downlevel",
+							downlevel_fold(
+								*target,
+								&generator_runtime,
+								inlined_runtime.clone(),
+								top_level_mark
+							),
+							dumps.clone()
+						),
+						*target < JscTarget::Es2022
+					),
+					Optional::new(
+						dump_pass(
+							"target_query",
+							target_query_fold(target_urls.clone()),
+							dumps.clone()
+						),
+						!target_urls.is_empty()
+					),
+					dump_pass(
+						"output_format",
+						output_format_fold(
+							&options.output_format,
+							// the frozen exports object can't be reassigned
+							options.live_bindings || options.freeze_exports,
+							options.esmodule_interop,
+							top_level_mark
+						),
+						dumps.clone()
+					),
+					dump_pass("inject_helpers", helpers::inject_helpers(), dumps.clone()),
+					Optional::new(
+						dump_pass(
+							"shared_helpers",
+							shared_helpers_fold(used_helpers.clone()),
+							dumps.clone()
+						),
+						options.shared_helpers
+					),
+					dump_pass(
+						"output_wrapper",
+						output_wrapper_fold(
+							options.output_format.clone(),
+							options.global_name.clone(),
+							options.emit_use_strict,
+							options.import_meta_url.clone(),
+							options.freeze_exports
+						),
+						dumps.clone()
+					),
+					dump_pass("fixer", fixer(Some(&self.comments)), dumps.clone()),
+					dump_pass("hygiene", hygiene(), dumps.clone())
+				);
+				let program = helpers::HELPERS.set(&target_helpers, || {
+					program.clone().fold_with(&mut passes)
+				});
+				let (mut code, mut map) = self.emit(&program, options);
+				if let Some(comment) = &exports_comment {
+					let (with_comment, shifted_map) =
+						prepend_line(code, map, comment, options.newline);
+					code = with_comment;
+					map = shifted_map;
+				}
+				if let (Some(input_map), Some(raw_map)) = (&options.input_source_map, &map) {
+					let input_map = sourcemap::SourceMap::from_slice(input_map.as_bytes())
+						.map_err(|err| anyhow::anyhow!("invalid input source map: {}", err))?;
+					map = Some(compose_source_maps(raw_map, &input_map));
+				}
+				if let (Some(file), Some(map)) = (&options.source_map_file, &mut map) {
+					map.set_file(Some(file.as_str()));
+				}
+				if options.verify_output {
+					verify_js(self.specifier.as_str(), code.as_str())?;
+				}
+				let split_imports = if options.split_imports {
+					Some(split_imports(self.specifier.as_str(), code.as_str(), options.newline)?)
+				} else {
+					None
+				};
+
+				let resolver = resolver.borrow();
+				let with_target = |specifier: &String| {
+					target_urls.get(specifier).unwrap_or(specifier).clone()
+				};
+				let target_deps: Vec<DependencyDescriptor> = all_deps
+					.iter()
+					.map(|dep| DependencyDescriptor {
+						specifier: with_target(&dep.specifier),
+						..dep.clone()
+					})
+					.collect();
+				let star_exports: Vec<String> =
+					resolver.star_exports.iter().map(with_target).collect();
+				let deps = tree_shake_deps(
+					&target_deps,
+					&star_exports,
+					&directives.references,
+					|specifier| code.contains(to_str_lit(specifier).as_str()),
+				);
+				// the chunks of the dynamic imports removed from the code of the target are dropped
+				let chunks = resolver
+					.chunks
+					.iter()
+					.map(|chunk| Chunk {
+						specifier: with_target(&chunk.specifier),
+						..chunk.clone()
+					})
+					.filter(|chunk| deps.iter().any(|dep| dep.specifier == chunk.specifier))
+					.collect();
+				let import_map = resolver
+					.resolved_bare_specifiers
+					.iter()
+					.map(|(specifier, url)| (specifier.clone(), with_target(url)))
+					.collect();
+				let helpers = used_helpers.take();
+				outputs.push(TargetOutput {
+					code,
+					map,
+					deps,
+					helpers,
+					split_imports,
+					chunks,
+					star_exports,
+					import_map,
+				});
+			}
+			// the deps and the helpers of the resolver are used by any of the targets
+			let mut resolver = resolver.borrow_mut();
+			let mut deps: Vec<DependencyDescriptor> = vec![];
+			for dep in outputs.iter().flat_map(|output| output.deps.iter()) {
+				let first = deps
+					.iter_mut()
+					.find(|d| d.specifier == dep.specifier && d.kind == dep.kind);
+				match first {
+					Some(first) => first.merge(dep),
+					None => deps.push(dep.clone()),
 				}
 			}
 			resolver.deps = deps;
-
-			Ok((code, map))
+			for output in &outputs {
+				resolver.helpers.extend(output.helpers.iter().cloned());
+			}
+			resolver.chunks.retain(|chunk| {
+				outputs
+					.iter()
+					.any(|output| output.chunks.iter().any(|c| c.name == chunk.name))
+			});
+			if let Some(dumps) = dumps {
+				resolver.ast_dumps = dumps.take();
+			}
+			Ok(outputs)
 		})
 	}
 
-	/// Apply transform with the fold.
-	pub fn apply_fold<T: Fold>(
+	/// analyze the module with the shared passes of the transform, the lowering of the targets and
+	/// the code generation are skipped. The deps and the warnings are recorded in the resolver like
+	/// `transform`.
+	pub fn analyze(
 		&self,
-		mut fold: T,
-		source_map: bool,
-	) -> Result<(String, Option<String>), anyhow::Error> {
-		let program = Program::Module(self.module.clone());
-		let program = helpers::HELPERS.set(&helpers::Helpers::new(false), || {
-			program.fold_with(&mut fold)
-		});
+		resolver: Rc<RefCell<Resolver>>,
+		options: &EmitOptions,
+	) -> Result<AnalysisReport, anyhow::Error> {
+		let directives = self.directives();
+		let dumps: Option<AstDumps> = if options.dump_ast {
+			Some(Rc::new(RefCell::new(vec![])))
+		} else {
+			None
+		};
+		let program = swc_common::GLOBALS.set(&Globals::new(), || {
+			let top_level_mark = Mark::fresh(Mark::root());
+			helpers::HELPERS.set(&helpers::Helpers::new(options.shared_helpers), || {
+				self.transform_front(
+					resolver.clone(),
+					options,
+					&[],
+					&directives,
+					dumps.clone(),
+					top_level_mark,
+				)
+			})
+		})?;
+		let mut resolver = resolver.borrow_mut();
+		// the deps are tree-shaken by the string literals of the program instead of the
+		// emitted code.
+		let literals = collect_string_literals(&program);
+		resolver.deps = tree_shake_deps(
+			&resolver.deps,
+			&resolver.star_exports,
+			&directives.references,
+			|specifier| literals.contains(specifier),
+		);
+		if let Some(dumps) = dumps {
+			resolver.ast_dumps = dumps.take();
+		}
+		// the star exports(`{./util.ts}`) are reported by the deps
+		let export_names = self
+			.parse_export_names()?
+			.into_iter()
+			.filter(|name| !name.starts_with('{'))
+			.collect();
+		Ok(AnalysisReport {
+			deps: resolver.deps.clone(),
+			export_names,
+			warnings: resolver.warnings.clone(),
+			has_top_level_await: self.has_top_level_await(),
+			is_side_effect_free: self.is_side_effect_free(),
+		})
+	}
+
+	/// print the program, with the source map if the `source_map` option is set.
+	fn emit(&self, program: &Program, options: &EmitOptions) -> (String, Option<sourcemap::SourceMap>) {
+		let source_map = options.source_map;
 		let mut buf = Vec::new();
 		let mut src_map_buf = Vec::new();
 		let src_map = if source_map {
@@ -203,18 +1027,32 @@ impl SWC {
 		} else {
 			None
 		};
+		let filtered_comments = options
+			.comment_filter
+			.as_ref()
+			.map(|filter| filter_comments(&self.comments, filter));
+		let clauses = match program {
+			Program::Module(module) => {
+				collect_attributes_clauses(module, options.import_attributes, &self.with_clauses)
+			}
+			Program::Script(_) => HashMap::new(),
+		};
 		{
-			let writer = Box::new(JsWriter::new(
-				self.source_map.clone(),
-				"\n",
-				&mut buf,
-				src_map,
+			let js_writer =
+				JsWriter::new(self.source_map.clone(), options.newline.as_str(), &mut buf, src_map);
+			let writer = Box::new(ImportAttributesWriter::new(
+				IndentWriter::new(
+					js_writer,
+					options.indent,
+					options.line_width.filter(|_| !options.minify),
+				),
+				clauses,
 			));
 			let mut emitter = swc_ecmascript::codegen::Emitter {
 				cfg: swc_ecmascript::codegen::Config {
-					minify: false, // todo: use swc minify in the future, currently use terser
+					minify: options.minify,
 				},
-				comments: Some(&self.comments),
+				comments: Some(filtered_comments.as_ref().unwrap_or(&self.comments)),
 				cm: self.source_map.clone(),
 				wr: writer,
 			};
@@ -224,17 +1062,359 @@ impl SWC {
 		// output
 		let src = String::from_utf8(buf).unwrap();
 		if source_map {
-			let mut buf = Vec::new();
-			self
-				.source_map
-				.build_source_map_from(&mut src_map_buf, None)
-				.to_writer(&mut buf)
-				.unwrap();
-			Ok((src, Some(String::from_utf8(buf).unwrap())))
+			let map = self.source_map.build_source_map_from(&mut src_map_buf, None);
+			(src, Some(map))
+		} else {
+			(src, None)
+		}
+	}
+}
+
+/// the output of a target, see `SWC::transform_targets`.
+struct TargetOutput {
+	code: String,
+	map: Option<sourcemap::SourceMap>,
+	deps: Vec<DependencyDescriptor>,
+	helpers: IndexSet<String>,
+	split_imports: Option<(String, String)>,
+	chunks: Vec<Chunk>,
+	star_exports: Vec<String>,
+	// the resolved bare specifiers with the target query
+	import_map: IndexMap<String, String>,
+}
+
+/// compose the source map of the transform with the source map of the input, the tokens that
+/// are not mapped by the input map are dropped.
+fn compose_source_maps(
+	map: &sourcemap::SourceMap,
+	input_map: &sourcemap::SourceMap,
+) -> sourcemap::SourceMap {
+	let mut builder = sourcemap::SourceMapBuilder::new(map.get_file());
+	for (idx, source) in input_map.sources().enumerate() {
+		let src_id = builder.add_source(source);
+		builder.set_source_contents(src_id, input_map.get_source_contents(idx as u32));
+	}
+	for token in map.tokens() {
+		let (src_line, src_col) = token.get_src();
+		let orig = match input_map.lookup_token(src_line, src_col) {
+			Some(orig) if orig.get_dst_line() == src_line && orig.has_source() => orig,
+			_ => continue,
+		};
+		builder.add(
+			token.get_dst_line(),
+			token.get_dst_col(),
+			orig.get_src_line(),
+			orig.get_src_col(),
+			orig.get_source(),
+			token.get_name().or_else(|| orig.get_name()),
+		);
+	}
+	builder.into_sourcemap()
+}
+
+/// insert the line at the top of the code, after the shebang line, the lines of the source map
+/// are shifted.
+fn prepend_line(
+	code: String,
+	map: Option<sourcemap::SourceMap>,
+	line: &str,
+	newline: Newline,
+) -> (String, Option<sourcemap::SourceMap>) {
+	let (pos, dst_line) = if code.starts_with("#!") {
+		(code.find('\n').map(|i| i + 1).unwrap_or(code.len()), 1)
+	} else {
+		(0, 0)
+	};
+	let code = format!("{}{}{}{}", &code[..pos], line, newline.as_str(), &code[pos..]);
+	let map = map.map(|map| {
+		let mut builder = sourcemap::SourceMapBuilder::new(map.get_file());
+		for (idx, source) in map.sources().enumerate() {
+			let src_id = builder.add_source(source);
+			builder.set_source_contents(src_id, map.get_source_contents(idx as u32));
+		}
+		for token in map.tokens() {
+			let line = token.get_dst_line();
+			builder.add(
+				if line >= dst_line { line + 1 } else { line },
+				token.get_dst_col(),
+				token.get_src_line(),
+				token.get_src_col(),
+				token.get_source(),
+				token.get_name(),
+			);
+		}
+		builder.into_sourcemap()
+	});
+	(code, map)
+}
+
+/// serialize the source map to the JSON string.
+fn to_json_source_map(map: sourcemap::SourceMap) -> String {
+	let mut buf = Vec::new();
+	map.to_writer(&mut buf).unwrap();
+	String::from_utf8(buf).unwrap()
+}
+
+/// check if the code is syntactically valid JavaScript.
+pub fn verify_js(specifier: &str, code: &str) -> Result<(), anyhow::Error> {
+	let syntax = Syntax::Es(get_es_config(false));
+	// the parser doesn't support the `with` import attributes
+	let tokens = lex(code, syntax, &[WITH]);
+	let mut code = code.to_owned();
+	strip_with_clauses(&mut code, &tokens);
+	let source_map = SourceMap::default();
+	let source_file =
+		source_map.new_source_file(FileName::Real(Path::new(specifier).to_path_buf()), code);
+	let error_buffer = ErrorBuffer::new(specifier);
+	let input = StringInput::from(&*source_file);
+	let lexer = Lexer::new(syntax, JscTarget::Es2020, input, None);
+	let mut parser = swc_ecmascript::parser::Parser::new_from(lexer);
+	let handler = Handler::with_emitter_and_flags(
+		Box::new(error_buffer.clone()),
+		HandlerFlags {
+			can_emit_warnings: true,
+			dont_buffer_diagnostics: true,
+			..HandlerFlags::default()
+		},
+	);
+	let mut errors = parser.take_errors();
+	match parser.parse_module() {
+		Ok(_) => errors.extend(parser.take_errors()),
+		Err(err) => errors.push(err),
+	};
+	if errors.is_empty() {
+		return Ok(());
+	}
+	for err in errors {
+		err.into_diagnostic(&handler).emit();
+	}
+	let diagnostics =
+		DiagnosticBuffer::from_error_buffer(error_buffer, |span| source_map.lookup_char_pos(span.lo));
+	Err(anyhow::anyhow!("invalid output: {}", diagnostics))
+}
+
+/// Split the emitted code into the top-level import statements and the rest, the code is parsed
+/// to find the imports. The import statements are joined with the new lines, the comments and the
+/// shebang line are kept in the rest.
+fn split_imports(
+	specifier: &str,
+	code: &str,
+	newline: Newline,
+) -> Result<(String, String), anyhow::Error> {
+	let syntax = Syntax::Es(get_es_config(false));
+	// the parser doesn't support the `with` import attributes, the positions are kept
+	let mut stripped = code.to_owned();
+	strip_with_clauses(&mut stripped, &lex(code, syntax, &[WITH]));
+	let input = StringInput::new(&stripped, BytePos(0), BytePos(stripped.len() as u32));
+	let lexer = Lexer::new(syntax, JscTarget::Es2020, input, None);
+	let mut parser = swc_ecmascript::parser::Parser::new_from(lexer);
+	let module = parser
+		.parse_module()
+		.map_err(|err| anyhow::anyhow!("could not split the imports of {}: {:?}", specifier, err))?;
+	let mut imports = String::new();
+	let mut body = String::new();
+	let mut pos = 0;
+	for item in &module.body {
+		if let ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl { span, .. })) = item {
+			let (lo, mut hi) = (span.lo.0 as usize, span.hi.0 as usize);
+			if code[hi..].starts_with(newline.as_str()) {
+				hi += newline.as_str().len();
+			}
+			body.push_str(&code[pos..lo]);
+			imports.push_str(code[lo..hi].trim_end());
+			imports.push_str(newline.as_str());
+			pos = hi;
+		}
+	}
+	body.push_str(&code[pos..]);
+	Ok((imports, body))
+}
+
+/// A keyword of the syntax rewritten before the parsing and the check of the first char after
+/// it, like the `{` of `with { type: "css" }`.
+struct RewriteKeyword {
+	word: &'static str,
+	next: fn(char) -> bool,
+}
+
+// match: import defer * as ns from "x"
+const DEFER: RewriteKeyword = RewriteKeyword {
+	word: "defer",
+	next: |c| c == '*',
+};
+
+// match: accessor x = 1, accessor #x = 1, accessor "x" = 1
+const ACCESSOR: RewriteKeyword = RewriteKeyword {
+	word: "accessor",
+	next: |c| is_ident_start(c) || matches!(c, '#' | '"' | '\'' | '0'..='9'),
+};
+
+// match: using x = f(), await using x = f()
+const USING: RewriteKeyword = RewriteKeyword {
+	word: "using",
+	next: is_ident_start,
+};
+
+// match: import a from "./a.css" with { type: "css" }
+const WITH: RewriteKeyword = RewriteKeyword {
+	word: "with",
+	next: |c| c == '{',
+};
+
+fn is_ident_start(c: char) -> bool {
+	c.is_alphabetic() || c == '_' || c == '$'
+}
+
+impl RewriteKeyword {
+	/// check if the source may contain the keyword, the keyword must be a whole word followed by
+	/// an accepted char. It's a cheap check before lexing, the comments and the strings are not
+	/// skipped.
+	fn find_in(&self, source: &str) -> bool {
+		let is_ident_char =
+			|c: Option<char>| c.map_or(false, |c| is_ident_start(c) || c.is_ascii_digit());
+		source.match_indices(self.word).any(|(i, _)| {
+			let after = &source[i + self.word.len()..];
+			!is_ident_char(source[..i].chars().next_back())
+				&& !is_ident_char(after.chars().next())
+				&& after.trim_start().chars().next().map_or(false, self.next)
+		})
+	}
+}
+
+/// Lex the source for the rewrites of the syntax not supported by the parser, the source is lexed
+/// only if it may contain any of the keywords.
+fn lex(source: &str, syntax: Syntax, keywords: &[RewriteKeyword]) -> Vec<TokenAndSpan> {
+	if !keywords.iter().any(|keyword| keyword.find_in(source)) {
+		return vec![];
+	}
+	let input = StringInput::new(source, BytePos(0), BytePos(source.len() as u32));
+	Lexer::new(syntax, JscTarget::Es2020, input, None).collect()
+}
+
+/// The parser doesn't support the deferred import evaluation proposal(`import defer * as ns from "x"`) yet,
+/// this function blanks the `defer` keyword(keeps the positions of the source map) and returns the offsets
+/// of the deferred imports. The import is evaluated eagerly in the output.
+fn strip_import_defer(source: &mut String, tokens: &[TokenAndSpan]) -> Vec<u32> {
+	let mut offsets = vec![];
+	for window in tokens.windows(3) {
+		if let [import, defer, star] = window {
+			let is_import_defer = matches!(import.token, Token::Word(Word::Keyword(Keyword::Import)))
+				&& matches!(&defer.token, Token::Word(Word::Ident(w)) if w == "defer")
+				&& matches!(star.token, Token::BinOp(BinOpToken::Mul));
+			if is_import_defer {
+				let (lo, hi) = (defer.span.lo.0 as usize, defer.span.hi.0 as usize);
+				source.replace_range(lo..hi, " ".repeat(hi - lo).as_str());
+				offsets.push(import.span.lo.0);
+			}
+		}
+	}
+	offsets
+}
+
+/// The parser doesn't support the `accessor` keyword of class fields yet, remove the keyword
+/// from `accessor x = 1` and return the offsets of the field keys. Only the keywords of the
+/// members of the class bodies are removed.
+fn strip_accessor_keyword(source: &mut String, tokens: &[TokenAndSpan]) -> Vec<u32> {
+	let mut offsets = vec![];
+	let in_class_body = find_class_body_tokens(tokens);
+	for (i, window) in tokens.windows(2).enumerate() {
+		if let [accessor, key] = window {
+			// an identifier followed by a field key on the same line is only valid for the
+			// auto-accessors in a class body, the `#` is the start of a private key
+			let is_accessor = in_class_body[i]
+				&& matches!(&accessor.token, Token::Word(Word::Ident(w)) if w == "accessor")
+				&& !key.had_line_break
+				&& matches!(
+					key.token,
+					Token::Word(Word::Ident(_)) | Token::Str { .. } | Token::Num(_) | Token::Hash
+				);
+			if is_accessor {
+				let (lo, hi) = (accessor.span.lo.0 as usize, accessor.span.hi.0 as usize);
+				source.replace_range(lo..hi, " ".repeat(hi - lo).as_str());
+				offsets.push(key.span.lo.0);
+			}
+		}
+	}
+	offsets
+}
+
+/// Check if the tokens are at the top level of a class body, like the `x` of
+/// `class A { x = 1 }` but not the `y` of `class A { m() { y } }`.
+fn find_class_body_tokens(tokens: &[TokenAndSpan]) -> Vec<bool> {
+	// the open brackets, `true` for the braces of the class bodies
+	let mut stack: Vec<bool> = vec![];
+	// the depth of the class whose body is the next brace
+	let mut class_depth: Option<usize> = None;
+	let mut in_class_body = Vec::with_capacity(tokens.len());
+	for (i, token) in tokens.iter().enumerate() {
+		in_class_body.push(stack.last() == Some(&true));
+		match token.token {
+			// match: class A {, class extends B {, class {
+			Token::Word(Word::Keyword(Keyword::Class)) => {
+				let next = tokens.get(i + 1).map(|next| &next.token);
+				if matches!(
+					next,
+					Some(Token::Word(Word::Ident(_)))
+						| Some(Token::Word(Word::Keyword(Keyword::Extends)))
+						| Some(Token::LBrace)
+				) {
+					class_depth = Some(stack.len());
+				}
+			}
+			Token::LBrace => {
+				let is_class_body = class_depth == Some(stack.len());
+				if is_class_body {
+					class_depth = None;
+				}
+				stack.push(is_class_body);
+			}
+			Token::DollarLBrace | Token::LParen | Token::LBracket => stack.push(false),
+			Token::RBrace | Token::RParen | Token::RBracket => {
+				stack.pop();
+			}
+			_ => {}
+		}
+	}
+	in_class_body
+}
+
+/// The parser doesn't support the `using` declarations of the explicit resource management yet,
+/// replace the `using`(or `await using`) keyword with `const` and return the offsets of the
+/// declarations, and if they are `await using`. Only the keywords starting the statements are
+/// replaced, the declarations are lowered by the `using_decl_fold`.
+fn replace_using_keyword(source: &mut String, tokens: &[TokenAndSpan]) -> Vec<(u32, bool)> {
+	let mut offsets = vec![];
+	// match: using x = y
+	let is_using = |i: usize| {
+		i + 2 < tokens.len()
+			&& matches!(&tokens[i].token, Token::Word(Word::Ident(w)) if w == "using")
+			&& !tokens[i + 1].had_line_break
+			&& matches!(tokens[i + 1].token, Token::Word(Word::Ident(_)))
+			&& matches!(tokens[i + 2].token, Token::AssignOp(AssignOp::Assign))
+	};
+	// the statements start after `;`, `{`, `}` or a line break
+	let is_stmt_start = |i: usize| {
+		i == 0
+			|| tokens[i].had_line_break
+			|| matches!(tokens[i - 1].token, Token::Semi | Token::LBrace | Token::RBrace)
+	};
+	let mut i = 0;
+	while i + 2 < tokens.len() {
+		// match: await using x = y
+		let is_await = matches!(tokens[i].token, Token::Word(Word::Keyword(Keyword::Await)))
+			&& !tokens[i + 1].had_line_break
+			&& is_using(i + 1);
+		if is_stmt_start(i) && (is_await || is_using(i)) {
+			let lo = tokens[i].span.lo.0 as usize;
+			let hi = tokens[if is_await { i + 1 } else { i }].span.hi.0 as usize;
+			source.replace_range(lo..hi, format!("{:<1$}", "const", hi - lo).as_str());
+			offsets.push((lo as u32, is_await));
+			i += if is_await { 3 } else { 2 };
 		} else {
-			Ok((src, None))
+			i += 1;
 		}
 	}
+	offsets
 }
 
 fn get_es_config(jsx: bool) -> EsConfig {
@@ -250,6 +1430,8 @@ fn get_es_config(jsx: bool) -> EsConfig {
 		optional_chaining: true,
 		top_level_await: true,
 		import_meta: true,
+		private_in_object: true,
+		static_blocks: true,
 		import_assertions: true,
 		jsx,
 		..EsConfig::default()
@@ -260,6 +1442,7 @@ fn get_ts_config(tsx: bool) -> TsConfig {
 	TsConfig {
 		decorators: true,
 		dynamic_import: true,
+		import_assertions: true,
 		tsx,
 		..TsConfig::default()
 	}
@@ -275,135 +1458,64 @@ fn get_syntax(source_type: &SourceType) -> Syntax {
 	}
 }
 
+/// remove the unused deps by tree-shaking, the kept deps are in the original order since
+/// side-effecting imports must not be reordered. the duplicate deps of the same kind are merged
+/// into the first one.
+/// the `path` and `types` references are recorded, the `lib` references are built in.
+fn tree_shake_deps(
+	all_deps: &[DependencyDescriptor],
+	star_exports: &[String],
+	references: &[Reference],
+	is_used: impl Fn(&str) -> bool,
+) -> Vec<DependencyDescriptor> {
+	let mut deps: Vec<DependencyDescriptor> = Vec::new();
+	for dep in all_deps.iter() {
+		if star_exports.contains(&dep.specifier) || is_used(dep.specifier.as_str()) {
+			match deps.iter_mut().find(|d| d.specifier == dep.specifier && d.kind == dep.kind) {
+				Some(first) => first.merge(dep),
+				None => deps.push(dep.clone()),
+			}
+		}
+	}
+	for reference in references {
+		if reference.kind != ReferenceKind::Lib {
+			deps.push(DependencyDescriptor {
+				specifier: reference.value.clone(),
+				is_dynamic: false,
+				is_deferred: false,
+				is_side_effect: false,
+				cjs_interop: None,
+				import_type: None,
+				kind: DependencyKind::TypeReference,
+			});
+		}
+	}
+	deps
+}
+
+/// parse the inlined generator runtime, the spans are dropped since the code isn't in the source
+/// map of the module.
+fn parse_inlined_runtime(code: &str) -> Result<Vec<Stmt>, anyhow::Error> {
+	let runtime = SWC::parse("/regenerator-runtime.js", code, None)?;
+	runtime
+		.module
+		.body
+		.iter()
+		.map(|item| match item {
+			ModuleItem::Stmt(stmt) => Ok(drop_span(stmt.clone())),
+			ModuleItem::ModuleDecl(_) => {
+				Err(anyhow::anyhow!("the inlined generator runtime must be a CommonJS module"))
+			}
+		})
+		.collect()
+}
+
 fn to_str_lit(sub_text: &str) -> String {
 	let mut s = "\"".to_owned();
 	s.push_str(sub_text);
 	s.push('"');
 	s
 }
+
 #[cfg(test)]
-mod tests {
-	use super::*;
-	use crate::import_map::ImportHashMap;
-
-	fn st(specifer: &str, source: &str, bundle_mode: bool) -> (String, Rc<RefCell<Resolver>>) {
-		let module = SWC::parse(specifer, source, None).expect("could not parse module");
-		let resolver = Rc::new(RefCell::new(Resolver::new(
-			specifer,
-			ImportHashMap::default(),
-			bundle_mode,
-			vec![],
-			None,
-		)));
-		let (code, _) = module
-			.transform(resolver.clone(), &EmitOptions::default())
-			.unwrap();
-		println!("{}", code);
-		(code, resolver)
-	}
-
-	#[test]
-	fn typescript() {
-		let source = r#"
-      enum D {
-        A,
-        B,
-        C,
-      }
-
-      function enumerable(value: boolean) {
-        return function (
-          _target: any,
-          _propertyKey: string,
-          descriptor: PropertyDescriptor,
-        ) {
-          descriptor.enumerable = value;
-        };
-      }
-
-      export class A {
-        private b: string;
-        protected c: number = 1;
-        e: "foo";
-        constructor (public d = D.A) {
-          const e = "foo" as const;
-          this.e = e;
-        }
-        @enumerable(false)
-        bar() {}
-      }
-    "#;
-		let (code, _) = st("https://deno.land/x/mod.ts", source, false);
-		assert!(code.contains("var D;\n(function(D) {\n"));
-		assert!(code.contains("_applyDecoratedDescriptor("));
-	}
-
-	#[test]
-	fn react_jsx() {
-		let source = r#"
-      import React from "https://esm.sh/react"
-      export default function App() {
-        return (
-          <>
-            <h1 className="title">Hello World</h1>
-          </>
-        )
-      }
-    "#;
-		let (code, _) = st("app.tsx", source, false);
-		assert!(code.contains("React.createElement(React.Fragment, null"));
-		assert!(code.contains("React.createElement(\"h1\", {"));
-		assert!(code.contains("className: \"title\""));
-	}
-
-	#[test]
-	fn parse_export_names() {
-		let source = r#"
-      export const name = "alephjs"
-      export const version = "1.0.1"
-      const start = () => {}
-      export default start
-      export const { build } = { build: () => {} }
-      export function dev() {}
-      export class Server {}
-      export const { a: { a1, a2 }, 'b': [ b1, b2 ], c, ...rest } = { a: { a1: 0, a2: 0 }, b: [ 0, 0 ], c: 0, d: 0 }
-      export const [ d, e, ...{f, g, rest3} ] = [0, 0, {f:0,g:0,h:0}]
-      let i
-      export const j = i = [0, 0]
-      export { exists, existsSync } from "https://deno.land/std/fs/exists.ts"
-      export * as DenoStdServer from "https://deno.land/std/http/sever.ts"
-      export * from "https://deno.land/std/http/sever.ts"
-    "#;
-		let module = SWC::parse("/app.ts", source, None).expect("could not parse module");
-		assert_eq!(
-			module.parse_export_names().unwrap(),
-			vec![
-				"name",
-				"version",
-				"default",
-				"build",
-				"dev",
-				"Server",
-				"a1",
-				"a2",
-				"b1",
-				"b2",
-				"c",
-				"rest",
-				"d",
-				"e",
-				"f",
-				"g",
-				"rest3",
-				"j",
-				"exists",
-				"existsSync",
-				"DenoStdServer",
-				"{https://deno.land/std/http/sever.ts}",
-			]
-			.into_iter()
-			.map(|s| s.to_owned())
-			.collect::<Vec<String>>()
-		)
-	}
-}
+mod tests;
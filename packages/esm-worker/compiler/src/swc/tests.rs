@@ -0,0 +1,3989 @@
+use super::*;
+use crate::auto_accessor::DecoratorsVersion;
+use crate::comment_filter::CommentFilter;
+use crate::dynamic_imports::DynamicSpecifier;
+use crate::facade::build_facade;
+use crate::forbidden::ForbiddenFeature;
+use crate::import_attributes::ImportAttributes;
+use crate::import_map::ImportHashMap;
+use crate::inject::InjectImport;
+use crate::require_in_esm::RequireMode;
+use crate::resolver::{
+	replace_extension, CjsInterop, ExportKind, ExportShape, ImportKind, ResolveMode,
+};
+use crate::shared_helpers::build_shared_helpers;
+use crate::text_writer::Indent;
+use crate::types_stub::generate_types_stub;
+use std::collections::HashMap;
+
+fn new_resolver(specifier: &str, bundle_mode: bool) -> Rc<RefCell<Resolver>> {
+	Rc::new(RefCell::new(Resolver::new(
+		specifier,
+		ImportHashMap::default(),
+		bundle_mode,
+		vec![],
+		None,
+	)))
+}
+
+fn st(specifer: &str, source: &str, bundle_mode: bool) -> (String, Rc<RefCell<Resolver>>) {
+	let module = SWC::parse(specifer, source, None).expect("could not parse module");
+	let resolver = new_resolver(specifer, bundle_mode);
+	let (code, _) = module
+		.transform(resolver.clone(), &EmitOptions::default())
+		.unwrap();
+	println!("{}", code);
+	(code, resolver)
+}
+
+/// transform the module like `st` with the options, the errors of the transform are returned.
+fn st_with(
+	specifer: &str,
+	source: &str,
+	options: &EmitOptions,
+) -> Result<(String, Rc<RefCell<Resolver>>), anyhow::Error> {
+	let module = SWC::parse(specifer, source, None).expect("could not parse module");
+	let resolver = new_resolver(specifer, false);
+	let (code, _) = module.transform(resolver.clone(), options)?;
+	println!("{}", code);
+	Ok((code, resolver))
+}
+
+/// transform the module like `transform` for the `target` of the options, the syntax newer
+/// than the target is lowered.
+fn transform_target(
+	module: &SWC,
+	resolver: Rc<RefCell<Resolver>>,
+	options: &EmitOptions,
+) -> String {
+	let (_, output) = module
+		.transform_targets(resolver, options, vec![options.target])
+		.unwrap()
+		.remove(0);
+	output.code
+}
+
+/// transform the module with `transform_target`.
+fn st_target(specifer: &str, source: &str, options: &EmitOptions) -> String {
+	let module = SWC::parse(specifer, source, None).expect("could not parse module");
+	transform_target(&module, new_resolver(specifer, false), options)
+}
+
+#[test]
+fn typescript() {
+	let source = r#"
+      enum D {
+        A,
+        B,
+        C,
+      }
+
+      function enumerable(value: boolean) {
+        return function (
+          _target: any,
+          _propertyKey: string,
+          descriptor: PropertyDescriptor,
+        ) {
+          descriptor.enumerable = value;
+        };
+      }
+
+      export class A {
+        private b: string;
+        protected c: number = 1;
+        e: "foo";
+        constructor (public d = D.A) {
+          const e = "foo" as const;
+          this.e = e;
+        }
+        @enumerable(false)
+        bar() {}
+      }
+    "#;
+	let (code, _) = st("https://deno.land/x/mod.ts", source, false);
+	assert!(code.contains("var D;\n(function(D) {\n"));
+	assert!(code.contains("_applyDecoratedDescriptor("));
+}
+
+#[test]
+fn react_jsx() {
+	let source = r#"
+      import React from "https://esm.sh/react"
+      export default function App() {
+        return (
+          <>
+            <h1 className="title">Hello World</h1>
+          </>
+        )
+      }
+    "#;
+	let (code, _) = st("app.tsx", source, false);
+	assert!(code.contains("React.createElement(React.Fragment, null"));
+	assert!(code.contains("React.createElement(\"h1\", {"));
+	assert!(code.contains("className: \"title\""));
+}
+
+#[test]
+fn jsx_member_and_namespaced_names() {
+	let source = r#"
+      import React from "https://esm.sh/react"
+      import * as UI from "./ui.tsx"
+      const Foo = { Bar: (props) => null }
+      export const a = <Foo.Bar prop={1} />
+      export const b = <UI.Card.Header title="x" />
+      export const c = <svg:rect xlink:href="/icons.svg" width={1} />
+    "#;
+	let (code, _) = st("/app.tsx", source, false);
+	assert!(code.contains("React.createElement(Foo.Bar, {\n    prop: 1\n})"));
+	assert!(code.contains("React.createElement(UI.Card.Header, {\n"));
+	assert!(code.contains("React.createElement(\"svg:rect\", {\n"));
+	assert!(code.contains("\"xlink:href\": \"/icons.svg\""));
+}
+
+#[test]
+fn jsx_fragment_pragma() {
+	let source = r#"
+      /* @jsxFrag Fragment */
+      import React, { Fragment } from "https://esm.sh/react"
+      export default () => <><h1>Hello World</h1></>
+    "#;
+	let (code, _) = st("app.tsx", source, false);
+	assert!(code.contains("React.createElement(Fragment, null"));
+
+	let source = r#"
+      /* @jsxFragmentFactory Frag */
+      /* @jsxFragment Fragment */
+      import React, { Fragment } from "https://esm.sh/react"
+      const Frag = Fragment
+      export default () => <><h1>Hello World</h1></>
+    "#;
+	let module = SWC::parse("/app.tsx", source, None).expect("could not parse module");
+	assert_eq!(module.directives().jsx_fragment_factory.as_deref(), Some("Frag"));
+	let (code, _) = st("app.tsx", source, false);
+	assert!(code.contains("React.createElement(Frag, null"));
+}
+
+#[test]
+fn cjs_default_namespace_interop() {
+	let source = r#"
+      import pkg, * as ns from "https://esm.sh/lodash"
+      console.log(pkg.VERSION, ns.VERSION)
+    "#;
+	let module = SWC::parse("/app.js", source, None).expect("could not parse module");
+	let mut resolver = Resolver::new("/app.js", ImportHashMap::default(), false, vec![], None);
+	resolver.cjs_modules.insert("https://esm.sh/lodash".into());
+	let resolver = Rc::new(RefCell::new(resolver));
+	let (code, _) = module
+		.transform(resolver.clone(), &EmitOptions::default())
+		.unwrap();
+	assert!(code.contains("import * as ns from \"https://esm.sh/lodash\""));
+	assert!(code.contains("const pkg = ns.default !== void 0 ? ns.default : ns;"));
+	let r = resolver.borrow();
+	assert_eq!(r.deps.len(), 1);
+	assert_eq!(r.deps[0].cjs_interop, Some(CjsInterop::DefaultOrNamespace));
+}
+
+#[test]
+fn cjs_default_interop() {
+	let source = r#"
+      import pkg from "https://esm.sh/lodash"
+      import React, { useState } from "https://esm.sh/react"
+      import dayjs from "https://esm.sh/dayjs"
+      console.log(pkg.VERSION, React.version, useState, dayjs())
+    "#;
+	let module = SWC::parse("/app.js", source, None).expect("could not parse module");
+	let mut resolver = Resolver::new("/app.js", ImportHashMap::default(), false, vec![], None);
+	resolver.cjs_modules.insert("https://esm.sh/lodash".into());
+	resolver.cjs_modules.insert("https://esm.sh/react".into());
+	let resolver = Rc::new(RefCell::new(resolver));
+	let (code, _) = module
+		.transform(resolver.clone(), &EmitOptions::default())
+		.unwrap();
+	assert!(code.contains("import * as _pkg from \"https://esm.sh/lodash\";\n"));
+	assert!(code.contains("const pkg = _pkg.default !== void 0 ? _pkg.default : _pkg;\n"));
+	assert!(code.contains("import { useState } from \"https://esm.sh/react\";\n"));
+	assert!(code.contains("import * as _React from \"https://esm.sh/react\";\n"));
+	assert!(code.contains("const React = _React.default !== void 0 ? _React.default : _React;\n"));
+	assert!(code.contains("import dayjs from \"https://esm.sh/dayjs\";\n"));
+	let r = resolver.borrow();
+	let interops: Vec<Option<CjsInterop>> =
+		r.deps.iter().map(|dep| dep.cjs_interop.clone()).collect();
+	assert_eq!(
+		interops,
+		vec![
+			Some(CjsInterop::DefaultOrNamespace),
+			Some(CjsInterop::DefaultOrNamespace),
+			None
+		]
+	);
+}
+
+#[test]
+fn verify_output() {
+	let source = r#"
+      export const sum = (a: number, b: number): number => a + b
+    "#;
+	let options = EmitOptions {
+		verify_output: true,
+		..Default::default()
+	};
+	let (code, _) = st_with("/sum.ts", source, &options).unwrap();
+	assert!(code.contains("export const sum = (a, b)=>a + b"));
+	assert!(verify_js("/sum.js", code.as_str()).is_ok());
+	let err = verify_js("/broken.js", "export const sum = (a, b)=>;").unwrap_err();
+	assert!(err.to_string().contains("/broken.js:1:"));
+}
+
+#[test]
+fn indent() {
+	let source = r#"
+      export function foo() {
+        return 1
+      }
+    "#;
+	let emit = |indent: Indent| {
+		let options = EmitOptions {
+			indent,
+			..Default::default()
+		};
+		let (code, _) = st_with("/foo.js", source, &options).unwrap();
+		code
+	};
+	assert!(emit(Indent::default()).contains("\n    return 1;\n"));
+	assert!(emit(Indent::Spaces(2)).contains("\n  return 1;\n"));
+	assert!(emit(Indent::Tabs).contains("\n\treturn 1;\n"));
+}
+
+#[test]
+fn line_width() {
+	let source = r#"
+      export const color = pick("red", "green", "blue", "cyan", "magenta", "yellow", "black")
+    "#;
+	let emit = |line_width: Option<usize>| {
+		let options = EmitOptions {
+			indent: Indent::Spaces(2),
+			line_width,
+			..Default::default()
+		};
+		let (code, _) = st_with("/color.js", source, &options).unwrap();
+		code
+	};
+	let code = emit(Some(40));
+	assert_eq!(
+		code,
+		concat!(
+			"export const color = pick(\"red\", \"green\",\n",
+			"  \"blue\", \"cyan\", \"magenta\", \"yellow\", \"black\");\n"
+		)
+	);
+	assert!(verify_js("/color.js", code.as_str()).is_ok());
+	assert!(!emit(None).contains(",\n"));
+}
+
+#[test]
+fn import_defer() {
+	let source = r#"
+      import defer * as ns from "./x.ts"
+      import * as eager from "./y.ts"
+      export const defer = () => ns.value + eager.value
+    "#;
+	let (code, resolver) = st("/app.ts", source, false);
+	assert!(code.contains("import * as ns from \"/x.ts\""));
+	assert!(code.contains("import * as eager from \"/y.ts\""));
+	let r = resolver.borrow();
+	assert_eq!(r.deps.len(), 2);
+	assert_eq!(r.deps[0].specifier, "/x.ts");
+	assert!(r.deps[0].is_deferred);
+	assert!(!r.deps[1].is_deferred);
+}
+
+#[test]
+fn import_attributes() {
+	let source = r#"
+      import sheet from "./s.css" with { type: "css" }
+      import data from "./data.json" assert { type: "json" }
+      export const note = 'from "/theme.css";'
+      export { default as theme } from "./theme.css" with { type: "css" }
+      export const lazy = () => import("./lazy.css", { with: { type: "css" } })
+      document.adoptedStyleSheets = [sheet]
+      console.log(data)
+    "#;
+	let emit = |import_attributes: ImportAttributes| {
+		let options = EmitOptions {
+			import_attributes,
+			verify_output: true,
+			..Default::default()
+		};
+		let (code, resolver) = st_with("/app.ts", source, &options).unwrap();
+		let import_types: Vec<(String, Option<String>)> = resolver
+			.borrow()
+			.deps
+			.iter()
+			.map(|dep| (dep.specifier.clone(), dep.import_type.clone()))
+			.collect();
+		(code, import_types)
+	};
+	let (code, import_types) = emit(ImportAttributes::Strip);
+	assert!(code.contains("import sheet from \"/s.css\";\n"));
+	assert!(code.contains("import(\"/lazy.css\")"));
+	assert_eq!(
+		import_types,
+		vec![
+			("/s.css".into(), Some("css".into())),
+			("/data.json".into(), Some("json".into())),
+			("/theme.css".into(), Some("css".into())),
+			("/lazy.css".into(), Some("css".into())),
+		]
+	);
+	let (code, _) = emit(ImportAttributes::Preserve);
+	assert!(code.contains("export const note = 'from \"/theme.css\";';\n"));
+	assert!(code.contains("import(\"/lazy.css\", {\n        with: {\n"));
+	assert!(code.contains("import sheet from \"/s.css\" with { type: \"css\" };\n"));
+	assert!(code.contains("import data from \"/data.json\" assert { type: \"json\" };\n"));
+	assert!(code.contains("from \"/theme.css\" with { type: \"css\" };"));
+	let (code, _) = emit(ImportAttributes::With);
+	assert!(code.contains("import data from \"/data.json\" with { type: \"json\" };\n"));
+	let (code, _) = emit(ImportAttributes::Assert);
+	assert!(code.contains("import sheet from \"/s.css\" assert { type: \"css\" };\n"));
+	assert!(code.contains("import(\"/lazy.css\", {\n        assert: {\n"));
+}
+
+#[test]
+fn strip_hook() {
+	use swc_common::DUMMY_SP;
+	use swc_ecma_ast::*;
+	use swc_ecma_utils::quote_ident;
+	use swc_ecma_visit::{noop_fold_type, Fold, FoldWith};
+
+	// match: function f(x: number) {} => function f(x) { assertNumber(x) }
+	struct AssertNumberHook {}
+
+	impl Fold for AssertNumberHook {
+		noop_fold_type!();
+
+		fn fold_function(&mut self, function: Function) -> Function {
+			let mut function = function.fold_children_with(self);
+			let checks: Vec<Stmt> = function
+				.params
+				.iter()
+				.filter_map(|param| match &param.pat {
+					Pat::Ident(BindingIdent {
+						id,
+						type_ann: Some(TsTypeAnn { type_ann, .. }),
+					}) => match type_ann.as_ref() {
+						TsType::TsKeywordType(TsKeywordType {
+							kind: TsKeywordTypeKind::TsNumberKeyword,
+							..
+						}) => Some(Stmt::Expr(ExprStmt {
+							span: DUMMY_SP,
+							expr: Box::new(Expr::Call(CallExpr {
+								span: DUMMY_SP,
+								callee: ExprOrSuper::Expr(Box::new(Expr::Ident(quote_ident!(
+									"assertNumber"
+								)))),
+								args: vec![ExprOrSpread {
+									spread: None,
+									expr: Box::new(Expr::Ident(id.clone())),
+								}],
+								type_args: None,
+							})),
+						})),
+						_ => None,
+					},
+					_ => None,
+				})
+				.collect();
+			if let Some(body) = function.body.as_mut() {
+				body.stmts.splice(0..0, checks);
+			}
+			function
+		}
+	}
+
+	let source = r#"
+      export function add(a: number, b: number, label: string) {
+        return a + b
+      }
+    "#;
+	let options = EmitOptions {
+		strip_hook: Some(StripHook::new(AssertNumberHook {})),
+		..Default::default()
+	};
+	let (code, _) = st_with("/mod.ts", source, &options).unwrap();
+	assert!(code.contains("export function add(a, b, label) {\n"));
+	assert!(code.contains("    assertNumber(a);\n    assertNumber(b);\n    return a + b;\n"));
+}
+
+#[test]
+fn rewrite_fn() {
+	let source = r#"
+      import React from "https://esm.sh/react"
+      import { foo } from "./foo.ts"
+      export { bar } from "./bar.ts"
+      export * from "./baz.ts"
+      export const lazy = () => import("./lazy.ts")
+      console.log(React, foo)
+    "#;
+	let module = SWC::parse("/app.ts", source, None).expect("could not parse module");
+	let mut resolver = Resolver::new("/app.ts", ImportHashMap::default(), false, vec![], None);
+	resolver.rewrite_fn = Some(Box::new(|specifier, kind| {
+		if specifier.starts_with("./") || kind == ImportKind::Dynamic {
+			Some(specifier.to_uppercase())
+		} else {
+			None
+		}
+	}));
+	let resolver = Rc::new(RefCell::new(resolver));
+	let (code, _) = module
+		.transform(resolver.clone(), &EmitOptions::default())
+		.unwrap();
+	assert!(code.contains("import React from \"https://esm.sh/react\""));
+	assert!(code.contains("import { foo } from \"./FOO.TS\""));
+	assert!(code.contains("export { bar } from \"./BAR.TS\""));
+	assert!(code.contains("export * from \"[./BAZ.TS]:./BAZ.TS\""));
+	assert!(code.contains("import(\"./LAZY.TS\")"));
+	let r = resolver.borrow();
+	assert_eq!(
+		r.deps
+			.iter()
+			.map(|dep| dep.specifier.as_str())
+			.collect::<Vec<&str>>(),
+		vec![
+			"https://esm.sh/react",
+			"./FOO.TS",
+			"./BAR.TS",
+			"./BAZ.TS",
+			"./LAZY.TS"
+		]
+	);
+}
+
+#[test]
+fn build_import_map() {
+	let source = r#"
+      import React from "react"
+      import { render } from "react-dom/client"
+      import { foo } from "./foo.ts"
+      export const lazy = () => import("react")
+      render(React, foo)
+    "#;
+	let module = SWC::parse("/app.ts", source, None).expect("could not parse module");
+	let mut resolver = Resolver::new("/app.ts", ImportHashMap::default(), false, vec![], None);
+	resolver.rewrite_fn = Some(Box::new(|specifier, _| match specifier {
+		"react" => Some("/-/react.3f2a9c.js".into()),
+		"react-dom/client" => Some("/-/react-dom_client.9b0e1d.js".into()),
+		_ => None,
+	}));
+	let resolver = Rc::new(RefCell::new(resolver));
+	let (code, _) = module
+		.transform(resolver.clone(), &EmitOptions::default())
+		.unwrap();
+	assert!(code.contains("import React from \"/-/react.3f2a9c.js\""));
+	let import_map = resolver.borrow().build_import_map();
+	assert_eq!(
+		serde_json::to_string(&import_map).unwrap(),
+		concat!(
+			r#"{"imports":{"#,
+			r#""react":"/-/react.3f2a9c.js","#,
+			r#""react-dom/client":"/-/react-dom_client.9b0e1d.js""#,
+			r#"}}"#
+		)
+	);
+	let outputs = module
+		.transform_targets(resolver.clone(), &EmitOptions::default(), vec![JscTarget::Es2022])
+		.unwrap();
+	assert!(outputs[0].1.import_map.is_none());
+	let options = EmitOptions {
+		generate_import_map: true,
+		..Default::default()
+	};
+	let outputs = module.transform_targets(resolver, &options, vec![JscTarget::Es2022]).unwrap();
+	assert_eq!(outputs[0].1.import_map.as_ref().unwrap().imports.len(), 2);
+}
+
+#[test]
+fn unused_imports() {
+	let source = r#"
+      import React from "https://esm.sh/react"
+      import { useState, useEffect } from "https://esm.sh/react"
+      import Logo from "./logo.tsx"
+      import type { FC } from "https://esm.sh/react"
+      export const App: FC = () => {
+        const [count] = useState(0)
+        return <Logo count={count} />
+      }
+    "#;
+	let (_, resolver) = st("/app.tsx", source, false);
+	// the warnings of the previous transform are cleared
+	let module = SWC::parse("/app.tsx", source, None).unwrap();
+	module.transform(resolver.clone(), &EmitOptions::default()).unwrap();
+	let r = resolver.borrow();
+	assert_eq!(r.warnings.len(), 1);
+	assert_eq!(r.warnings[0].message, "'useEffect' is imported but never used");
+	assert_eq!(r.warnings[0].line, 3);
+	assert_eq!(r.warnings[0].col, 25);
+
+	let source = r#"
+      import Logo from "./logo.tsx"
+      export default () => <Logo />
+    "#;
+	let (_, resolver) = st("/app.jsx", source, false);
+	assert!(resolver.borrow().warnings.is_empty());
+}
+
+#[test]
+fn sloppy_mode_errors() {
+	let err = SWC::parse("/mod.js", "const o = {}\nwith (o) {}", None).err().unwrap();
+	assert_eq!(
+		err.to_string(),
+		"with statements are not allowed in module code at /mod.js:2:0"
+	);
+	let err = SWC::parse("/mod.ts", "export const n = 010", None).err().unwrap();
+	assert_eq!(
+		err.to_string(),
+		"legacy octal literals are not allowed in module code at /mod.ts:1:17"
+	);
+}
+
+#[test]
+fn default_class_export() {
+	let source = r#"
+      export default class Foo { static create() { return new Foo() } }
+      export const foo = Foo.create()
+    "#;
+	let module = SWC::parse("/mod.ts", source, None).expect("could not parse module");
+	let resolver = new_resolver("/mod.ts", false);
+	let (code, _) = module.transform(resolver, &EmitOptions::default()).unwrap();
+	assert!(code.contains("class Foo {\n    static create() {\n        return new Foo();\n"));
+	assert!(code.contains("export { Foo as default };\n"));
+	assert!(code.contains("export const foo = Foo.create();\n"));
+
+	for output_format in [OutputFormat::Cjs, OutputFormat::Iife] {
+		let options = EmitOptions {
+			output_format,
+			verify_output: true,
+			..Default::default()
+		};
+		let (code, _) = st_with("/mod.ts", source, &options).unwrap();
+		assert!(code.contains("class Foo {\n"));
+		assert!(code.contains("exports.default = Foo;\n"));
+		assert!(code.contains("const foo = Foo.create();\n"));
+		assert!(code.contains("exports.foo = foo;\n"));
+	}
+
+	// the default function is hoisted with the local binding
+	let source = "export default function render() { return render }";
+	let options = EmitOptions {
+		output_format: OutputFormat::Cjs,
+		..Default::default()
+	};
+	let (code, _) = st_with("/mod.ts", source, &options).unwrap();
+	assert!(code.contains("exports.default = render;\nfunction render() {\n"));
+	assert!(code.contains("    return render;\n"));
+}
+
+#[test]
+fn custom_register_output() {
+	let source = r#"
+      import { h } from "./h.ts"
+      import * as util from "./util.ts"
+      export const el = h("div", util.props(import.meta.url))
+    "#;
+	let options = EmitOptions {
+		output_format: OutputFormat::CustomRegister {
+			register_fn: "runtime.register".into(),
+			module_id: "app/mod".into(),
+		},
+		import_meta_url: Some("https://esm.sh/app/mod.js".into()),
+		verify_output: true,
+		..Default::default()
+	};
+	let (code, _) = st_with("/mod.ts", source, &options).unwrap();
+	assert!(code.contains("runtime.register(\"app/mod\", [\n    \"/h.ts\",\n"));
+	assert!(code.contains("    \"/util.ts\"\n], function"));
+	assert!(code.contains("], function(require, exports) {\n    \"use strict\";\n"));
+	assert!(code.contains("    var _hTs = require(\"/h.ts\");\n"));
+	assert!(code.contains("util.props(\"https://esm.sh/app/mod.js\")"));
+	assert!(code.trim_end().ends_with("    exports.el = el;\n});"));
+	// the helpers are defined in the factory
+	assert!(code.starts_with("runtime.register(\"app/mod\", ["));
+	assert!(code.contains("    \"use strict\";\n    function _interopRequireWildcard(obj) {\n"));
+
+	for register_fn in ["", "runtime.", "a..b"] {
+		let options = serde_json::json!({
+			"customRegister": { "registerFn": register_fn, "moduleId": "app/mod" }
+		});
+		let err = serde_json::from_value::<OutputFormat>(options).unwrap_err();
+		assert!(err.to_string().starts_with("invalid registerFn"));
+	}
+}
+
+#[test]
+fn import_meta_shim() {
+	let source = r#"
+      const url = new URL("./data.json", import.meta.url)
+      const meta = import.meta
+      export default url
+    "#;
+	let emit = |output_format: OutputFormat, import_meta_url: Option<String>| {
+		let options = EmitOptions {
+			output_format,
+			import_meta_url,
+			verify_output: true,
+			..Default::default()
+		};
+		let (code, _) = st_with("/mod.js", source, &options).unwrap();
+		code
+	};
+	let code = emit(OutputFormat::Cjs, None);
+	assert!(!code.contains("import.meta"));
+	assert!(code.contains(
+		"const url = new URL(\"./data.json\", require(\"url\").pathToFileURL(__filename).href);"
+	));
+	assert!(code.contains(
+		"const meta = {\n    url: require(\"url\").pathToFileURL(__filename).href\n};"
+	));
+	let code = emit(OutputFormat::Iife, None);
+	assert!(code.contains("const url = new URL(\"./data.json\", location.href);"));
+	let code = emit(OutputFormat::Iife, Some("https://esm.sh/mod.js".into()));
+	assert!(code.contains("const url = new URL(\"./data.json\", \"https://esm.sh/mod.js\");"));
+	let code = emit(OutputFormat::Esm, None);
+	assert!(code.contains("const url = new URL(\"./data.json\", import.meta.url);"));
+}
+
+#[test]
+fn live_bindings() {
+	let source = r#"
+      export let x = 1
+      let y = 1
+      export { y as count }
+      export const z = 1
+      export function inc() {
+        x++
+        y += 1
+      }
+    "#;
+	let emit = |live_bindings: bool| {
+		let options = EmitOptions {
+			output_format: OutputFormat::Cjs,
+			live_bindings,
+			verify_output: true,
+			..Default::default()
+		};
+		let (code, _) = st_with("/mod.js", source, &options).unwrap();
+		code
+	};
+	let code = emit(true);
+	assert!(code.contains(
+		"Object.defineProperty(exports, \"x\", {\n    enumerable: true,\n    get: ()=>x\n});"
+	));
+	assert!(code.contains(
+		"Object.defineProperty(exports, \"count\", {\n    enumerable: true,\n    get: ()=>y\n});"
+	));
+	assert!(code.contains("exports.z = z;"));
+	assert!(code.contains("exports.inc = inc;"));
+	assert!(code.contains("    x++;\n    y += 1;\n"));
+	assert!(!code.contains("exports.x ="));
+	assert!(!code.contains("exports.count ="));
+	assert_eq!(code.matches("\"__esModule\"").count(), 1);
+	let code = emit(false);
+	assert!(code.contains("exports.x = x;"));
+	assert!(!code.contains("get: ()=>x"));
+}
+
+#[test]
+fn top_level_await_wrapper() {
+	let source = r#"
+      import { load } from "./data.js"
+      export const data = await load()
+      export function get() { return data }
+    "#;
+	let emit = |source: &str, output_format: OutputFormat| {
+		let options = EmitOptions {
+			output_format,
+			global_name: Some("Data".into()),
+			verify_output: true,
+			..Default::default()
+		};
+		let (code, _) = st_with("/mod.js", source, &options).unwrap();
+		code
+	};
+	let module = SWC::parse("/mod.js", source, None).expect("could not parse module");
+	assert!(module.has_top_level_await());
+
+	// the global is the promise of the exports
+	let code = emit(source, OutputFormat::Iife);
+	assert!(code.starts_with("var Data = async function() {\n"));
+	assert!(code.contains("    const data = await (0, _dataJs).load();\n"));
+	assert!(code.contains("    exports.data = data;\n"));
+	assert!(code.ends_with("    return exports;\n}();\n"));
+
+	let code = emit(source, OutputFormat::Cjs);
+	assert!(code.starts_with("\"use strict\";\nmodule.exports = (async function() {\n"));
+	assert!(code.ends_with("    return exports;\n})();\n"));
+
+	// the awaits in the functions are not top-level
+	let source = "export async function get() { return await load() }";
+	let module = SWC::parse("/mod.js", source, None).expect("could not parse module");
+	assert!(!module.has_top_level_await());
+	assert!(emit(source, OutputFormat::Iife).starts_with("var Data = function() {\n"));
+
+	// the async wrapper isn't lowered for the older targets
+	let source = "export const data = await load()";
+	let module = SWC::parse("/mod.js", source, None).expect("could not parse module");
+	let resolver = Resolver::new("/mod.js", ImportHashMap::default(), false, vec![], None);
+	let options = EmitOptions {
+		output_format: OutputFormat::Iife,
+		target: JscTarget::Es2016,
+		..Default::default()
+	};
+	let err = module.transform(Rc::new(RefCell::new(resolver)), &options).unwrap_err();
+	assert_eq!(
+		err.to_string(),
+		concat!(
+			"top-level await can't be wrapped in an async function for the es2016 target",
+			" at /mod.js:1:20"
+		)
+	);
+}
+
+#[test]
+fn freeze_exports() {
+	let source = r#"
+      export let count = 0
+      export const name = "counter"
+      export function inc() {
+        count++
+      }
+    "#;
+	let options = EmitOptions {
+		output_format: OutputFormat::Iife,
+		global_name: Some("Counter".into()),
+		freeze_exports: true,
+		verify_output: true,
+		..Default::default()
+	};
+	let (code, _) = st_with("/mod.js", source, &options).unwrap();
+	assert!(code.contains(
+		"Object.defineProperty(exports, \"count\", {\n        enumerable: true,\n        get: ()=>count\n    });"
+	));
+	assert!(code.contains("exports.name = name;"));
+	assert!(!code.contains("exports.count ="));
+	assert!(code.contains("    Object.freeze(exports);\n    return exports;\n}();"));
+}
+
+#[test]
+fn pure_functions() {
+	let source = r#"
+      Object.freeze({})
+      Object.freeze({ a: Symbol("a") })
+      Object.freeze(console.log("a"))
+      console.log("b")
+      const unused = 1
+      const kept = Object.freeze({})
+      export const frozen = Object.freeze([1, 2])
+    "#;
+	let emit = |pure_functions: Vec<String>| {
+		let options = EmitOptions {
+			dce: true,
+			pure_functions,
+			..Default::default()
+		};
+		let (code, _) = st_with("/mod.js", source, &options).unwrap();
+		code
+	};
+	let code = emit(vec!["Object.freeze".into(), "Symbol".into()]);
+	assert_eq!(
+		code,
+		"Object.freeze(console.log(\"a\"));\nconsole.log(\"b\");\nexport const frozen = Object.freeze([\n    1,\n    2\n]);\n"
+	);
+	let code = emit(vec![]);
+	assert!(code.starts_with("Object.freeze({\n});\n"));
+	assert!(!code.contains("unused"));
+}
+
+#[test]
+fn tree_shake_locals() {
+	let source = r#"
+      import { format } from "./format.ts"
+      function unusedHelper(s: string) { return format(s) }
+      function used() { return inner() }
+      function inner() { return 1 }
+      const config = { debug: false }
+      const registered = register()
+      class Plugin { static instance = createPlugin() }
+      class Unused {}
+      let counter = 0
+      counter++
+      export function run() { return used() + counter }
+      export default config
+    "#;
+	let emit = |tree_shake_locals: bool| {
+		let options = EmitOptions {
+			tree_shake_locals,
+			..Default::default()
+		};
+		let (code, resolver) = st_with("/mod.ts", source, &options).unwrap();
+		let deps: Vec<String> =
+			resolver.borrow().deps.iter().map(|dep| dep.specifier.clone()).collect();
+		(code, deps)
+	};
+	let (code, deps) = emit(true);
+	assert!(!code.contains("function unusedHelper("));
+	assert!(!code.contains("class Unused"));
+	assert!(code.contains("function used() {"));
+	assert!(code.contains("function inner() {"));
+	assert!(code.contains("const config = {"));
+	// the declarations with side effects are kept
+	assert!(code.contains("const registered = register();"));
+	assert!(code.contains("class Plugin {"));
+	assert!(code.contains("let counter = 0;"));
+	// the imports are kept for the side effects of the modules
+	assert_eq!(deps, vec!["/format.ts"]);
+
+	let (code, _) = emit(false);
+	assert!(code.contains("function unusedHelper(s) {"));
+	assert!(code.contains("class Unused {"));
+}
+
+#[test]
+fn esmodule_interop() {
+	let emit = |esmodule_interop: bool| {
+		let module =
+			SWC::parse("/mod.js", "export default 1", None).expect("could not parse module");
+		let resolver = Rc::new(RefCell::new(Resolver::new(
+			"/mod.js",
+			ImportHashMap::default(),
+			false,
+			vec![],
+			None,
+		)));
+		let options = EmitOptions {
+			output_format: OutputFormat::Cjs,
+			esmodule_interop,
+			..Default::default()
+		};
+		let (code, _) = module.transform(resolver, &options).unwrap();
+		code
+	};
+	let code = emit(true);
+	assert!(code.contains(
+		"Object.defineProperty(exports, \"__esModule\", {\n    value: true\n});"
+	));
+	assert!(code.contains("var _default = 1;\nexports.default = _default;"));
+	let code = emit(false);
+	assert!(!code.contains("__esModule"));
+	assert!(code.contains("var _default = 1;\nexports.default = _default;"));
+}
+
+#[test]
+fn use_strict() {
+	let source = r#"
+      import { v } from "./v.ts"
+      export const ver = v
+    "#;
+	let emit = |output_format: OutputFormat, emit_use_strict: Option<bool>| {
+		let options = EmitOptions {
+			output_format,
+			global_name: Some("mod".into()),
+			emit_use_strict,
+			..Default::default()
+		};
+		let (code, _) = st_with("/mod.ts", source, &options).unwrap();
+		code
+	};
+	let code = emit(OutputFormat::Esm, None);
+	assert!(!code.contains("\"use strict\";"));
+	assert!(code.contains("export const ver = v;"));
+	let code = emit(OutputFormat::Iife, Some(true));
+	assert!(code.starts_with("var mod = function() {\n    \"use strict\";\n    var exports = {"));
+	assert!(code.contains("require(\"/v.ts\")"));
+	assert!(code.contains("return exports;\n}();"));
+	let code = emit(OutputFormat::Iife, Some(false));
+	assert!(!code.contains("\"use strict\";"));
+	let code = emit(OutputFormat::Cjs, None);
+	assert!(code.starts_with("\"use strict\";\n"));
+	assert_eq!(code.matches("\"use strict\";").count(), 1);
+}
+
+#[test]
+fn iife_helpers() {
+	let source = r#"
+      import * as util from "./util.ts"
+      export const props = util.props
+    "#;
+	let options = EmitOptions {
+		output_format: OutputFormat::Iife,
+		global_name: Some("mod".into()),
+		verify_output: true,
+		..Default::default()
+	};
+	let (code, _) = st_with("/mod.ts", source, &options).unwrap();
+	// the helpers are defined in the function
+	assert!(code.starts_with("var mod = function() {\n    \"use strict\";\n"));
+	assert!(code.contains("\n    function _interopRequireWildcard(obj) {\n"));
+	assert!(code.contains("var util = _interopRequireWildcard(require(\"/util.ts\"));"));
+}
+
+#[test]
+fn raw_source_map() {
+	let source = r#"const a: number = 1
+const b = a + 1
+console.log(a, b)
+"#;
+	let module = SWC::parse("/mod.ts", source, None).expect("could not parse module");
+	let resolver = new_resolver("/mod.ts", false);
+	let options = EmitOptions {
+		source_map: true,
+		..Default::default()
+	};
+	let (code, map) = module.transform_with_raw_map(resolver, &options).unwrap();
+	assert_eq!(code, "const a = 1;\nconst b = a + 1;\nconsole.log(a, b);\n");
+	let map = map.unwrap();
+	assert_eq!(map.get_token_count(), 22);
+	let lines: Vec<u32> = map.tokens().map(|token| token.get_dst_line()).collect();
+	for (line, count) in [(0, 6), (1, 8), (2, 8)] {
+		assert_eq!(lines.iter().filter(|&&l| l == line).count(), count);
+	}
+	for token in map.tokens() {
+		assert_eq!(token.get_src_line(), token.get_dst_line());
+	}
+}
+
+#[test]
+fn input_source_map() {
+	let source = "const a: number = 1;\nconsole.log(a);\n";
+	let module = SWC::parse("/mod.ts", source, None).expect("could not parse module");
+	let resolver = new_resolver("/mod.ts", false);
+	let options = EmitOptions {
+		source_map: true,
+		// line 1 => original.ts:3, line 2 => original.ts:6
+		input_source_map: Some(
+			r#"{"version":3,"sources":["original.ts"],"names":[],"mappings":"AAEA;AAGA"}"#.into(),
+		),
+		..Default::default()
+	};
+	let (code, map) = module.transform_with_raw_map(resolver, &options).unwrap();
+	assert_eq!(code, "const a = 1;\nconsole.log(a);\n");
+	let map = map.unwrap();
+	assert_eq!(map.sources().collect::<Vec<&str>>(), vec!["original.ts"]);
+	let token = map.lookup_token(0, 0).unwrap();
+	assert_eq!((token.get_src_line(), token.get_source()), (2, Some("original.ts")));
+	let token = map.lookup_token(1, 0).unwrap();
+	assert_eq!((token.get_src_line(), token.get_source()), (5, Some("original.ts")));
+}
+
+#[test]
+fn private_in_object() {
+	let source = r#"
+      export class Brand {
+        #brand = 1
+        #check() {}
+        static is(obj: object) {
+          return #brand in obj && #check in obj
+        }
+      }
+    "#;
+	let emit = |target: JscTarget| {
+		let options = EmitOptions {
+			target,
+			..Default::default()
+		};
+		let (code, _) = st_with("/brand.ts", source, &options).unwrap();
+		code
+	};
+	let code = emit(JscTarget::Es2022);
+	assert!(code.contains("return #brand in obj && #check in obj;"));
+	for target in [JscTarget::Es2021, JscTarget::Es2015] {
+		let code = emit(target);
+		assert!(code.contains("return _brand.has(obj) && _check.has(obj);"));
+		assert!(code.contains("var _brand = new WeakMap();"));
+		assert!(code.contains("var _check = new WeakSet();"));
+		assert!(!code.contains("#brand"));
+	}
+}
+
+#[test]
+fn source_map_file() {
+	let module = SWC::parse("/mod.ts", "export const a: number = 1;\n", None)
+		.expect("could not parse module");
+	let resolver = Rc::new(RefCell::new(Resolver::new(
+		"/mod.ts",
+		ImportHashMap::default(),
+		false,
+		vec![],
+		None,
+	)));
+	let options = EmitOptions {
+		source_map: true,
+		source_map_file: Some("mod.abc123.js".into()),
+		..Default::default()
+	};
+	let (_, map) = module.transform(resolver, &options).unwrap();
+	let map: serde_json::Value = serde_json::from_str(map.unwrap().as_str()).unwrap();
+	assert_eq!(map["file"], "mod.abc123.js");
+}
+
+#[test]
+fn side_effect_imports_order() {
+	let source = r#"
+      import "./a-polyfill.ts"
+      import { Unused } from "./unused.ts"
+      import "./b-polyfill.ts"
+      import type { T } from "./types.ts"
+      import "./c-polyfill.ts"
+      export const t: T | null = null
+    "#;
+	let (code, resolver) = st("/app.ts", source, false);
+	let a = code.find("import \"/a-polyfill.ts\";").unwrap();
+	let b = code.find("import \"/b-polyfill.ts\";").unwrap();
+	let c = code.find("import \"/c-polyfill.ts\";").unwrap();
+	assert!(a < b && b < c);
+	assert!(!code.contains("/unused.ts"));
+	assert!(!code.contains("/types.ts"));
+	let r = resolver.borrow();
+	assert_eq!(
+		r.deps
+			.iter()
+			.map(|dep| dep.specifier.as_str())
+			.collect::<Vec<&str>>(),
+		vec!["/a-polyfill.ts", "/b-polyfill.ts", "/c-polyfill.ts"]
+	);
+}
+
+#[test]
+fn shebang() {
+	let source = "#!/usr/bin/env node\nconst name: string = \"esm\"\nconsole.log(name)\n";
+	for emit_shebang in [false, true] {
+		let module = SWC::parse("/cli.ts", source, None).expect("could not parse module");
+		assert_eq!(module.module.shebang.as_deref(), Some("/usr/bin/env node"));
+		let resolver = Rc::new(RefCell::new(Resolver::new(
+			"/cli.ts",
+			ImportHashMap::default(),
+			false,
+			vec![],
+			None,
+		)));
+		let options = EmitOptions {
+			source_map: true,
+			emit_shebang,
+			..Default::default()
+		};
+		let (code, map) = module.transform(resolver, &options).unwrap();
+		let body = "const name = \"esm\";\nconsole.log(name);\n";
+		if emit_shebang {
+			assert_eq!(code, format!("#!/usr/bin/env node\n{}", body));
+		} else {
+			assert_eq!(code, body);
+		}
+		let map = sourcemap::SourceMap::from_slice(map.unwrap().as_bytes()).unwrap();
+		let line = code.lines().position(|l| l.starts_with("console")).unwrap() as u32;
+		let token = map.lookup_token(line, 0).unwrap();
+		assert_eq!(token.get_dst_line(), line);
+		assert_eq!((token.get_src_line(), token.get_src_col()), (2, 0));
+	}
+}
+
+#[test]
+fn newline_crlf() {
+	let source = r#"#!/usr/bin/env node
+      import { run } from "./run.ts"
+      /* run the cli */
+      export const name = `esm
+sh`
+      run(name)
+    "#;
+	let options = EmitOptions {
+		newline: Newline::CrLf,
+		emit_shebang: true,
+		exports_comment: true,
+		split_imports: true,
+		..Default::default()
+	};
+	let (code, resolver) = st_with("/cli.js", source, &options).unwrap();
+	assert!(code.starts_with("#!/usr/bin/env node\r\n/* esm.sh exports: name */\r\n"));
+	assert!(code.contains("import { run } from \"/run.ts\";\r\n/* run the cli */"));
+	assert!(code.contains("run(name);\r\n"));
+	// the line ending in the template literal is kept
+	assert!(code.contains("export const name = `esm\nsh`;\r\n"));
+	assert_eq!(code.matches('\n').count(), code.matches("\r\n").count() + 1);
+	let r = resolver.borrow();
+	let (imports, _) = r.split_imports.as_ref().unwrap();
+	assert_eq!(imports, "import { run } from \"/run.ts\";\r\n");
+}
+
+#[test]
+fn split_imports() {
+	let source = r#"
+      import React from "https://esm.sh/react"
+      import sheet from "./app.css" with { type: "css" }
+      const title = "Hello"
+      import { render } from "https://esm.sh/react-dom"
+      export default function App() { return render(React, sheet, title) }
+    "#;
+	let options = EmitOptions {
+		split_imports: true,
+		import_attributes: ImportAttributes::With,
+		exports_comment: true,
+		..Default::default()
+	};
+	let (code, resolver) = st_with("/app.tsx", source, &options).unwrap();
+	let r = resolver.borrow();
+	let (imports, body) = r.split_imports.as_ref().unwrap();
+	assert_eq!(
+		imports,
+		concat!(
+			"import React from \"https://esm.sh/react\";\n",
+			"import sheet from \"/app.css\" with { type: \"css\" };\n",
+			"import { render } from \"https://esm.sh/react-dom\";\n",
+		)
+	);
+	assert!(body.starts_with("/* esm.sh exports: default */\nconst title = \"Hello\";\n"));
+	assert!(body.contains("export default function App() {\n"));
+	assert!(!body.contains("import "));
+	assert!(code.contains("import { render } from \"https://esm.sh/react-dom\";\n"));
+}
+
+#[test]
+fn comment_filter() {
+	let source = r#"
+      // KEEP: the license
+      // a line comment
+      export const a = 1 /* KEEP: trailing */
+      /* a block comment */
+      export function b() {}
+    "#;
+	let module = SWC::parse("/mod.js", source, None).expect("could not parse module");
+	let resolver = new_resolver("/mod.js", false);
+	let options = EmitOptions {
+		comment_filter: Some(CommentFilter::new(|text| text.contains("KEEP"))),
+		..Default::default()
+	};
+	let (code, _) = module.transform(resolver.clone(), &options).unwrap();
+	assert!(code.contains("// KEEP: the license"));
+	assert!(code.contains("/* KEEP: trailing */"));
+	assert!(!code.contains("a line comment"));
+	assert!(!code.contains("a block comment"));
+	// the comments of the module are kept for the next transform
+	let (code, _) = module.transform(resolver, &EmitOptions::default()).unwrap();
+	assert!(code.contains("// a line comment"));
+	assert!(code.contains("/* a block comment */"));
+}
+
+#[test]
+fn exports_comment() {
+	let source = r#"#!/usr/bin/env node
+      export const name = "esm.sh", version = "1.0.0"
+      export default function run() {}
+      export * from "./util.js"
+    "#;
+	for emit_shebang in [false, true] {
+		let module = SWC::parse("/cli.js", source, None).expect("could not parse module");
+		let resolver = new_resolver("/cli.js", false);
+		let options = EmitOptions {
+			exports_comment: true,
+			emit_shebang,
+			source_map: true,
+			..Default::default()
+		};
+		let (code, map) = module.transform_with_raw_map(resolver, &options).unwrap();
+		let comment = "/* esm.sh exports: name,version,default */\n";
+		if emit_shebang {
+			assert!(code.starts_with(&format!("#!/usr/bin/env node\n{}", comment)));
+		} else {
+			assert!(code.starts_with(comment));
+		}
+		// the mappings are shifted by the comment line
+		let line = if emit_shebang { 2 } else { 1 };
+		let map = map.unwrap();
+		let token = map.lookup_token(line, 0).unwrap();
+		assert_eq!(token.get_dst_line(), line);
+		assert_eq!(token.get_src_line(), 1);
+	}
+}
+
+#[test]
+fn shebang_args() {
+	let shebang = concat!(
+		"#!/usr/bin/env -S node --experimental-vm-modules ",
+		"--import=\"./using loader.js\" # accessor ",
+	);
+	let source = format!("{}\nconsole.log(1)\n", shebang);
+	let module = SWC::parse("/cli.js", source.as_str(), None).expect("could not parse module");
+	assert_eq!(module.module.shebang.as_deref(), Some(&shebang[2..]));
+	let resolver = Rc::new(RefCell::new(Resolver::new(
+		"/cli.js",
+		ImportHashMap::default(),
+		false,
+		vec![],
+		None,
+	)));
+	let options = EmitOptions {
+		emit_shebang: true,
+		..Default::default()
+	};
+	let (code, _) = module.transform(resolver, &options).unwrap();
+	assert_eq!(code, format!("{}\nconsole.log(1);\n", shebang));
+}
+
+#[test]
+fn inject_imports() {
+	let source = r#"
+      import { render } from "./render.ts"
+      const el: unknown = h("div", null)
+      render(el)
+    "#;
+	let options = EmitOptions {
+		dce: true,
+		inject: vec![
+			InjectImport {
+				specifier: "./polyfill.ts".into(),
+				default: None,
+				names: vec![],
+			},
+			InjectImport {
+				specifier: "https://esm.sh/preact".into(),
+				default: None,
+				names: vec!["h".into(), "Fragment".into()],
+			},
+		],
+		..Default::default()
+	};
+	let (code, resolver) = st_with("/app.ts", source, &options).unwrap();
+	assert!(code.starts_with(concat!(
+		"import \"/polyfill.ts\";\n",
+		"import { h } from \"https://esm.sh/preact\";\n",
+		"import { render } from \"/render.ts\";\n",
+	)));
+	let deps: Vec<(String, bool)> = resolver
+		.borrow()
+		.deps
+		.iter()
+		.map(|dep| (dep.specifier.clone(), dep.is_side_effect))
+		.collect();
+	assert_eq!(
+		deps,
+		vec![
+			("/polyfill.ts".into(), true),
+			("https://esm.sh/preact".into(), false),
+			("/render.ts".into(), false),
+		]
+	);
+}
+
+#[test]
+fn provide_globals() {
+	let emit = |source: &str| {
+		let mut provide = HashMap::new();
+		provide.insert("Buffer".into(), ("https://esm.sh/buffer".into(), "Buffer".into()));
+		provide.insert("process".into(), ("https://esm.sh/process".into(), "default".into()));
+		let options = EmitOptions {
+			provide,
+			..Default::default()
+		};
+		let (code, _) = st_with("/app.js", source, &options).unwrap();
+		code
+	};
+	let code = emit("const buf = Buffer.from(\"esm\")\nconsole.log({ Buffer, env: obj.process })");
+	assert!(code.starts_with(concat!(
+		"import { Buffer } from \"https://esm.sh/buffer\";\n",
+		"const buf = Buffer.from(\"esm\");\n",
+	)));
+	assert!(!code.contains("https://esm.sh/process"));
+	let code = emit("console.log(process.env)");
+	assert!(code.starts_with("import process from \"https://esm.sh/process\";\n"));
+	let code = emit("const Buffer = globalThis.Buffer\nconsole.log(Buffer)");
+	assert!(!code.contains("import"));
+	let code = emit("console.log(\"Buffer\", obj.Buffer)");
+	assert!(!code.contains("import"));
+	// the local bindings shadow the globals in their scopes only
+	let code = emit("function concat(Buffer) { return Buffer.concat([]) }");
+	assert!(!code.contains("import"));
+	let code = emit("function concat(Buffer) { return Buffer.concat([]) }\nBuffer.alloc(1)");
+	assert!(code.starts_with("import { Buffer } from \"https://esm.sh/buffer\";\n"));
+}
+
+#[test]
+fn polyfill_methods() {
+	let emit = |source: &str| {
+		let mut polyfill_methods = HashMap::new();
+		let core_js = "https://esm.sh/core-js/modules";
+		polyfill_methods.insert("at".into(), format!("{}/es.array.at", core_js));
+		polyfill_methods.insert("findLast".into(), format!("{}/es.array.find-last", core_js));
+		let options = EmitOptions {
+			polyfill_methods,
+			..Default::default()
+		};
+		let (code, _) = st_with("/app.js", source, &options).unwrap();
+		code
+	};
+	let code = emit("const last = arr.at(-1)\nconst first = list[\"at\"](0)");
+	assert!(code.starts_with(concat!(
+		"import \"https://esm.sh/core-js/modules/es.array.at\";\n",
+		"const last = arr.at(-1);\n",
+	)));
+	assert_eq!(code.matches("es.array.at").count(), 1);
+	assert!(!code.contains("find-last"));
+	let code = emit("const at = obj.at\nconsole.log(\"at\")");
+	assert!(!code.contains("import"));
+}
+
+#[test]
+fn import_type_elision() {
+	let source = r#"
+      import type { A } from "./a.ts"
+      import type B from "./b.ts"
+      import { type C, c } from "./c.ts"
+      import { type D } from "./d.ts"
+      export const v: A | B | C | D = c
+    "#;
+	let (code, resolver) = st("/mod.ts", source, false);
+	assert_eq!(code, "import { c } from \"/c.ts\";\nexport const v = c;\n");
+	let r = resolver.borrow();
+	assert_eq!(
+		r.deps
+			.iter()
+			.map(|dep| dep.specifier.as_str())
+			.collect::<Vec<&str>>(),
+		vec!["/c.ts"]
+	);
+	assert!(r.warnings.is_empty());
+}
+
+#[test]
+fn dedupe_deps() {
+	let source = r#"
+      import "./a.ts"
+      import { b } from "./b.ts"
+      import { a } from "./a.ts"
+      export { b as bb } from "./b.ts"
+      export const lazyB = () => import("./b.ts")
+      export const lazyC = () => import("./c.ts")
+      export const lazyC2 = () => import("./c.ts")
+      console.log(a, b)
+    "#;
+	let (_, resolver) = st("/mod.ts", source, false);
+	let r = resolver.borrow();
+	assert_eq!(
+		r.deps
+			.iter()
+			.map(|dep| (dep.specifier.as_str(), dep.is_dynamic, dep.is_side_effect))
+			.collect::<Vec<(&str, bool, bool)>>(),
+		vec![
+			("/a.ts", false, true),
+			("/b.ts", false, false),
+			("/c.ts", true, false)
+		]
+	);
+}
+
+#[test]
+fn strip_declare() {
+	for (source, expected) in [
+		("declare const x: number", ""),
+		("declare let y: string, z: boolean\ndeclare var w: unknown", ""),
+		("declare function f(a: string): void", ""),
+		("declare class C { m(): void }", ""),
+		("declare enum E { A }\ndeclare const enum F { B }", ""),
+		("namespace N { declare enum E { A } }", ""),
+		("export declare enum E { A }", ""),
+		("declare namespace NS { const v: number }", ""),
+		("declare global { interface Window { foo: string } }\nexport {}", "export { };\n"),
+		("declare module \"x\" { export const v: number }", ""),
+		("declare module \"y\" { interface Foo {} }", ""),
+	] {
+		let (code, _) = st("/mod.ts", source, false);
+		assert_eq!(code, expected);
+	}
+	let source = r#"
+      import { init } from "./init.ts"
+      declare global {
+        interface Window { init: typeof init }
+        var DEBUG: boolean
+      }
+      declare module "./init.ts" {
+        function init(debug: boolean): void
+      }
+      init()
+    "#;
+	let (code, _) = st("/mod.ts", source, false);
+	assert_eq!(code, "import { init } from \"/init.ts\";\ninit();\n");
+}
+
+#[test]
+fn name_default_export() {
+	let emit = |specifier: &str, source: &str| {
+		let options = EmitOptions {
+			name_default_export: true,
+			..Default::default()
+		};
+		let (code, _) = st_with(specifier, source, &options).unwrap();
+		code
+	};
+	let code = emit("/components/App.tsx", "export default () => <div />");
+	assert!(code.contains("const App_default = ()=>/*#__PURE__*/ React.createElement(\"div\", null)\n;"));
+	assert!(code.contains("export default App_default;"));
+	let code = emit("/lib/use-state.js?dev", "export default function() {}");
+	assert!(code.starts_with("export default function use_state_default() {"));
+	let code = emit("/404.js", "export default class {}");
+	assert!(code.starts_with("class _404_default {"));
+	assert!(code.contains("export { _404_default as default };"));
+	let code = emit("/foo.js", "const foo_default = 1\nexport default async () => foo_default");
+	assert!(code.contains("const foo_default1 = async ()=>foo_default"));
+	assert!(code.contains("export default foo_default1;"));
+	let code = emit("/foo.js", "export default function named() {}");
+	assert!(code.starts_with("export default function named() {"));
+}
+
+#[test]
+fn target_syntax() {
+	let emit = |source: &str, target: JscTarget| {
+		let options = EmitOptions {
+			target,
+			..Default::default()
+		};
+		st_with("/mod.js", source, &options).map(|(code, _)| code)
+	};
+	let code = emit("const x = 1_000_000\nconst y = 0b1010_1010", JscTarget::Es2015).unwrap();
+	assert_eq!(code, "const x = 1000000;\nconst y = 170;\n");
+	let code = emit("const x = 123n", JscTarget::Es2020).unwrap();
+	assert_eq!(code, "const x = 123n;\n");
+	let err = emit("const x = 1\nconst y = 123n", JscTarget::Es2019).unwrap_err();
+	assert_eq!(
+		err.to_string(),
+		"BigInt literals are not supported by the es2019 target at /mod.js:2:10"
+	);
+}
+
+#[test]
+fn optional_catch_binding() {
+	let emit = |source: &str, target: JscTarget| {
+		let options = EmitOptions {
+			target,
+			..Default::default()
+		};
+		st_target("/mod.js", source, &options)
+	};
+	let code = emit("try { f() } catch {}", JscTarget::Es2018);
+	assert!(code.contains("} catch (_e) {\n"));
+	let code = emit("try { f() } catch { const _e = 1; g(_e) }", JscTarget::Es2018);
+	assert!(code.contains("} catch (_e1) {\n"));
+	let code = emit("try { f() } catch {}", JscTarget::Es2019);
+	assert!(code.contains("} catch  {\n"));
+}
+
+#[test]
+fn class_static_blocks() {
+	let source = r#"
+      class Registry {
+        static items = []
+        static {
+          this.items.push("first")
+        }
+        static count = Registry.items.length
+        static { init(this) }
+      }
+      export default class { static { ready() } }
+    "#;
+	let options = EmitOptions {
+		target: JscTarget::Es2015,
+		verify_output: true,
+		..Default::default()
+	};
+	let code = st_target("/mod.js", source, &options);
+	assert!(!code.contains("static"));
+	// the blocks run between the static properties in the declaration order
+	let items = code.find("_defineProperty(Registry, \"items\", []);").unwrap();
+	let first = code.find("Registry.items.push(\"first\");").unwrap();
+	let count = code.find("_defineProperty(Registry, \"count\"").unwrap();
+	let init = code.find("init(Registry);").unwrap();
+	assert!(items < first && first < count && count < init);
+	assert!(code.contains("value: (()=>{\n        ready();\n    })()"));
+}
+
+#[test]
+fn logical_assignments() {
+	let source = r#"
+      let a = 0
+      a ||= b
+      get().x ??= y
+      obj[key()] &&= z
+      const init = (o) => o.cache ||= new Map()
+      const load = (o) => get(o).data ??= {}
+      function withDefault(o = get().x ||= 1) { return o }
+      const props = {
+        get x() { return get().y ||= 1 },
+        set x(v) { get().y ||= v },
+      }
+    "#;
+	let options = EmitOptions {
+		target: JscTarget::Es2015,
+		..Default::default()
+	};
+	let code = st_target("/mod.js", source, &options);
+	assert!(code.starts_with("var _ref4, _key, _ref1;\n"));
+	assert!(code.contains("a || (a = b);\n"));
+	assert!(code.contains("(_x = (_ref4 = get()).x) !== null && _x !== void 0 ? _x : _ref4.x"));
+	assert!(code.contains("obj[_key = key()] && (obj[_key] = z);\n"));
+	assert!(code.contains("(o)=>o.cache || (o.cache = new Map())"));
+	// the temporary variables of the arrow function are declared in its body
+	assert!(code.contains("(o)=>{\n    var _ref;\n"));
+	assert!(code.contains("(_data = (_ref = get(o)).data) !== null"));
+	// the temporary variables of the default values are declared in the enclosing scope
+	assert!(code.contains("function withDefault(o = (_ref1 = get()).x || (_ref1.x = 1)) {"));
+	// the getters and the setters declare their own temporary variables
+	assert!(code.contains("get x () {\n        var _ref2;\n"));
+	assert!(code.contains("set x (v){\n        var _ref3;\n"));
+}
+
+#[test]
+fn generator_runtime() {
+	let emit = |target: JscTarget, generator_runtime: GeneratorRuntime| {
+		let source = r#"
+        import "./polyfills.js"
+        export async function load() { return await fetch("/data.json") }
+      "#;
+		let module = SWC::parse("/mod.js", source, None).expect("could not parse module");
+		let resolver = new_resolver("/mod.js", false);
+		let options = EmitOptions {
+			target,
+			generator_runtime,
+			..Default::default()
+		};
+		let code = transform_target(&module, resolver.clone(), &options);
+		let deps: Vec<String> =
+			resolver.borrow().deps.iter().map(|dep| dep.specifier.clone()).collect();
+		(code, deps)
+	};
+	let runtime = GeneratorRuntime::Import("https://esm.sh/regenerator-runtime".into());
+	let (code, deps) = emit(JscTarget::Es2016, runtime.clone());
+	assert!(code.contains(
+		"import regeneratorRuntime from \"https://esm.sh/regenerator-runtime\";\n"
+	));
+	assert!(code.contains("regeneratorRuntime.mark("));
+	assert!(!code.contains("function*"));
+	assert_eq!(deps, vec!["/polyfills.js", "https://esm.sh/regenerator-runtime"]);
+
+	let (code, deps) = emit(JscTarget::Es2017, runtime);
+	assert!(code.contains("export async function load()"));
+	assert_eq!(deps, vec!["/polyfills.js"]);
+
+	let (code, _) = emit(JscTarget::Es2016, GeneratorRuntime::Global);
+	assert!(!code.contains("import regeneratorRuntime"));
+	assert!(code.contains("import \"/polyfills.js\";\n"));
+	assert!(code.contains("regeneratorRuntime.mark("));
+
+	let runtime = "module.exports = { mark(fn) { return fn }, wrap() {} };";
+	let (code, deps) = emit(JscTarget::Es2016, GeneratorRuntime::Inline(runtime.into()));
+	assert!(!code.contains("import regeneratorRuntime"));
+	assert!(code.contains(concat!(
+		"const regeneratorRuntime = (()=>{\n",
+		"    var module = {\n        exports: {\n        }\n    }, exports = module.exports;\n",
+		"    module.exports = {"
+	)));
+	assert!(code.contains("return module.exports;\n})();\n"));
+	assert!(code.contains("regeneratorRuntime.mark("));
+	assert_eq!(deps, vec!["/polyfills.js"]);
+	let module = SWC::parse("/mod.js", "async function f() {}", None).unwrap();
+	let options = EmitOptions {
+		target: JscTarget::Es2016,
+		generator_runtime: GeneratorRuntime::Inline("export {}".into()),
+		..Default::default()
+	};
+	let resolver = Resolver::new("/mod.js", ImportHashMap::default(), false, vec![], None);
+	let resolver = Rc::new(RefCell::new(resolver));
+	assert!(module.transform_targets(resolver, &options, vec![JscTarget::Es2016]).is_err());
+
+	let (code, _) = emit(JscTarget::Es2016, GeneratorRuntime::Native);
+	assert!(code.contains("function*"));
+	assert!(!code.contains("regeneratorRuntime"));
+}
+
+#[test]
+fn global_this() {
+	let emit = |source: &str, target: JscTarget| {
+		let options = EmitOptions {
+			target,
+			..Default::default()
+		};
+		st_target("/mod.js", source, &options)
+	};
+	let source = "import \"./polyfill.js\"\nglobalThis.foo = { globalThis, bar: obj.globalThis }";
+	let code = emit(source, JscTarget::Es2019);
+	assert!(code.starts_with(concat!(
+		"import \"/polyfill.js\";\n",
+		"var _globalThis = typeof globalThis !== \"undefined\" ? globalThis : ",
+		"typeof self !== \"undefined\" ? self : typeof window !== \"undefined\" ? window : ",
+		"typeof global !== \"undefined\" ? global : Function(\"return this\")();\n",
+	)));
+	assert!(code.contains("_globalThis.foo = {\n    globalThis: _globalThis,\n"));
+	assert!(code.contains("bar: obj.globalThis\n"));
+	let code = emit(source, JscTarget::Es2020);
+	assert!(code.contains("globalThis.foo = {\n    globalThis,\n"));
+	assert!(!code.contains("_globalThis"));
+	let code = emit("const globalThis = {}\nglobalThis.foo = 1", JscTarget::Es2015);
+	assert!(!code.contains("_globalThis"));
+}
+
+#[test]
+fn dynamic_code_warnings() {
+	let emit = |warn_on_dynamic_code: bool| {
+		let source = concat!(
+			"const x = 1\nconsole.log(eval(\"x\"), new Function(\"return x\"))\n",
+			"sandbox.eval(\"x\"), new vm.Function(\"return x\")",
+		);
+		let module = SWC::parse("/mod.js", source, None).expect("could not parse module");
+		let resolver = new_resolver("/mod.js", false);
+		let options = EmitOptions {
+			warn_on_dynamic_code,
+			..Default::default()
+		};
+		module.transform(resolver.clone(), &options).unwrap();
+		let warnings: Vec<(String, usize, usize)> = resolver
+			.borrow()
+			.warnings
+			.iter()
+			.map(|w| (w.message.clone(), w.line, w.col))
+			.collect();
+		warnings
+	};
+	assert_eq!(
+		emit(true),
+		vec![
+			("Dynamic code evaluation with \"eval\"".into(), 2, 12),
+			("Dynamic code evaluation with \"Function constructor\"".into(), 2, 23),
+		]
+	);
+	assert!(emit(false).is_empty());
+}
+
+#[test]
+fn forbidden_features() {
+	let emit = |source: &str, forbidden: Vec<ForbiddenFeature>| {
+		let options = EmitOptions {
+			forbidden,
+			..Default::default()
+		};
+		st_with("/mod.js", source, &options).map(|(code, _)| code)
+	};
+	let source = "const x = 1\nconsole.log(eval(\"x\"))";
+	assert!(emit(source, vec![]).is_ok());
+	let err = emit(source, vec![ForbiddenFeature::Eval]).unwrap_err();
+	assert_eq!(err.to_string(), "Forbidden feature \"eval\" at /mod.js:2:12");
+	let source = r#"
+const f = new Function("return 1")
+const m = await import("./m.js")
+async function load() {
+  await import("./n.js")
+}
+"#;
+	let err = emit(
+		source,
+		vec![
+			ForbiddenFeature::FunctionConstructor,
+			ForbiddenFeature::DynamicImport,
+			ForbiddenFeature::TopLevelAwait,
+		],
+	)
+	.unwrap_err();
+	assert_eq!(
+		err.to_string(),
+		[
+			"Forbidden feature \"Function constructor\" at /mod.js:2:10",
+			"Forbidden feature \"top-level await\" at /mod.js:3:10",
+			"Forbidden feature \"dynamic import\" at /mod.js:3:16",
+			"Forbidden feature \"dynamic import\" at /mod.js:5:8",
+		]
+		.join("\n")
+	);
+	assert!(emit(source, vec![ForbiddenFeature::Eval]).is_ok());
+	let source = "obj.eval(\"x\")\nnew x.Function(\"return 1\")\nx.Function()\nobj[eval]";
+	let err = emit(source, vec![ForbiddenFeature::Eval, ForbiddenFeature::FunctionConstructor])
+		.unwrap_err();
+	assert_eq!(err.to_string(), "Forbidden feature \"eval\" at /mod.js:4:4");
+}
+
+#[test]
+fn private_fields_lowering() {
+	let source = "class C { #x = 1; static #y = 2; has(o) { return #x in o } }";
+	let options = EmitOptions {
+		target: JscTarget::Es2019,
+		..Default::default()
+	};
+	let (code, _) = st_with("/mod.js", source, &options).unwrap();
+	assert!(code.contains("var _x = new WeakMap();\n"));
+	assert!(code.contains("_x.set(this, {\n"));
+	assert!(code.contains("return _x.has(o);\n"));
+	assert!(!code.contains("#x"));
+	assert!(!code.contains("#y"));
+}
+
+#[test]
+fn gzip_size() {
+	let mut source = String::new();
+	for i in 0..50 {
+		source.push_str(&format!("export const item{} = {{ id: {}, tags: [] }};\n", i, i));
+	}
+	let module = SWC::parse("/items.js", &source, None).expect("could not parse module");
+	let resolver = new_resolver("/items.js", false);
+	let options = EmitOptions {
+		gzip_size: true,
+		..Default::default()
+	};
+	let outputs =
+		module.transform_targets(resolver.clone(), &options, vec![JscTarget::Es2022]).unwrap();
+	let (_, output) = &outputs[0];
+	let size = output.gzip_size.unwrap();
+	assert!(size > 0 && size < output.code.len() / 4);
+	let outputs = module
+		.transform_targets(resolver, &EmitOptions::default(), vec![JscTarget::Es2022])
+		.unwrap();
+	assert_eq!(outputs[0].1.gzip_size, None);
+}
+
+#[test]
+fn transform_targets() {
+	let source = r#"
+      import { fetchData } from "./api.ts"
+      export async function load(url) {
+        const data = await fetchData(url)
+        return data?.items ?? []
+      }
+    "#;
+	let module = SWC::parse("/mod.js", source, None).expect("could not parse module");
+	let resolver = new_resolver("/mod.js", false);
+	let outputs = module
+		.transform_targets(
+			resolver,
+			&EmitOptions::default(),
+			vec![JscTarget::Es2015, JscTarget::Es2020],
+		)
+		.unwrap();
+	let targets: Vec<JscTarget> = outputs.iter().map(|(target, _)| *target).collect();
+	assert_eq!(targets, vec![JscTarget::Es2015, JscTarget::Es2020]);
+	let (_, es2015) = &outputs[0];
+	assert!(es2015.code.contains("function _asyncToGenerator(fn) {"));
+	assert!(es2015.code.contains("const data = yield fetchData(url);"));
+	assert!(!es2015.code.contains("async function"));
+	assert!(!es2015.code.contains("?."));
+	let (_, es2020) = &outputs[1];
+	assert!(es2020.code.contains("export async function load(url) {"));
+	assert!(es2020.code.contains("const data = await fetchData(url);"));
+	assert!(es2020.code.contains("return data?.items ?? [];"));
+	assert!(!es2020.code.contains("_asyncToGenerator"));
+	for (_, output) in &outputs {
+		assert_eq!(output.deps.len(), 1);
+		assert_eq!(output.deps[0].specifier, "/api.ts");
+	}
+
+	// each target records its own helpers, the resolver records the helpers of all targets
+	let resolver = Rc::new(RefCell::new(Resolver::new(
+		"/mod.js",
+		ImportHashMap::default(),
+		false,
+		vec![],
+		None,
+	)));
+	let options = EmitOptions {
+		shared_helpers: true,
+		..Default::default()
+	};
+	let outputs = module
+		.transform_targets(
+			resolver.clone(),
+			&options,
+			vec![JscTarget::Es2020, JscTarget::Es2015],
+		)
+		.unwrap();
+	assert!(outputs[0].1.helpers.is_empty());
+	assert_eq!(outputs[1].1.helpers, vec!["asyncToGenerator".to_owned()]);
+	assert!(resolver.borrow().helpers.contains("asyncToGenerator"));
+	assert_eq!(resolver.borrow().deps.len(), 1);
+}
+
+#[test]
+fn transform_target_lowering() {
+	let source = r#"
+      export async function load(xs, opts) {
+        try { opts.retries ||= 3 } catch {}
+        for await (const x of xs) console.log(x ** 2)
+        return 2 ** 3
+      }
+    "#;
+	let options = EmitOptions {
+		target: JscTarget::Es2015,
+		..Default::default()
+	};
+	let (code, _) = st_with("/mod.js", source, &options).unwrap();
+	assert!(code.contains("} catch (_e) {\n"));
+	assert!(code.contains("opts.retries || (opts.retries = 3)"));
+	assert!(code.contains("Math.pow(2, 3)"));
+	assert!(!code.contains("for await"));
+	assert!(!code.contains("async function"));
+	// `transform` lowers the syntax like the output of the target of `transform_targets`
+	let module = SWC::parse("/mod.js", source, None).unwrap();
+	let outputs = module
+		.transform_targets(new_resolver("/mod.js", false), &options, vec![JscTarget::Es2015])
+		.unwrap();
+	assert_eq!(outputs[0].1.code, code);
+}
+
+#[test]
+fn analyze() {
+	let source = r#"
+      import type { Config } from "./types.ts"
+      import { unused } from "./unused.ts"
+      import { load } from "./data.ts"
+      export * from "./reexports.ts"
+      export const data: Config = await load()
+      export const lazy = () => import("./lazy.ts")
+    "#;
+	let module = SWC::parse("/mod.ts", source, None).expect("could not parse module");
+	let resolver = new_resolver("/mod.ts", false);
+	let report = module.analyze(resolver.clone(), &EmitOptions::default()).unwrap();
+	let deps: Vec<(&str, bool)> = report
+		.deps
+		.iter()
+		.map(|dep| (dep.specifier.as_str(), dep.is_dynamic))
+		.collect();
+	// the unused import is stripped like the transform
+	assert_eq!(
+		deps,
+		vec![("/data.ts", false), ("/reexports.ts", false), ("/lazy.ts", true)]
+	);
+	assert_eq!(report.export_names, vec!["data", "lazy"]);
+	assert!(report.has_top_level_await);
+	assert!(!report.is_side_effect_free);
+	assert_eq!(report.warnings.len(), 1);
+	assert_eq!(report.warnings[0].message, "'unused' is imported but never used");
+	assert_eq!(resolver.borrow().deps, report.deps);
+
+	// the deps are the same as the transform
+	let (_, resolver) = st("/mod.ts", source, false);
+	assert_eq!(resolver.borrow().deps, report.deps);
+}
+
+#[test]
+fn transform_twice() {
+	let source = r#"
+      #!/usr/bin/env node
+      import React from "https://esm.sh/react"
+      export const App = () => <h1 className="title">{name ?? "World"}</h1>
+    "#;
+	let module = SWC::parse("/app.jsx", source.trim(), None).expect("could not parse module");
+	let transform = |module: &SWC, options: &EmitOptions| {
+		let resolver = Rc::new(RefCell::new(Resolver::new(
+			"/app.jsx",
+			ImportHashMap::default(),
+			false,
+			vec![],
+			None,
+		)));
+		transform_target(module, resolver, options)
+	};
+	// the clones share the parsed AST
+	let cached = module.clone();
+	assert!(Rc::ptr_eq(&module.module, &cached.module));
+
+	let es2015 = EmitOptions {
+		target: JscTarget::Es2015,
+		..Default::default()
+	};
+	let code = transform(&cached, &es2015);
+	assert!(code.contains("name !== null && name !== void 0 ? name : \"World\""));
+	assert!(!code.starts_with("#!"));
+	let es2022 = EmitOptions {
+		emit_shebang: true,
+		..Default::default()
+	};
+	let code = transform(&cached, &es2022);
+	assert!(code.starts_with("#!/usr/bin/env node\n"));
+	assert!(code.contains("name ?? \"World\""));
+	// the transforms don't change the parsed module
+	assert_eq!(cached.module.shebang.as_deref(), Some("/usr/bin/env node"));
+	assert_eq!(transform(&cached, &es2015), transform(&module, &es2015));
+	let reparsed = SWC::parse("/app.jsx", source.trim(), None).expect("could not parse module");
+	assert_eq!(transform(&cached, &es2015), transform(&reparsed, &es2015));
+}
+
+#[test]
+fn extract_css() {
+	let source = r#"
+      import { css, styled } from "goober"
+      const s = css`color:red`
+      const Button = styled.button`
+        padding: 4px;
+      `
+      const t = css`color: ${s}`
+    "#;
+	let emit = |replace: bool| {
+		let options = EmitOptions {
+			extract_css: true,
+			replace_extracted_css: replace,
+			..Default::default()
+		};
+		let (code, resolver) = st_with("/mod.js", source, &options).unwrap();
+		let extracted_css = resolver.borrow().extracted_css.clone();
+		(code, extracted_css)
+	};
+	let (code, extracted_css) = emit(false);
+	assert!(code.contains("const s = css`color:red`;"));
+	assert_eq!(extracted_css.len(), 2);
+	assert_eq!(extracted_css[0].tag, "css");
+	assert_eq!(extracted_css[0].css, "color:red");
+	assert_eq!(extracted_css[0].class_name, None);
+	assert_eq!(extracted_css[1].tag, "styled.button");
+	assert_eq!(extracted_css[1].css, "padding: 4px;");
+	let (code, extracted_css) = emit(true);
+	let class_name = extracted_css[0].class_name.clone().unwrap();
+	assert!(class_name.starts_with("css-"));
+	assert!(code.contains(format!("const s = \"{}\";", class_name).as_str()));
+	assert!(code.contains("const Button = styled.button`"));
+	assert!(code.contains("const t = css`color: ${s}`;"));
+	assert_eq!(extracted_css[1].class_name, None);
+}
+
+#[test]
+fn export_shape() {
+	let source = r#"
+      import { h } from "https://esm.sh/preact"
+      import type { Props } from "./types.ts"
+      export interface Theme { dark: boolean }
+      export type Size = number
+      export enum Align { Left, Right }
+      export const [a, b] = [1, 2]
+      export function render(props: Props) { return h("div", props) }
+      class Store {}
+      export { Store, h, type Theme as T }
+      export { default as Button } from "./button.ts"
+      export * as utils from "./utils.ts"
+      export * from "./hooks.ts"
+      export default () => null
+    "#;
+	let module = SWC::parse("/app.tsx", source, None).expect("could not parse module");
+	let resolver = new_resolver("/app.tsx", false);
+	let options = EmitOptions {
+		export_shape: true,
+		name_default_export: true,
+		..Default::default()
+	};
+	module.transform(resolver.clone(), &options).unwrap();
+	let shape = |name: &str, kind: ExportKind| ExportShape {
+		name: name.into(),
+		kind,
+	};
+	assert_eq!(
+		resolver.borrow().export_shape,
+		vec![
+			shape("Align", ExportKind::Variable),
+			shape("a", ExportKind::Variable),
+			shape("b", ExportKind::Variable),
+			shape("render", ExportKind::Function),
+			shape("Store", ExportKind::Class),
+			shape("h", ExportKind::Reexport),
+			shape("Button", ExportKind::Reexport),
+			shape("utils", ExportKind::Namespace),
+			shape("[/hooks.ts]:/hooks.ts", ExportKind::Star),
+			shape("default", ExportKind::Variable),
+		]
+	);
+
+	let module = SWC::parse("/app.tsx", source, None).expect("could not parse module");
+	let resolver = new_resolver("/app.tsx", false);
+	module.transform(resolver.clone(), &EmitOptions::default()).unwrap();
+	assert!(resolver.borrow().export_shape.is_empty());
+}
+
+#[test]
+fn extract_strings() {
+	let source = r#"
+      const title = t("hello")
+      const label = i18n.t(`world`, { count: 1 })
+      const other = t(name, "ignored")
+      console.log(translate("skipped"))
+    "#;
+	let module = SWC::parse("/app.ts", source, None).expect("could not parse module");
+	let resolver = new_resolver("/app.ts", false);
+	let options = EmitOptions {
+		extract_strings: true,
+		..Default::default()
+	};
+	module.transform(resolver.clone(), &options).unwrap();
+	// the strings of the previous transform are cleared
+	module.transform(resolver.clone(), &options).unwrap();
+	assert_eq!(
+		resolver.borrow().extracted_strings,
+		vec![
+			ExtractedString {
+				key: "hello".into(),
+				line: 2,
+				col: 22,
+			},
+			ExtractedString {
+				key: "world".into(),
+				line: 3,
+				col: 27,
+			},
+		]
+	);
+}
+
+#[test]
+fn optimize_reexports() {
+	let source = r#"
+      import { x, y } from "./a.ts"
+      import * as b from "./b.ts"
+      import c, { d as e } from "./c.ts"
+      import { f } from "./f.ts"
+      export { x, b, c as default, e }
+      export { f }
+      console.log(y, f)
+    "#;
+	let options = EmitOptions {
+		optimize_reexports: true,
+		..Default::default()
+	};
+	let (code, _) = st_with("/app.ts", source, &options).unwrap();
+	assert!(code.contains("import { y } from \"/a.ts\";"));
+	assert!(code.contains("export { x } from \"/a.ts\";"));
+	assert!(code.contains("export * as b from \"/b.ts\";"));
+	assert!(code.contains("export { default as default } from \"/c.ts\";"));
+	assert!(code.contains("export { d as e } from \"/c.ts\";"));
+	assert!(!code.contains("import * as b"));
+	assert!(!code.contains("import c"));
+	assert!(code.contains("import { f } from \"/f.ts\";"));
+	assert!(code.contains("export { f };"));
+
+	// the re-exports take the place of the imports
+	let source = r#"
+      import { x } from "./a.ts"
+      import "./b.ts"
+      export { x, x as y }
+    "#;
+	let (code, _) = st_with("/app.ts", source, &options).unwrap();
+	assert_eq!(
+		code,
+		concat!(
+			"export { x } from \"/a.ts\";\n",
+			"export { x as y } from \"/a.ts\";\n",
+			"import \"/b.ts\";\n"
+		)
+	);
+}
+
+#[test]
+fn glob_imports() {
+	let source = r#"
+      const pages = import.meta.glob("./pages/*.ts")
+      const eager = import.meta.glob("./pages/*.ts", { eager: true })
+      const lazy = import.meta.glob("./pages/*.ts")
+    "#;
+	let emit = |with_lister: bool| {
+		let module = SWC::parse("/mod.js", source, None).expect("could not parse module");
+		let mut resolver = Resolver::new("/mod.js", ImportHashMap::default(), false, vec![], None);
+		if with_lister {
+			resolver.file_lister = Some(Box::new(|pattern: &str| {
+				assert_eq!(pattern, "./pages/*.ts");
+				vec!["./pages/a.ts".into(), "./pages/b.ts".into()]
+			}));
+		}
+		let resolver = Rc::new(RefCell::new(resolver));
+		let options = EmitOptions {
+			glob_imports: true,
+			..Default::default()
+		};
+		let (code, _) = module.transform(resolver.clone(), &options).unwrap();
+		let deps: Vec<(String, bool, DependencyKind)> = resolver
+			.borrow()
+			.deps
+			.iter()
+			.map(|dep| (dep.specifier.clone(), dep.is_dynamic, dep.kind))
+			.collect();
+		(code, deps)
+	};
+	let (code, deps) = emit(true);
+	assert!(code.contains("\"./pages/a.ts\": ()=>import(\"/pages/a.ts\")"));
+	assert!(code.contains("\"./pages/b.ts\": ()=>import(\"/pages/b.ts\")"));
+	assert!(code.contains("import * as __glob_0 from \"/pages/a.ts\";"));
+	assert!(code.contains("import * as __glob_1 from \"/pages/b.ts\";"));
+	assert!(code.contains("\"./pages/a.ts\": __glob_0"));
+	assert_eq!(
+		deps,
+		vec![
+			("/pages/a.ts".to_owned(), false, DependencyKind::Import),
+			("/pages/b.ts".to_owned(), false, DependencyKind::Import),
+			("/pages/a.ts".to_owned(), true, DependencyKind::Glob),
+			("/pages/b.ts".to_owned(), true, DependencyKind::Glob),
+		]
+	);
+	let (code, deps) = emit(false);
+	assert!(code.contains("const pages = import.meta.glob(\"./pages/*.ts\");"));
+	assert!(deps.is_empty());
+}
+
+#[test]
+fn jsx_components() {
+	let source = r#"
+      import { Foo } from "./foo.tsx"
+      export default function App() {
+        return <Foo><div /><Foo.Item /><svg:rect /><span><Foo /></span></Foo>
+      }
+    "#;
+	let module = SWC::parse("/app.tsx", source, None).expect("could not parse module");
+	let jsx = module.jsx_components();
+	assert_eq!(jsx.components, vec!["Foo", "Foo.Item"]);
+	assert_eq!(jsx.intrinsics, vec!["div", "svg:rect", "span"]);
+}
+
+#[test]
+fn side_effect_free() {
+	let source = r#"
+      "use strict"
+      import { h } from "./h.ts"
+      export const version = "1.0.0", noop = () => {}
+      export function render() { return h("div") }
+      export class App { static displayName = "App"; render() {} }
+      export { h }
+      export * from "./util.ts"
+      export default { render }
+    "#;
+	let module = SWC::parse("/mod.ts", source, None).expect("could not parse module");
+	assert!(module.is_side_effect_free());
+
+	for source in [
+		"import './polyfill.ts'",
+		"init()",
+		"export const app = createApp()",
+		"window.app = {}",
+		"export class App extends mixin(Base) {}",
+		"export class App { static instance = new App() }",
+	] {
+		let module = SWC::parse("/mod.ts", source, None).expect("could not parse module");
+		assert!(!module.is_side_effect_free(), "{}", source);
+	}
+}
+
+#[test]
+fn dynamic_imports() {
+	let source = r#"const routes = {
+  home: () => import("./pages/home.tsx"),
+  page: (name) => import(`./pages/${name}.tsx`),
+}
+"#;
+	let module = SWC::parse("/mod.ts", source, None).expect("could not parse module");
+	assert_eq!(
+		module.dynamic_imports(),
+		vec![
+			DynamicImportSite {
+				specifier: DynamicSpecifier::Literal("./pages/home.tsx".into()),
+				line: 2,
+				col: 14,
+			},
+			DynamicImportSite {
+				specifier: DynamicSpecifier::Computed("`./pages/${name}.tsx`".into()),
+				line: 3,
+				col: 18,
+			},
+		]
+	);
+}
+
+#[test]
+fn worker_specifiers() {
+	let source = r#"
+      navigator.serviceWorker.register("./sw.ts", { scope: "/" })
+      const worker = new Worker("./worker.ts", { type: "module" })
+    "#;
+	let module =
+		SWC::parse("https://esm.sh/app/main.js", source, None).expect("could not parse module");
+	let resolver = Rc::new(RefCell::new(Resolver::new(
+		"https://esm.sh/app/main.js",
+		ImportHashMap::default(),
+		false,
+		vec![],
+		None,
+	)));
+	let (code, _) = module.transform(resolver.clone(), &EmitOptions::default()).unwrap();
+	assert!(code.contains(
+		"navigator.serviceWorker.register(\"https://esm.sh/app/sw.ts\", {\n    scope: \"/\"\n});"
+	));
+	assert!(code.contains("new Worker(\"https://esm.sh/app/worker.ts\", {"));
+	let deps: Vec<(String, bool, DependencyKind)> = resolver
+		.borrow()
+		.deps
+		.iter()
+		.map(|dep| (dep.specifier.clone(), dep.is_dynamic, dep.kind))
+		.collect();
+	assert_eq!(
+		deps,
+		vec![
+			("https://esm.sh/app/sw.ts".into(), true, DependencyKind::ServiceWorker),
+			("https://esm.sh/app/worker.ts".into(), true, DependencyKind::Worker),
+		]
+	);
+}
+
+#[test]
+fn shared_worker_and_worklet_specifiers() {
+	let source = r#"
+      const worker = new SharedWorker(new URL("./shared.ts", import.meta.url), { type: "module" })
+      await audioWorklet.addModule("./processor.ts")
+      await CSS.paintWorklet.addModule("./paint.ts")
+      store.addModule("user", {})
+    "#;
+	let module =
+		SWC::parse("https://esm.sh/app/main.js", source, None).expect("could not parse module");
+	let resolver = Rc::new(RefCell::new(Resolver::new(
+		"https://esm.sh/app/main.js",
+		ImportHashMap::default(),
+		false,
+		vec![],
+		None,
+	)));
+	let (code, _) = module.transform(resolver.clone(), &EmitOptions::default()).unwrap();
+	assert!(code.contains(
+		"new SharedWorker(new URL(\"https://esm.sh/app/shared.ts\", import.meta.url), {"
+	));
+	assert!(code.contains("await audioWorklet.addModule(\"https://esm.sh/app/processor.ts\");"));
+	assert!(code.contains("await CSS.paintWorklet.addModule(\"https://esm.sh/app/paint.ts\");"));
+	assert!(code.contains("store.addModule(\"user\", {"));
+	let deps: Vec<(String, bool, DependencyKind)> = resolver
+		.borrow()
+		.deps
+		.iter()
+		.map(|dep| (dep.specifier.clone(), dep.is_dynamic, dep.kind))
+		.collect();
+	assert_eq!(
+		deps,
+		vec![
+			("https://esm.sh/app/shared.ts".into(), true, DependencyKind::Worker),
+			("https://esm.sh/app/processor.ts".into(), true, DependencyKind::Worklet),
+			("https://esm.sh/app/paint.ts".into(), true, DependencyKind::Worklet),
+		]
+	);
+}
+
+#[test]
+fn fetch_specifiers() {
+	let source = r#"
+      const data = await fetch(new URL("./data.json", import.meta.url)).then(res => res.json())
+      const base = await fetch(new URL("./base.json", location.href))
+    "#;
+	let module =
+		SWC::parse("https://esm.sh/app/main.js", source, None).expect("could not parse module");
+	let resolver = Rc::new(RefCell::new(Resolver::new(
+		"https://esm.sh/app/main.js",
+		ImportHashMap::default(),
+		false,
+		vec![],
+		None,
+	)));
+	let (code, _) = module.transform(resolver.clone(), &EmitOptions::default()).unwrap();
+	assert!(code.contains("fetch(new URL(\"https://esm.sh/app/data.json\", import.meta.url))"));
+	assert!(code.contains("fetch(new URL(\"./base.json\", location.href))"));
+	let deps: Vec<(String, bool, DependencyKind)> = resolver
+		.borrow()
+		.deps
+		.iter()
+		.map(|dep| (dep.specifier.clone(), dep.is_dynamic, dep.kind))
+		.collect();
+	assert_eq!(
+		deps,
+		vec![("https://esm.sh/app/data.json".into(), true, DependencyKind::Asset)]
+	);
+}
+
+#[test]
+fn code_splitting() {
+	let source = r#"
+      import { a } from "./a.ts"
+      const Heavy = () => import("./heavy.ts")
+      const About = () => import("./pages/about/index.tsx")
+      const Other = () => import("./lib/heavy.ts?v=2")
+      const again = () => import("./heavy.ts")
+      const packed = () => import("./a.ts")
+      console.log(a)
+    "#;
+	let resolver = Rc::new(RefCell::new(Resolver::new(
+		"/app.ts",
+		ImportHashMap::default(),
+		true,
+		vec!["/a.ts".into()],
+		None,
+	)));
+	let transform = |source: &str, code_splitting: bool| {
+		let module = SWC::parse("/app.ts", source, None).expect("could not parse module");
+		resolver.borrow_mut().code_splitting = code_splitting;
+		let (code, _) = module.transform(resolver.clone(), &EmitOptions::default()).unwrap();
+		let chunks = resolver.borrow().chunks.clone();
+		(code, chunks)
+	};
+	let (code, chunks) = transform(source, true);
+	// the static imports are packed in the bundle
+	assert!(code.contains("const { a  } = __ALEPH__.pack[\"/a.ts\"];"));
+	assert!(code.contains("__ALEPH__.import(\"/heavy.ts\")"));
+	let chunk = |name: &str, specifier: &str| Chunk {
+		name: name.into(),
+		specifier: specifier.into(),
+	};
+	assert_eq!(
+		chunks,
+		vec![
+			chunk("heavy", "/heavy.ts"),
+			chunk("about", "/pages/about/index.tsx"),
+			chunk("heavy-2", "/lib/heavy.ts?v=2"),
+		]
+	);
+	assert_eq!(
+		serde_json::to_string(&chunks[0]).unwrap(),
+		r#"{"name":"heavy","specifier":"/heavy.ts"}"#
+	);
+	// the chunks of the previous transform are not kept
+	let (_, chunks) = transform("const a = () => import(\"./about.ts\")", true);
+	assert_eq!(chunks, vec![chunk("about", "/about.ts")]);
+	let (_, chunks) = transform(source, false);
+	assert!(chunks.is_empty());
+}
+
+#[test]
+fn export_manifest() {
+	let source = r#"
+      export { a, b as c } from "./a.ts"
+      export * as ns from "./b.ts"
+      export { default as d } from "./b.ts"
+      export * from "./c.ts"
+      export const e = 1
+      export default function App() {}
+    "#;
+	let module = SWC::parse("/app.ts", source, None).expect("could not parse module");
+	let resolver = Rc::new(RefCell::new(Resolver::new(
+		"/app.ts",
+		ImportHashMap::default(),
+		true,
+		vec!["/a.ts".into(), "/b.ts".into()],
+		None,
+	)));
+	let (code, _) = module.transform(resolver.clone(), &EmitOptions::default()).unwrap();
+	assert!(code.contains("__ALEPH__.pack[\"/a.ts\"]"));
+	assert!(code.contains("__ALEPH__.pack[\"/b.ts\"]"));
+	let mut manifest: Vec<(String, String)> = resolver
+		.borrow()
+		.bundle_output
+		.export_manifest
+		.clone()
+		.into_iter()
+		.collect();
+	manifest.sort();
+	assert_eq!(
+		manifest,
+		vec![
+			("a".into(), "/a.ts".into()),
+			("c".into(), "/a.ts".into()),
+			("d".into(), "/b.ts".into()),
+			("default".into(), "/app.ts".into()),
+			("e".into(), "/app.ts".into()),
+			("ns".into(), "/b.ts".into()),
+			("{/c.ts}".into(), "/c.ts".into()),
+		]
+	);
+
+	// the manifest is recorded in the bundle mode only
+	let (_, resolver) = st("/app.ts", source, false);
+	assert!(resolver.borrow().bundle_output.export_manifest.is_empty());
+}
+
+#[test]
+fn scope_prefix() {
+	let source = r#"
+      import { h } from "https://esm.sh/preact"
+      const x = 1
+      const { y = 2 } = globalThis.config
+      function get() {
+        const h = 3
+        return { x, y, h }
+      }
+      export { x }
+      export const z = get(h)
+    "#;
+	let options = EmitOptions {
+		scope_prefix: Some("m1_".into()),
+		..Default::default()
+	};
+	let (code, _) = st_with("/mod.ts", source, &options).unwrap();
+	assert!(code.contains("import { h as m1_h } from \"https://esm.sh/preact\";"));
+	assert!(code.contains("const m1_x = 1;"));
+	assert!(code.contains("const { y: m1_y = 2  } = globalThis.config;"));
+	assert!(code.contains("function m1_get() {"));
+	assert!(code.contains("const h = 3;"));
+	assert!(code.contains("x: m1_x,"));
+	assert!(code.contains("y: m1_y,"));
+	assert!(code.contains("const m1_z = m1_get(m1_h);"));
+	assert!(code.contains("export { m1_x as x };"));
+	assert!(code.contains("export { m1_z as z };"));
+
+	// the keys sharing the names of the top-level bindings are kept
+	let source = r#"
+      import React from "https://esm.sh/react"
+      const x = 1
+      const key = "k"
+      export class A {
+        x = x
+        static key = "a";
+        [key] = 2
+        get() { return { x: this.x, key: A.key } }
+      }
+      export const App = () => <div title={key} />
+    "#;
+	let (code, _) = st_with("/mod.tsx", source, &options).unwrap();
+	assert!(code.contains("class m1_A {\n"));
+	assert!(code.contains("    x = m1_x;\n"));
+	assert!(code.contains("    static key = \"a\";\n"));
+	assert!(code.contains("    [m1_key] = 2;\n"));
+	assert!(code.contains("x: this.x,"));
+	assert!(code.contains("key: m1_A.key"));
+	assert!(code.contains("m1_React.createElement(\"div\", {\n        title: m1_key\n    })"));
+	assert!(!code.contains("m1_x1"));
+}
+
+#[test]
+fn type_only_enums() {
+	let source = r#"
+      enum Mode { Light, Dark }
+      enum Size { Small, Large }
+      enum Shadow { None }
+      export enum Align { Left, Right }
+      let mode: Mode = getMode()
+      const size = Size.Small
+      function render(shadow: Shadow): Array<Mode> {}
+    "#;
+	let (code, _) = st("/app.ts", source, false);
+	assert!(!code.contains("Mode["));
+	assert!(!code.contains("Shadow["));
+	assert!(code.contains("var Size;"));
+	assert!(code.contains("export var Align;"));
+	assert!(code.contains("let mode = getMode();"));
+}
+
+#[test]
+fn using_declarations() {
+	let source = r#"
+      import { connect } from "./db.ts"
+      await using c = connect()
+      using lock = c.lock(), log = c.log()
+      console.log("connected")
+      export const rows = await c.query("select 1"), size = rows.length
+      export default rows.length
+      let using = 1, x = using
+      function open() {
+        using file = openFile()
+        return file.read()
+      }
+      function read(path) {
+        const header = decode(path)
+        using file = openFile(path)
+        return header + decode(file.read())
+        function decode(data) { return data.trim() }
+      }
+      console.log(open())
+    "#;
+	let (code, _) = st("/app.ts", source, false);
+	assert!(code.contains(concat!(
+		"const c = connect();\n",
+		"let _lock;\n",
+		"try {\n",
+		"    _lock = c.lock();\n",
+		"} catch (_e) {\n",
+		"    if (c != null) await c[Symbol.asyncDispose]();\n",
+		"    throw _e;\n",
+		"}\n",
+		"const lock = _lock;\n",
+	)));
+	// the `lock` is disposed if the `log` can't be acquired
+	assert!(code.contains(concat!(
+		"        _log = c.log();\n",
+		"    } catch (_e) {\n",
+		"        if (lock != null) lock[Symbol.dispose]();\n",
+		"        throw _e;\n",
+		"    }\n",
+	)));
+	assert!(code.contains("const log = _log;\n"));
+	assert!(code.contains("            _rows = await c.query(\"select 1\");\n"));
+	assert!(code.contains("export const rows = _rows;\n"));
+	assert!(code.contains("            _size = rows.length;\n"));
+	assert!(code.contains("export const size = _size;\n"));
+	assert!(code.contains("export default _default;\n"));
+	assert!(code.contains("let using = 1;\nlet x = using;\n"));
+	assert!(!code.contains("export let"));
+	assert!(code.contains(concat!(
+		"            console.log(open());\n",
+		"        } finally{\n",
+		"            if (log != null) log[Symbol.dispose]();\n",
+		"        }\n",
+		"    } finally{\n",
+		"        if (lock != null) lock[Symbol.dispose]();\n",
+		"    }\n",
+		"} finally{\n",
+		"    if (c != null) await c[Symbol.asyncDispose]();\n",
+		"}\n",
+	)));
+	assert!(code.contains("const file = openFile();\n    try {\n        return file.read();"));
+	assert!(code.contains("if (file != null) file[Symbol.dispose]();"));
+	// the function declared after the `using` is hoisted in its block
+	assert!(code.contains(concat!(
+		"    const file = openFile(path);\n",
+		"    function decode(data) {\n",
+		"        return data.trim();\n",
+		"    }\n",
+		"    try {\n",
+		"        return header + decode(file.read());\n",
+	)));
+}
+
+#[test]
+fn opaque_specifiers() {
+	let source = r#"
+      import "data:text/javascript,console.log(1)"
+      const m = await import("data:text/javascript,export const x=1")
+      const worker = new Worker(URL.createObjectURL(blob))
+      const shared = new SharedWorker("blob:https://esm.sh/0b2f6a5c")
+      const local = await import("file:///tmp/mod.js")
+    "#;
+	let (code, resolver) = st("https://esm.sh/app/main.js", source, false);
+	assert!(code.contains("import \"data:text/javascript,console.log(1)\";"));
+	assert!(code.contains("import(\"data:text/javascript,export const x=1\")"));
+	assert!(code.contains("new SharedWorker(\"blob:https://esm.sh/0b2f6a5c\")"));
+	assert!(code.contains("import(\"file:///tmp/mod.js\")"));
+	let deps: Vec<(String, DependencyKind)> = resolver
+		.borrow()
+		.deps
+		.iter()
+		.map(|dep| (dep.specifier.clone(), dep.kind))
+		.collect();
+	assert_eq!(
+		deps,
+		vec![
+			("data:text/javascript,console.log(1)".into(), DependencyKind::Opaque),
+			("data:text/javascript,export const x=1".into(), DependencyKind::Opaque),
+			("blob:https://esm.sh/0b2f6a5c".into(), DependencyKind::Opaque),
+			("file:///tmp/mod.js".into(), DependencyKind::Opaque),
+		]
+	);
+}
+
+#[test]
+fn labeled_statements() {
+	let source = r#"
+      export async function find(rows, load) {
+        let found = null
+        outer: for (const row of rows) {
+          inner: for (const cell of row) {
+            if (cell === null) continue outer
+            if (await load(cell)) {
+              found = cell
+              break outer
+            }
+            if (cell < 0) break inner
+          }
+        }
+        return found
+      }
+    "#;
+	let emit = |source: &str, options: EmitOptions| {
+		let (code, _) = st_with("/mod.js", source, &options).unwrap();
+		code
+	};
+	let loops = [
+		"outer: for (const row of rows){",
+		"    inner: for (const cell of row){",
+		"        if (cell === null) continue outer;",
+		"            break outer;",
+		"        if (cell < 0) break inner;",
+	];
+	for target in [JscTarget::Es2022, JscTarget::Es2015] {
+		let code = emit(
+			source,
+			EmitOptions {
+				target,
+				verify_output: true,
+				..Default::default()
+			},
+		);
+		for line in loops {
+			assert!(code.contains(line), "{}", line);
+		}
+	}
+
+	// the labels and the bindings with the same names survive the resolver and hygiene passes
+	let source = r#"
+      export function f(rows) {
+        const outer = 2
+        outer: for (const row of rows) {
+          for (const outer of row) {
+            if (outer) break outer
+            continue outer
+          }
+        }
+        return outer
+      }
+    "#;
+	let code = emit(
+		source,
+		EmitOptions {
+			output_format: OutputFormat::Cjs,
+			dce: true,
+			verify_output: true,
+			..Default::default()
+		},
+	);
+	assert!(code.contains(
+		"    const outer = 2;\n    outer: for (const row of rows){\n        for (const outer of row){\n            if (outer) break outer;\n            continue outer;\n        }\n    }\n    return outer;\n"
+	));
+}
+
+#[test]
+fn directives() {
+	let source = r#"
+      /// <reference types="node" />
+      /// <reference path="./globals.d.ts" />
+      /// <reference lib="dom" />
+      import App from "./App.tsx"
+
+      // @ts-expect-error: the prop is missing
+      export default <App />
+      /* just a comment */
+    "#;
+	let module = SWC::parse("/index.tsx", source, None).expect("could not parse module");
+	let directives = module.directives();
+	assert_eq!(
+		directives
+			.references
+			.iter()
+			.map(|r| (r.kind, r.value.as_str()))
+			.collect::<Vec<(ReferenceKind, &str)>>(),
+		vec![
+			(ReferenceKind::Types, "node"),
+			(ReferenceKind::Path, "./globals.d.ts"),
+			(ReferenceKind::Lib, "dom"),
+		]
+	);
+	assert_eq!(directives.ts_directives.len(), 1);
+	assert_eq!(directives.ts_directives[0].name, "ts-expect-error");
+	assert_eq!(directives.ts_directives[0].text, "the prop is missing");
+	assert_eq!(directives.ts_directives[0].line, 7);
+
+	let resolver = Rc::new(RefCell::new(Resolver::new(
+		"/index.tsx",
+		ImportHashMap::default(),
+		false,
+		vec![],
+		None,
+	)));
+	module
+		.transform(resolver.clone(), &EmitOptions::default())
+		.unwrap();
+	let r = resolver.borrow();
+	assert_eq!(
+		r.deps
+			.iter()
+			.map(|dep| (dep.specifier.as_str(), dep.kind))
+			.collect::<Vec<(&str, DependencyKind)>>(),
+		vec![
+			("/App.tsx", DependencyKind::Import),
+			("node", DependencyKind::TypeReference),
+			("./globals.d.ts", DependencyKind::TypeReference),
+		]
+	);
+}
+
+#[test]
+fn types_only() {
+	let source = r#"
+      import { expensiveCall } from "./expensive.ts"
+      import type { Options } from "./types.ts"
+      interface Props { name: string }
+      export interface Theme { color: string }
+      export type Mode = "light" | "dark"
+      export const x: number = expensiveCall()
+      export let { a, b: [c] } = expensiveCall()
+      export function f(options: Options) { return expensiveCall(options) }
+      export class C {}
+      export enum E { A }
+      const local = 1
+      export { local as renamed, Props }
+      export default class {}
+      export { y } from "./y.ts"
+      export * from "./z.ts"
+      console.log(expensiveCall())
+    "#;
+	let options = EmitOptions {
+		types_only: true,
+		..Default::default()
+	};
+	let (code, resolver) = st_with("/mod.ts", source, &options).unwrap();
+	assert_eq!(
+		code,
+		[
+			"export const x = undefined;",
+			"export const a = undefined;",
+			"export const c = undefined;",
+			"export const f = undefined;",
+			"export const C = undefined;",
+			"export const E = undefined;",
+			"export const renamed = undefined;",
+			"export default undefined;",
+			"export { y } from \"/y.ts\";",
+			"export * from \"[/z.ts]:/z.ts\";",
+			"",
+		]
+		.join("\n")
+	);
+	let r = resolver.borrow();
+	assert_eq!(
+		r.deps
+			.iter()
+			.map(|dep| dep.specifier.as_str())
+			.collect::<Vec<&str>>(),
+		vec!["/y.ts", "/z.ts"]
+	);
+}
+
+#[test]
+fn inline_constants() {
+	let source = r#"
+      import { VERSION, DEBUG as D } from "./v.ts"
+      import { VERSION as V, createApp } from "./app.ts"
+      import { NAME } from "./name.ts"
+      function print(VERSION) {
+        console.log(VERSION)
+      }
+      print({ VERSION, D, meta: env.VERSION })
+      createApp(V, NAME)
+      export { NAME }
+    "#;
+	let module = SWC::parse("/main.ts", source, None).expect("could not parse module");
+	let mut resolver = Resolver::new("/main.ts", ImportHashMap::default(), false, vec![], None);
+	resolver.source_fetcher = Some(Box::new(|specifier| match specifier {
+		"./v.ts" => Some("export const VERSION = \"1.0\"\nexport const DEBUG = false as const".into()),
+		"./app.ts" => Some("export const VERSION = 2\nexport function createApp() {}".into()),
+		"./name.ts" => Some("export const NAME = \"app\"".into()),
+		_ => None,
+	}));
+	let resolver = Rc::new(RefCell::new(resolver));
+	let options = EmitOptions {
+		inline_constants: true,
+		..Default::default()
+	};
+	let (code, _) = module.transform(resolver.clone(), &options).unwrap();
+	assert!(!code.contains("./v.ts"));
+	assert!(code.contains("console.log(VERSION)"));
+	assert!(code.contains("VERSION: \"1.0\""));
+	assert!(code.contains("D: false"));
+	assert!(code.contains("env.VERSION"));
+	// bail: `createApp` is not a literal
+	assert!(code.contains("import { VERSION as V, createApp } from \"/app.ts\""));
+	// bail: `NAME` is re-exported
+	assert!(code.contains("import { NAME } from \"/name.ts\""));
+	let r = resolver.borrow();
+	assert_eq!(
+		r.deps
+			.iter()
+			.map(|dep| dep.specifier.as_str())
+			.collect::<Vec<&str>>(),
+		vec!["/app.ts", "/name.ts"]
+	);
+}
+
+#[test]
+fn expand_star_exports() {
+	let source = r#"
+      export * from "./a.ts"
+      export * from "./b.ts"
+      export * from "./c.ts"
+      export const x = 1
+    "#;
+	let module = SWC::parse("/mod.ts", source, None).expect("could not parse module");
+	let mut resolver = Resolver::new("/mod.ts", ImportHashMap::default(), false, vec![], None);
+	resolver.source_fetcher = Some(Box::new(|specifier| match specifier {
+		"./a.ts" => Some("export const x = 2, y = 2, z = 2\nexport default 2".into()),
+		"./b.ts" => Some("export function z() {}\nexport class B {}".into()),
+		"./c.ts" => Some("export * from \"./d.ts\"".into()),
+		_ => None,
+	}));
+	let resolver = Rc::new(RefCell::new(resolver));
+	let options = EmitOptions {
+		expand_star_exports: true,
+		..Default::default()
+	};
+	let (code, _) = module.transform(resolver.clone(), &options).unwrap();
+	// the local `x` shadows `a`'s `x`, the `z` is ambiguous
+	assert!(code.contains("export { y } from \"/a.ts\""));
+	assert!(code.contains("export { B } from \"/b.ts\""));
+	// the names of `c.ts` are unknown
+	assert!(code.contains("export * from \"[/c.ts]:/c.ts\""));
+	assert!(code.contains("export const x = 1"));
+}
+
+#[test]
+fn error_overlay() {
+	let source = r#"
+      import { init } from "./init.ts"
+      const { a, b: [c] } = await init()
+      export let d = a + c, e
+      export class C {}
+      export function f() { return a }
+      export default function App() {}
+      console.log(f())
+    "#;
+	// the react refresh is not applied to the remote modules
+	let emit = |error_handler: Option<String>, catch_rejections: bool| {
+		let options = EmitOptions {
+			is_dev: true,
+			error_overlay: true,
+			error_handler,
+			catch_rejections,
+			..Default::default()
+		};
+		let (code, _) = st_with("https://deno.land/x/mod.ts", source, &options).unwrap();
+		code
+	};
+	let code = emit(None, false);
+	assert!(code.contains("import { init } from \"https://deno.land/x/init.ts\";\n"));
+	assert!(code.contains("let a, c;\n"));
+	assert!(code.contains("export let d, e;\n"));
+	assert!(code.contains("export let C;\n"));
+	assert!(code.contains("export function f() {"));
+	assert!(code.contains("export default function App() {"));
+	assert!(code.contains("try {\n"));
+	assert!(code.contains("({ a , b: [c]  } = await init());"));
+	assert!(code.contains("d = a + c;"));
+	assert!(code.contains("C = class C {"));
+	assert!(code.contains("console.log(f());\n} catch (err) {\n"));
+	assert!(code.contains("if (import.meta.hot) import.meta.hot.reportError(err);"));
+	assert!(code.contains("throw err;"));
+	assert!(!code.contains("unhandledrejection"));
+
+	let code = emit(Some("__reportError".into()), true);
+	assert!(code.contains("if (typeof __reportError === \"function\") __reportError(err);"));
+	assert!(code.contains("addEventListener(\"unhandledrejection\", (e)=>{"));
+	assert!(code.contains("__reportError(e.reason);"));
+}
+
+#[test]
+fn cjs_to_esm() {
+	let emit = |source: &str| {
+		let options = EmitOptions {
+			cjs_to_esm: true,
+			..Default::default()
+		};
+		let (code, _) = st_with("/index.js", source, &options).unwrap();
+		code
+	};
+
+	let code = emit(
+		r#"
+      "use strict";
+      Object.defineProperty(exports, "__esModule", { value: true });
+      exports.default = App;
+      exports.version = "1.0";
+      function App() {}
+    "#,
+	);
+	assert!(!code.contains("__esModule"));
+	assert!(code.contains("var module = {\n    exports: {\n    }\n}, exports = module.exports;\n"));
+	assert!(code.contains("exports.default = App;\n"));
+	assert!(code.contains("export default module.exports.default;\n"));
+	assert!(code.contains("var _version = module.exports.version;\nexport { _version as version };\n"));
+
+	let code = emit("exports.foo = 1;\nmodule.exports.bar = 2;\n");
+	assert!(code.contains("export default module.exports;\n"));
+	assert!(code.contains("export { _foo as foo };\n"));
+	assert!(code.contains("export { _bar as bar };\n"));
+
+	let code = emit("module.exports = require(\"./app.js\").default;\n");
+	assert_eq!(code, "export { default } from \"/app.js\";\n");
+
+	let code = emit("module.exports = require(\"./app.js\");\n");
+	assert_eq!(
+		code,
+		"export { default } from \"/app.js\";\nexport * from \"[/app.js]:/app.js\";\n"
+	);
+
+	// the ES modules are kept
+	let code = emit("export const foo = exports.foo;\n");
+	assert_eq!(code, "export const foo = exports.foo;\n");
+}
+
+#[test]
+fn require_in_esm() {
+	let source = r#"
+      import { h } from "./h.ts"
+      const config = require("./config.js")
+      export async function load() {
+        const yaml = require("./yaml.js")
+        return yaml.parse(config.file)
+      }
+    "#;
+	let emit = |require_in_esm: RequireMode| {
+		let options = EmitOptions {
+			require_in_esm,
+			..Default::default()
+		};
+		st_with("/mod.ts", source, &options).map(|(code, _)| code)
+	};
+	let code = emit(RequireMode::DynamicImport).unwrap();
+	assert!(code.contains("import * as _config from \"/config.js\";\n"));
+	assert!(code.contains(
+		"const config = _config.default !== void 0 ? _config.default : _config;\n"
+	));
+	assert!(code.contains("const yaml = await import(\"/yaml.js\").then((m)=>m.default"));
+	assert!(code.contains("(m)=>m.default !== void 0 ? m.default : m\n"));
+	assert!(!code.contains("require("));
+
+	let err = emit(RequireMode::Error).unwrap_err().to_string();
+	assert_eq!(
+		err,
+		[
+			"`require(\"./config.js\")` is not allowed in an ES module at /mod.ts:3:21",
+			"`require(\"./yaml.js\")` is not allowed in an ES module at /mod.ts:5:21",
+		]
+		.join("\n")
+	);
+
+	let code = emit(RequireMode::Leave).unwrap();
+	assert!(code.contains("const config = require(\"./config.js\");\n"));
+
+	// the conditional calls would be evaluated eagerly by the static imports
+	let source = r#"
+      export const utils = require("./utils.js")
+      if (dev) { const devtools = require("./devtools.js") }
+      try { require("./optional.js") } catch {}
+      export function load() { return require("./lazy.js") }
+    "#;
+	let module = SWC::parse("/mod.ts", source, None).expect("could not parse module");
+	let resolver = new_resolver("/mod.ts", false);
+	let options = EmitOptions {
+		require_in_esm: RequireMode::DynamicImport,
+		..Default::default()
+	};
+	let err = module.transform(resolver, &options).unwrap_err().to_string();
+	let errors: Vec<&str> = err.lines().collect();
+	assert_eq!(errors.len(), 3);
+	assert!(errors[0].starts_with("`require(\"./devtools.js\")` can't be rewritten"));
+	assert!(errors[1].starts_with("`require(\"./optional.js\")`"));
+	assert!(errors[2].starts_with("`require(\"./lazy.js\")`"));
+	let module = SWC::parse("/mod.ts", r#"export const utils = require("./utils.js")"#, None)
+		.expect("could not parse module");
+	let resolver = Rc::new(RefCell::new(Resolver::new(
+		"/mod.ts",
+		ImportHashMap::default(),
+		false,
+		vec![],
+		None,
+	)));
+	let (code, _) = module.transform(resolver, &options).unwrap();
+	assert!(code.starts_with("import * as _utils from \"/utils.js\";\n"));
+	assert!(code.contains("export const utils = _utils.default !== void 0"));
+
+	// the `require` binding of the module is kept
+	let source = r#"
+      import { createRequire } from "node:module"
+      const require = createRequire(import.meta.url)
+      const path = require("node:path")
+    "#;
+	let options = EmitOptions {
+		require_in_esm: RequireMode::Error,
+		..Default::default()
+	};
+	let (code, _) = st_with("/mod.ts", source, &options).unwrap();
+	assert!(code.contains("const path = require(\"node:path\");\n"));
+}
+
+#[test]
+fn shared_helpers() {
+	let sources = [
+		(
+			"/a.ts",
+			r#"
+      export class A {
+        @log
+        method() {}
+      }
+    "#,
+		),
+		(
+			"/b.ts",
+			r#"
+      export class B {
+        @log
+        @memo
+        method() {}
+      }
+    "#,
+		),
+	];
+	let mut bundle: Vec<String> = vec![];
+	let mut helpers: Vec<String> = vec![];
+	for (specifier, source) in sources {
+		let module = SWC::parse(specifier, source, None).expect("could not parse module");
+		let resolver = new_resolver(specifier, true);
+		let options = EmitOptions {
+			shared_helpers: true,
+			..Default::default()
+		};
+		let (code, _) = module.transform(resolver.clone(), &options).unwrap();
+		assert!(code.contains("const swcHelpers = __ALEPH__.helpers;"));
+		assert!(code.contains("swcHelpers.applyDecoratedDescriptor("));
+		assert!(!code.contains("function _applyDecoratedDescriptor("));
+		assert!(!code.contains("@swc/helpers"));
+		helpers.extend(resolver.borrow().helpers.iter().cloned());
+		bundle.push(code);
+	}
+	assert_eq!(helpers, vec!["applyDecoratedDescriptor", "applyDecoratedDescriptor"]);
+	bundle.insert(0, build_shared_helpers(&helpers, Indent::default()));
+	let bundle = bundle.join("\n");
+	assert_eq!(bundle.matches("function _applyDecoratedDescriptor(").count(), 1);
+	assert!(bundle.contains("__ALEPH__.helpers = {\n    applyDecoratedDescriptor: _applyDecoratedDescriptor\n};"));
+
+	// the external names of swc, the unknown names are ignored
+	let names: Vec<String> = vec!["typeOf".into(), "_throw".into(), "unknown".into()];
+	let shared = build_shared_helpers(&names, Indent::default());
+	assert!(shared.contains("var _typeof = function(obj) {\n"));
+	assert!(shared.contains("function _throw(e) {\n"));
+	assert!(shared.ends_with("helpers = {\n    typeOf: _typeof,\n    _throw: _throw\n};\n"));
+}
+
+#[test]
+fn exponentiation() {
+	let source = r#"
+      export let x = 3
+      export const y = x ** 2
+      x **= 2
+      get().a **= 2
+      obj[key()] **= 2
+    "#;
+	let emit = |target: JscTarget| {
+		let options = EmitOptions {
+			target,
+			..Default::default()
+		};
+		st_target("/mod.ts", source, &options)
+	};
+	let code = emit(JscTarget::Es2015);
+	assert!(code.contains("var _ref, _key;\n"));
+	assert!(code.contains("export const y = Math.pow(x, 2);\n"));
+	assert!(code.contains("x = Math.pow(x, 2);\n"));
+	// the object and the computed key of the target are evaluated once
+	assert!(code.contains("(_ref = get()).a = Math.pow(_ref.a, 2);\n"));
+	assert!(code.contains("obj[_key = key()] = Math.pow(obj[_key], 2);\n"));
+	assert_eq!(code.matches("get()").count(), 1);
+	assert_eq!(code.matches("key()").count(), 1);
+
+	let code = emit(JscTarget::Es2016);
+	assert!(code.contains("export const y = x ** 2;\n"));
+	assert!(code.contains("x **= 2;\n"));
+	assert!(code.contains("obj[key()] **= 2;\n"));
+}
+
+#[test]
+fn object_spread() {
+	let source = "export const o = { ...a, b: 1 }";
+	let emit = |target: JscTarget, shared_helpers: bool| {
+		let module = SWC::parse("/mod.ts", source, None).expect("could not parse module");
+		let resolver = new_resolver("/mod.ts", shared_helpers);
+		let options = EmitOptions {
+			target,
+			shared_helpers,
+			..Default::default()
+		};
+		let code = transform_target(&module, resolver.clone(), &options);
+		let helpers: Vec<String> = resolver.borrow().helpers.iter().cloned().collect();
+		(code, helpers)
+	};
+	let (code, _) = emit(JscTarget::Es2017, false);
+	assert!(code.contains("function _defineProperty(obj, key, value) {\n"));
+	assert!(code.contains("function _objectSpread(target) {\n"));
+	assert!(code.contains("export const o = _objectSpread({\n}, a, {\n    b: 1\n});"));
+
+	let (code, helpers) = emit(JscTarget::Es2017, true);
+	assert!(code.contains("export const o = swcHelpers.objectSpread({\n}, a, {\n    b: 1\n});"));
+	assert!(!code.contains("function _objectSpread("));
+	assert_eq!(helpers, vec!["objectSpread"]);
+	let shared = build_shared_helpers(&helpers, Indent::default());
+	assert!(shared.contains("function _defineProperty(obj, key, value) {\n"));
+	assert!(shared.contains("__ALEPH__.helpers = {\n    objectSpread: _objectSpread\n};"));
+
+	let (code, _) = emit(JscTarget::Es2018, false);
+	assert!(code.contains("export const o = {\n    ...a,\n    b: 1\n};"));
+}
+
+#[test]
+fn minify_symbol_keys() {
+	let source = r#"
+      export class Range {
+        constructor(n) { this.n = n }
+        *[Symbol.iterator]() { for (let i = 0; i < this.n; i++) yield i }
+        async *[Symbol.asyncIterator]() { yield* this }
+        get [Symbol.toStringTag]() { return "Range" }
+        static [Symbol.for("range")] = true
+      }
+      export const first = (obj) => obj[Symbol.iterator]().next().value
+    "#;
+	let emit = |target: JscTarget| {
+		let options = EmitOptions {
+			target,
+			minify: true,
+			dce: true,
+			constant_fold: true,
+			verify_output: true,
+			..Default::default()
+		};
+		st_with("/mod.ts", source, &options).unwrap().0
+	};
+	let code = emit(JscTarget::Es2022);
+	assert!(code.contains("export class Range{constructor(n){this.n=n;}"));
+	assert!(code.contains("*[Symbol.iterator](){for(let i=0;i<this.n;i++)yield i;}"));
+	assert!(code.contains("async *[Symbol.asyncIterator](){yield*this;}"));
+	assert!(code.contains("get[Symbol.toStringTag](){return\"Range\";}"));
+	assert!(code.contains("static [Symbol.for(\"range\")]=true;}"));
+	assert!(code.contains("export const first=obj=>obj[Symbol.iterator]().next().value;"));
+
+	// the computed keys are evaluated before the class for the lowered class fields
+	let code = emit(JscTarget::Es2015);
+	assert!(code.contains("var tmp=Symbol.iterator,"));
+	assert!(code.contains("_ref=Symbol.for(\"range\");"));
+	assert!(code.contains("*[tmp](){for(let i=0;i<this.n;i++)yield i;}"));
+	assert!(code.contains("_defineProperty(Range,_ref,true);"));
+}
+
+#[test]
+fn constant_fold() {
+	let source = r#"
+      const x = 1 + 2 * 3
+      const s = "a" + "b" + "c"
+      const url = "https://esm.sh/" + "react@" + 17
+      const b = !true || (1 < 2 && "x" === "x")
+      const y = x + 1
+      const len = "abc".length
+      const pick = true ? x : y
+      const n = 1 - 2
+      export { x, s, url, b, y, len, pick, n }
+    "#;
+	let emit = |constant_fold: bool| {
+		let options = EmitOptions {
+			constant_fold,
+			..Default::default()
+		};
+		let (code, _) = st_with("/mod.js", source, &options).unwrap();
+		code
+	};
+	let code = emit(true);
+	assert!(code.contains("const x = 7;"));
+	assert!(code.contains("const s = \"abc\";"));
+	assert!(code.contains("const url = \"https://esm.sh/react@17\";"));
+	assert!(code.contains("const b = true;"));
+	assert!(code.contains("const y = x + 1;"));
+	// only the operations of the literals are folded
+	assert!(code.contains("const len = \"abc\".length;"));
+	assert!(code.contains("const pick = true ? x : y;"));
+	assert!(code.contains("const n = 1 - 2;"));
+	let code = emit(false);
+	assert!(code.contains("const x = 1 + 2 * 3;"));
+	assert!(code.contains("const s = \"a\" + \"b\" + \"c\";"));
+}
+
+#[test]
+fn restore_jsx() {
+	let source = r#"
+      import React from "https://esm.sh/react"
+      import * as UI from "./ui.js"
+      const a = React.createElement("div", {id:"a"}, "hi")
+      const b = React.createElement(React.Fragment, null, React.createElement(UI.Button, { onClick, ...props }), " ", count)
+      const c = React.createElement("input", { "aria-label": "name", value: "say \"hi\"" })
+      const d = React.createElement(tag, null)
+      const e = React.createElement("p", null, ...children)
+    "#;
+	let options = EmitOptions {
+		restore_jsx: true,
+		..Default::default()
+	};
+	let (code, _) = st_with("/app.js", source, &options).unwrap();
+	assert!(code.contains("const a = <div id=\"a\">hi</div>;"));
+	assert!(code.contains("const b = <><UI.Button onClick={onClick} {...props}/>{\" \"}{count}</>;"));
+	assert!(code.contains("const c = <input aria-label=\"name\" value={\"say \\\"hi\\\"\"}/>;"));
+	assert!(code.contains("const d = React.createElement(tag, null);"));
+	assert!(code.contains("const e = React.createElement(\"p\", null, ...children);"));
+}
+
+#[test]
+fn target_query() {
+	let source = r#"
+      import React from "https://esm.sh/react"
+      import { render } from "https://esm.sh/react-dom?target=es2015"
+      import { foo } from "./foo.ts"
+      export const lazy = () => import("https://esm.sh/lodash?dev")
+      console.log(React, render, foo)
+    "#;
+	let module = SWC::parse("/app.ts", source, None).expect("could not parse module");
+	let resolver = new_resolver("/app.ts", false);
+	let options = EmitOptions {
+		target: JscTarget::Es2017,
+		target_query: true,
+		..Default::default()
+	};
+	let (code, _) = module.transform(resolver.clone(), &options).unwrap();
+	assert!(code.contains("import React from \"https://esm.sh/react?target=es2017\""));
+	assert!(code.contains("import { render } from \"https://esm.sh/react-dom?target=es2015\""));
+	assert!(code.contains("import { foo } from \"/foo.ts\""));
+	assert!(code.contains("import(\"https://esm.sh/lodash?dev&target=es2017\")"));
+	let r = resolver.borrow();
+	assert_eq!(
+		r.deps
+			.iter()
+			.map(|dep| dep.specifier.as_str())
+			.collect::<Vec<&str>>(),
+		vec![
+			"https://esm.sh/react?target=es2017",
+			"https://esm.sh/react-dom?target=es2015",
+			"/foo.ts",
+			"https://esm.sh/lodash?dev&target=es2017",
+		]
+	);
+	drop(r);
+
+	// the target is not kept in the resolver
+	let (code, _) = module.transform(resolver.clone(), &EmitOptions::default()).unwrap();
+	assert!(code.contains("import React from \"https://esm.sh/react\""));
+
+	// each output requests the builds of its target
+	let resolver = Rc::new(RefCell::new(Resolver::new(
+		"/app.ts",
+		ImportHashMap::default(),
+		false,
+		vec![],
+		None,
+	)));
+	let outputs = module
+		.transform_targets(resolver, &options, vec![JscTarget::Es2015, JscTarget::Es2020])
+		.unwrap();
+	for ((target, output), name) in outputs.iter().zip(["es2015", "es2020"]) {
+		let react = format!("https://esm.sh/react?target={}", name);
+		assert!(output.code.contains(format!("import React from \"{}\"", react).as_str()));
+		assert!(output.code.contains("\"https://esm.sh/react-dom?target=es2015\""));
+		assert_eq!(output.deps[0].specifier, react, "{}", target_name(*target));
+	}
+}
+
+#[test]
+fn extra_query() {
+	let source = r#"
+      import React from "https://esm.sh/react"
+      import { foo } from "./foo.ts?foo=1"
+      import { bar } from "./bar.ts?target=es2015#bar"
+      export * from "./baz.ts"
+      export const lazy = () => import("./lazy.ts")
+      console.log(React, foo, bar)
+    "#;
+	let module = SWC::parse("/app.ts", source, None).expect("could not parse module");
+	let mut resolver = Resolver::new("/app.ts", ImportHashMap::default(), false, vec![], None);
+	resolver.extra_query = Some("target=es2020".into());
+	let resolver = Rc::new(RefCell::new(resolver));
+	let (code, _) = module
+		.transform(resolver.clone(), &EmitOptions::default())
+		.unwrap();
+	assert!(code.contains("import React from \"https://esm.sh/react?target=es2020\""));
+	assert!(code.contains("import { foo } from \"/foo.ts?foo=1&target=es2020\""));
+	assert!(code.contains("import { bar } from \"/bar.ts?target=es2015#bar\""));
+	assert!(code.contains("import(\"/lazy.ts?target=es2020\")"));
+	let r = resolver.borrow();
+	assert_eq!(
+		r.deps
+			.iter()
+			.map(|dep| dep.specifier.as_str())
+			.collect::<Vec<&str>>(),
+		vec![
+			"https://esm.sh/react?target=es2020",
+			"/foo.ts?foo=1&target=es2020",
+			"/bar.ts?target=es2015#bar",
+			"/baz.ts?target=es2020",
+			"/lazy.ts?target=es2020"
+		]
+	);
+}
+
+#[test]
+fn remote_mirrors() {
+	let source = r#"
+      import { serve } from "https://deno.land/std/http/server.ts"
+      import { assert } from "https://deno.land/std/testing/asserts.ts"
+      import React from "https://esm.sh/react"
+      console.log(serve, assert, React)
+    "#;
+	let module = SWC::parse("/app.ts", source, None).expect("could not parse module");
+	let mut resolver = Resolver::new("/app.ts", ImportHashMap::default(), false, vec![], None);
+	resolver.remote_mirrors = vec![
+		("https://deno.land/".into(), "https://mirror.internal/deno/".into()),
+		("https://deno.land/std/".into(), "https://mirror.internal/deno_std/".into()),
+		("https://deno.land/std/testing/".into(), "https://mirror.internal/testing/".into()),
+	];
+	let resolver = Rc::new(RefCell::new(resolver));
+	let (code, _) = module
+		.transform(resolver.clone(), &EmitOptions::default())
+		.unwrap();
+	assert!(code.contains(
+		"import { serve } from \"https://mirror.internal/deno_std/http/server.ts\""
+	));
+	assert!(code.contains(
+		"import { assert } from \"https://mirror.internal/testing/asserts.ts\""
+	));
+	assert!(code.contains("import React from \"https://esm.sh/react\""));
+}
+
+#[test]
+fn css_query() {
+	let source = r#"
+      import "./style.css"
+      import "https://esm.sh/normalize.css@8/normalize.css?v=1"
+      import "./app.ts"
+    "#;
+	let module = SWC::parse("/main.ts", source, None).expect("could not parse module");
+	let mut resolver = Resolver::new("/main.ts", ImportHashMap::default(), false, vec![], None);
+	resolver.css_query = Some("module".into());
+	let resolver = Rc::new(RefCell::new(resolver));
+	let (code, _) = module
+		.transform(resolver.clone(), &EmitOptions::default())
+		.unwrap();
+	assert!(code.contains("import \"/style.css?module\";\n"));
+	assert!(code.contains(
+		"import \"https://esm.sh/normalize.css@8/normalize.css?v=1&module\";\n"
+	));
+	assert!(code.contains("import \"/app.ts\";\n"));
+	let deps: Vec<(String, bool)> = resolver
+		.borrow()
+		.deps
+		.iter()
+		.map(|dep| (dep.specifier.clone(), dep.is_side_effect))
+		.collect();
+	assert_eq!(
+		deps,
+		vec![
+			("/style.css?module".into(), true),
+			("https://esm.sh/normalize.css@8/normalize.css?v=1&module".into(), true),
+			("/app.ts".into(), true),
+		]
+	);
+}
+
+#[test]
+fn output_extensions() {
+	let extensions: HashMap<String, String> = [(".ts", ".js"), (".tsx", ".js"), (".jsx", ".js")]
+		.iter()
+		.map(|(from, to)| (from.to_string(), to.to_string()))
+		.collect();
+	assert_eq!(replace_extension("./a.ts", &extensions), "./a.js");
+	assert_eq!(replace_extension("./b.tsx?x=1", &extensions), "./b.js?x=1");
+	assert_eq!(replace_extension("./c.jsx#c", &extensions), "./c.js#c");
+	assert_eq!(replace_extension("./d", &extensions), "./d");
+	assert_eq!(replace_extension("./v1.0/e?f=g.ts", &extensions), "./v1.0/e?f=g.ts");
+
+	let source = r#"
+      import { a } from "./a.ts"
+      import { b } from "./b.tsx?x=1"
+      import { c } from "../c"
+      import { d } from "/d.ts"
+      import { e } from "https://deno.land/x/e.ts"
+      console.log(a, b, c, d, e)
+    "#;
+	let module = SWC::parse("/src/app.ts", source, None).expect("could not parse module");
+	let mut resolver = Resolver::new("/src/app.ts", ImportHashMap::default(), false, vec![], None);
+	resolver.output_extensions = extensions;
+	let resolver = Rc::new(RefCell::new(resolver));
+	let (code, _) = module
+		.transform(resolver.clone(), &EmitOptions::default())
+		.unwrap();
+	assert!(code.contains("import { a } from \"/src/a.js\""));
+	assert!(code.contains("import { b } from \"/src/b.js?x=1\""));
+	assert!(code.contains("import { c } from \"/c\""));
+	assert!(code.contains("import { d } from \"/d.ts\""));
+	assert!(code.contains("import { e } from \"https://deno.land/x/e.ts\""));
+}
+
+#[test]
+fn relative_preserve() {
+	let source = r#"
+      import React from "react"
+      import { a } from "./a.ts"
+      import { b } from "../lib/b.tsx?x=1"
+      import { c } from "/c.ts"
+      console.log(React, a, b, c)
+    "#;
+	let mut import_map = ImportHashMap::default();
+	import_map
+		.imports
+		.insert("react".into(), "https://esm.sh/react@17.0.2".into());
+	let module = SWC::parse("/src/app.tsx", source, None).expect("could not parse module");
+	let mut resolver = Resolver::new("/src/app.tsx", import_map, false, vec![], None);
+	resolver.resolve_mode = ResolveMode::RelativePreserve;
+	resolver.output_extensions = [(".ts", ".js"), (".tsx", ".js")]
+		.iter()
+		.map(|(from, to)| (from.to_string(), to.to_string()))
+		.collect();
+	let resolver = Rc::new(RefCell::new(resolver));
+	let (code, _) = module
+		.transform(resolver.clone(), &EmitOptions::default())
+		.unwrap();
+	assert!(code.contains("import React from \"https://esm.sh/react@17.0.2\""));
+	assert!(code.contains("import { a } from \"./a.js\""));
+	assert!(code.contains("import { b } from \"../lib/b.js?x=1\""));
+	assert!(code.contains("import { c } from \"/c.ts\""));
+}
+
+#[test]
+fn on_unresolved() {
+	let source = r#"
+      import React from "react"
+      import { foo } from "this-package-does-not-exist"
+      import type { Bar } from "types-does-not-exist"
+      export const lazy = () => import("lazy-does-not-exist")
+      console.log(React, foo)
+    "#;
+	let emit = |on_unresolved: Option<UnresolvedPolicy>| {
+		let module = SWC::parse("/app.ts", source, None).expect("could not parse module");
+		let mut import_map = ImportHashMap::default();
+		import_map
+			.imports
+			.insert("react".into(), "https://esm.sh/react".into());
+		let mut resolver = Resolver::new("/app.ts", import_map, false, vec![], None);
+		resolver.on_unresolved = on_unresolved;
+		let resolver = Rc::new(RefCell::new(resolver));
+		module
+			.transform(resolver, &EmitOptions::default())
+			.map(|(code, _)| code)
+	};
+
+	let code = emit(None).unwrap();
+	assert!(code.contains("import { foo } from \"/this-package-does-not-exist\""));
+
+	let code = emit(Some(UnresolvedPolicy::LeaveAsIs)).unwrap();
+	assert!(code.contains("import React from \"https://esm.sh/react\""));
+	assert!(code.contains("import { foo } from \"this-package-does-not-exist\""));
+	assert!(code.contains("import(\"lazy-does-not-exist\")"));
+
+	let code = emit(Some(UnresolvedPolicy::RewriteToCdn)).unwrap();
+	assert!(code.contains("import React from \"https://esm.sh/react\""));
+	assert!(code.contains("import { foo } from \"https://esm.sh/this-package-does-not-exist\""));
+	assert!(code.contains("import(\"https://esm.sh/lazy-does-not-exist\")"));
+
+	let err = emit(Some(UnresolvedPolicy::Error)).unwrap_err();
+	assert_eq!(
+		err.to_string(),
+		[
+			"Unresolved specifier \"this-package-does-not-exist\" at /app.ts:3:26",
+			"Unresolved specifier \"lazy-does-not-exist\" at /app.ts:5:39",
+		]
+		.join("\n")
+	);
+}
+
+#[test]
+fn require_all_resolved() {
+	let source = r#"
+      import { a } from "./a.ts"
+      import { b } from "../../b.ts"
+      export const lazy = () => import("./lazy.ts")
+      console.log(a, b)
+    "#;
+	let emit = |specifier: &str, require_all_resolved: bool| {
+		let module = SWC::parse(specifier, source, None).expect("could not parse module");
+		let resolver = Resolver::new(specifier, ImportHashMap::default(), false, vec![], None);
+		let resolver = Rc::new(RefCell::new(resolver));
+		let options = EmitOptions {
+			require_all_resolved,
+			..Default::default()
+		};
+		module.transform(resolver, &options).map(|(code, _)| code)
+	};
+
+	let code = emit("/src/lib/app.ts", true).unwrap();
+	assert!(code.contains("import { a } from \"/src/lib/a.ts\""));
+	assert!(code.contains("import { b } from \"/b.ts\""));
+	assert!(code.contains("import(\"/src/lib/lazy.ts\")"));
+
+	// `../../b.ts` points outside of the root
+	let err = emit("/app.ts", true).unwrap_err();
+	assert_eq!(err.to_string(), "Unresolved specifier \"../../b.ts\" at /app.ts:3:24");
+	assert!(emit("/app.ts", false).is_ok());
+
+	// the relative specifiers of a module without a base
+	let err = emit("inline.ts", true).unwrap_err();
+	assert_eq!(
+		err.to_string(),
+		[
+			"Unresolved specifier \"./a.ts\" at inline.ts:2:24",
+			"Unresolved specifier \"../../b.ts\" at inline.ts:3:24",
+			"Unresolved specifier \"./lazy.ts\" at inline.ts:4:39",
+		]
+		.join("\n")
+	);
+
+	// the unresolved specifiers of the previous transform are cleared
+	let resolver = Rc::new(RefCell::new(Resolver::new(
+		"/app.ts",
+		ImportHashMap::default(),
+		false,
+		vec![],
+		None,
+	)));
+	let options = EmitOptions {
+		require_all_resolved: true,
+		..Default::default()
+	};
+	let module = SWC::parse("/app.ts", source, None).expect("could not parse module");
+	assert!(module.transform(resolver.clone(), &options).is_err());
+	let module =
+		SWC::parse("/app.ts", "import \"./a.ts\"", None).expect("could not parse module");
+	assert!(module.transform(resolver, &options).is_ok());
+}
+
+#[test]
+fn auto_accessor() {
+	let source = r#"
+      class C {
+        accessor x = 1
+        static accessor "y": number
+        accessor
+        z = 2
+      }
+    "#;
+	let (code, _) = st("/mod.ts", source, false);
+	assert!(code.contains("#x_accessor_storage = 1;"));
+	assert!(code.contains("get x() {\n        return this.#x_accessor_storage;\n    }"));
+	assert!(code.contains("set x(value) {\n        this.#x_accessor_storage = value;\n    }"));
+	assert!(code.contains("static #_1_accessor_storage;"));
+	assert!(code.contains("static get \"y\"() {"));
+	assert!(code.contains("static set \"y\"(value) {"));
+	assert!(code.contains("accessor;"));
+	assert!(code.contains("z = 2;"));
+
+	// the private auto-accessors have the private getter/setter pairs
+	let source = r#"
+      class P {
+        accessor #x = 1
+        static accessor #y
+        accessor x = 2
+        has(p) { return #x in p && this.#x === P.#y }
+      }
+    "#;
+	let (code, _) = st("/mod.ts", source, false);
+	assert!(code.contains("#x_private_accessor_storage = 1;"));
+	assert!(code.contains("get #x() {\n        return this.#x_private_accessor_storage;\n    }"));
+	assert!(code.contains("set #x(value) {\n        this.#x_private_accessor_storage = value;\n"));
+	assert!(code.contains("static #y_private_accessor_storage;"));
+	assert!(code.contains("static get #y() {"));
+	assert!(code.contains("#x_accessor_storage = 2;"));
+	assert!(code.contains("return #x in p && this.#x === P.#y;"));
+
+	// the `accessor` identifiers out of the class bodies are kept
+	let source = r#"
+      import { accessor as x } from "./x.ts"
+      for (accessor of xs) {}
+      class D extends mixin({ accessor }) {
+        m() { return accessor }
+        accessor y = 1
+      }
+      export { x }
+    "#;
+	let (code, _) = st("/mod.ts", source, false);
+	assert!(code.contains("import { accessor as x } from \"/x.ts\";"));
+	assert!(code.contains("for (accessor of xs){\n}"));
+	assert!(code.contains("mixin({\n    accessor\n})"));
+	assert!(code.contains("m() {\n        return accessor;\n    }"));
+	assert!(code.contains("get y() {"));
+
+	let source = r#"
+      class E {
+        @observable accessor v = 1
+      }
+    "#;
+	let emit = |decorators_version: DecoratorsVersion| {
+		let options = EmitOptions {
+			decorators_version,
+			..Default::default()
+		};
+		st_with("/mod.ts", source, &options).map(|(code, _)| code)
+	};
+	let code = emit(DecoratorsVersion::Legacy).unwrap();
+	assert!(code.contains("#v_accessor_storage = 1;"));
+	assert!(code.contains(
+		"_applyDecoratedDescriptor(_class.prototype, \"v\", [\n    observable\n]"
+	));
+	let err = emit(DecoratorsVersion::V2018_09).unwrap_err().to_string();
+	assert!(err.starts_with(
+		"the auto-accessors of the decorated classes are not supported by the 2018-09 decorators"
+	));
+	let source = "class F {\n  @observable accessor #v = 1\n}";
+	let err = st_with("/mod.ts", source, &EmitOptions::default()).err().unwrap().to_string();
+	assert!(err.starts_with(
+		"the decorated private auto-accessors are not supported by the legacy decorators"
+	));
+}
+
+#[test]
+fn dump_ast() {
+	let source = r#"
+      import { v } from "./v.ts"
+      export const ver: string = v
+    "#;
+	let module = SWC::parse("/mod.ts", source, None).expect("could not parse module");
+	let dump = module.dump_ast();
+	assert!(dump.contains("ImportDecl {"));
+	assert!(dump.contains("ExportDecl {"));
+	assert!(dump.contains("TsTypeAnn {"));
+	assert_eq!(dump, module.dump_ast());
+
+	let resolver = Rc::new(RefCell::new(Resolver::new(
+		"/mod.ts",
+		ImportHashMap::default(),
+		false,
+		vec![],
+		None,
+	)));
+	let options = EmitOptions {
+		dump_ast: true,
+		..Default::default()
+	};
+	module.transform(resolver.clone(), &options).unwrap();
+	let dumps = &resolver.borrow().ast_dumps;
+	assert_eq!(
+		dumps.iter().map(|(name, _)| name.as_str()).collect::<Vec<&str>>(),
+		vec![
+			"resolve",
+			"decorators",
+			"strip_ambient",
+			"strip",
+			"output_format",
+			"inject_helpers",
+			"output_wrapper",
+			"fixer",
+			"hygiene"
+		]
+	);
+	let (_, resolved) = &dumps[0];
+	assert!(resolved.contains("value: Atom('/v.ts'"));
+	let (_, stripped) = &dumps[3];
+	assert!(!stripped.contains("TsTypeAnn {"));
+}
+
+#[test]
+fn types_stub() {
+	let source = r#"
+      interface Props { name: string }
+      type Size = "sm" | "lg"
+      export interface Theme { color: string }
+      export type Mode = "light" | "dark"
+      export enum Align { Left, Right }
+      export const Button = (props: Props) => null
+      export default Button
+      export { Props, Size as ButtonSize }
+      export type { Config } from "./config.ts"
+      export { type Plugin, createPlugin } from "./plugin.ts"
+      export * from "./utils.ts"
+    "#;
+	let module = SWC::parse("/button.ts", source, None).expect("could not parse module");
+	let (names, types) = module.parse_export_names_with_types().unwrap();
+	assert_eq!(names, vec!["Align", "Button", "default", "createPlugin", "{./utils.ts}"]);
+	assert_eq!(types, vec!["Theme", "Mode", "Props", "ButtonSize", "Config", "Plugin"]);
+	// the names of `parse_export_names` are kept
+	assert_eq!(
+		module.parse_export_names().unwrap(),
+		vec![
+			"Button",
+			"default",
+			"Props",
+			"ButtonSize",
+			"Plugin",
+			"createPlugin",
+			"{./utils.ts}"
+		]
+	);
+	assert_eq!(
+		generate_types_stub("https://esm.sh/button.js", &names, &types),
+		[
+			"export * from \"https://esm.sh/button.js\";",
+			"export { Align, Button, default, createPlugin } from \"https://esm.sh/button.js\";",
+			"export type { Theme, Mode, Props, ButtonSize, Config, Plugin } from \"https://esm.sh/button.js\";",
+			"",
+		]
+		.join("\n")
+	);
+	assert_eq!(generate_types_stub("./empty.js", &[], &[]), "export {};\n");
+}
+
+#[test]
+fn facade() {
+	let names = vec![
+		ExportName::Star,
+		ExportName::Default,
+		ExportName::Named("useState".into()),
+		ExportName::Named("useEffect".into()),
+		ExportName::Named("useState".into()),
+	];
+	let facade = build_facade("https://esm.sh/react", &names);
+	assert_eq!(
+		facade,
+		concat!(
+			"export * from \"https://esm.sh/react\";\n",
+			"export { default, useState, useEffect } from \"https://esm.sh/react\";\n",
+		)
+	);
+	let module = SWC::parse("/facade.js", facade.as_str(), None).expect("could not parse facade");
+	let names = module.parse_export_names().unwrap();
+	assert_eq!(names, vec!["{https://esm.sh/react}", "default", "useState", "useEffect"]);
+	assert_eq!(build_facade("https://esm.sh/react", &[]), "export {};\n");
+}
+
+#[test]
+fn for_await_of() {
+	let source = r#"
+      export async function take(stream, n) {
+        const chunks = []
+        outer: for await (const { value } of stream) {
+          for (const c of value) {
+            if (!c) continue outer
+          }
+          chunks.push(value)
+          if (chunks.length === n) break
+        }
+        return chunks
+      }
+      export async function first(xs) {
+        for await (const x of xs) {
+          const x = 1
+          return x
+        }
+      }
+    "#;
+	let emit = |target: JscTarget| {
+		let options = EmitOptions {
+			target,
+			..Default::default()
+		};
+		st_target("/take.js", source, &options)
+	};
+	let code = emit(JscTarget::Es2018);
+	assert!(code.contains("outer: for await (const { value  } of stream){"));
+	for (target, op) in [(JscTarget::Es2017, "await"), (JscTarget::Es2015, "yield")] {
+		let code = emit(target);
+		assert!(!code.contains("for await"));
+		assert!(code.contains("function _asyncIterator(iterable) {"));
+		assert!(code.contains(&format!(
+			concat!(
+				"outer: for(var _iterator = _asyncIterator(stream), _step; ",
+				"_iteratorAbruptCompletion = !(_step = {} _iterator.next()).done; ",
+				"_iteratorAbruptCompletion = false){{",
+			),
+			op
+		)));
+		assert!(code.contains("const { value  } = _step.value;"));
+		assert!(code.contains("if (!c) continue outer;"));
+		// the iterator is closed on break
+		assert!(code.contains(concat!(
+			"if (_iteratorAbruptCompletion && _iterator.return != null) {\n",
+		)));
+		assert!(code.contains(&format!("{} _iterator.return();", op)));
+		assert!(code.contains("if (_didIteratorError) {\n"));
+		// the body is a nested block of the loop binding
+		let code = code.split_whitespace().collect::<Vec<&str>>().join(" ");
+		assert!(code.contains("const x = _step.value; { const x = 1; return x; }"));
+	}
+}
+
+#[test]
+fn sorted_export_names() {
+	let source = r#"
+      export const version = "1.0.0"
+      export * from "https://esm.sh/react"
+      export default function render() {}
+      export { useState, Component } from "https://esm.sh/react"
+      export class App {}
+      export * from "./a.js"
+      export const _private = 1, Zoo = 2, apple = 3
+    "#;
+	let module = SWC::parse("/mod.js", source, None).expect("could not parse module");
+	assert_eq!(
+		module.parse_export_names().unwrap(),
+		vec![
+			"version",
+			"{https://esm.sh/react}",
+			"default",
+			"useState",
+			"Component",
+			"App",
+			"{./a.js}",
+			"_private",
+			"Zoo",
+			"apple"
+		]
+	);
+	assert_eq!(
+		module.parse_sorted_export_names().unwrap(),
+		vec![
+			"_private",
+			"App",
+			"apple",
+			"Component",
+			"default",
+			"useState",
+			"version",
+			"Zoo",
+			"{./a.js}",
+			"{https://esm.sh/react}"
+		]
+	);
+}
+
+#[test]
+fn reexport_default_as() {
+	let source = r#"
+      export { default as Thing, helper } from "./m.ts"
+      export { default } from "./n.ts"
+      export { a as b } from "./m.ts"
+      export type { default as T } from "./types.ts"
+    "#;
+	let module = SWC::parse("/mod.ts", source, None).expect("could not parse module");
+	assert_eq!(module.parse_export_names().unwrap(), vec!["Thing", "helper", "default", "b"]);
+	let reexports = module.parse_reexports().unwrap();
+	let reexport = |name: &str, source: &str, source_default: bool| ExportName::Reexport {
+		name: name.into(),
+		source: source.into(),
+		source_default,
+	};
+	assert_eq!(
+		reexports,
+		vec![
+			reexport("Thing", "./m.ts", true),
+			reexport("helper", "./m.ts", false),
+			reexport("default", "./n.ts", true),
+		]
+	);
+	assert_eq!(
+		serde_json::to_string(&reexports[0]).unwrap(),
+		r#"{"reexport":{"name":"Thing","source":"./m.ts","sourceDefault":true}}"#
+	);
+
+	let mut names = vec![ExportName::Named("version".into())];
+	names.extend(reexports);
+	assert_eq!(
+		build_facade("https://esm.sh/lib", &names),
+		concat!(
+			"export { version } from \"https://esm.sh/lib\";\n",
+			"export { default as Thing, helper } from \"./m.ts\";\n",
+			"export { default as default } from \"./n.ts\";\n",
+		)
+	);
+}
+
+#[test]
+fn parse_export_names() {
+	let source = r#"
+      export const name = "alephjs"
+      export const version = "1.0.1"
+      const start = () => {}
+      export default start
+      export const { build } = { build: () => {} }
+      export function dev() {}
+      export class Server {}
+      export const { a: { a1, a2 }, 'b': [ b1, b2 ], c, ...rest } = { a: { a1: 0, a2: 0 }, b: [ 0, 0 ], c: 0, d: 0 }
+      export const [ d, e, ...{f, g, rest3} ] = [0, 0, {f:0,g:0,h:0}]
+      let i
+      export const j = i = [0, 0]
+      export { exists, existsSync } from "https://deno.land/std/fs/exists.ts"
+      export * as DenoStdServer from "https://deno.land/std/http/sever.ts"
+      export * from "https://deno.land/std/http/sever.ts"
+    "#;
+	let module = SWC::parse("/app.ts", source, None).expect("could not parse module");
+	assert_eq!(
+		module.parse_export_names().unwrap(),
+		vec![
+			"name",
+			"version",
+			"default",
+			"build",
+			"dev",
+			"Server",
+			"a1",
+			"a2",
+			"b1",
+			"b2",
+			"c",
+			"rest",
+			"d",
+			"e",
+			"f",
+			"g",
+			"rest3",
+			"j",
+			"exists",
+			"existsSync",
+			"DenoStdServer",
+			"{https://deno.land/std/http/sever.ts}",
+		]
+		.into_iter()
+		.map(|s| s.to_owned())
+		.collect::<Vec<String>>()
+	)
+}
+
+#[test]
+fn worker_options() {
+	let options = serde_json::json!({
+		"swcOptions": { "jsxFactory": "h" },
+		"bundleMode": true,
+		"target": "es2017",
+		"cssTags": ["tw"],
+		"keepComments": ["KEEP"],
+	});
+	let options = serde_json::from_value::<crate::Options>(options).unwrap();
+	assert!(options.bundle_mode);
+	let emit_options = options.emit_options();
+	assert_eq!(emit_options.jsx_factory, "h");
+	assert_eq!(emit_options.jsx_fragment_factory, "React.Fragment");
+	assert_eq!(emit_options.target, JscTarget::Es2017);
+	assert_eq!(emit_options.css_tags, vec!["tw".to_owned()]);
+	// the missing options are the defaults of the emit options
+	assert!(emit_options.esmodule_interop);
+	assert_eq!(emit_options.translation_functions, vec!["t".to_owned(), "i18n.t".into()]);
+	let module = SWC::parse("/mod.js", "// KEEP\n// drop\nexport const a = 1", None).unwrap();
+	let (code, _) = module.transform(new_resolver("/mod.js", false), &emit_options).unwrap();
+	assert_eq!(code, "// KEEP\nexport const a = 1;\n");
+
+	for options in [
+		serde_json::json!({ "gzipSizes": true }),
+		serde_json::json!({ "jsxFactory": "h" }),
+		serde_json::json!({ "commentFilter": null }),
+	] {
+		let err = serde_json::from_value::<crate::Options>(options).err();
+		assert!(err.unwrap().to_string().starts_with("unknown field"));
+	}
+}
+
+#[test]
+fn lex_keywords() {
+	let syntax = Syntax::Es(get_es_config(false));
+	let sources = [
+		"const withdraw = 1\n// with care, through the accessors\nexport { withdraw }",
+		"obj.with(x)\nconst using = 1\nconst deferred = defer(x)",
+		"class A { accessor\n= 1 }",
+	];
+	for source in sources {
+		assert!(lex(source, syntax, &[DEFER, ACCESSOR, USING, WITH]).is_empty());
+	}
+	let sources = [
+		"import a from \"./a.css\" with { type: \"css\" }",
+		"import defer * as ns from \"./ns.js\"",
+		"class A { accessor #x = 1 }",
+		"{ await using\tres = open() }",
+	];
+	for source in sources {
+		assert!(!lex(source, syntax, &[DEFER, ACCESSOR, USING, WITH]).is_empty());
+	}
+}
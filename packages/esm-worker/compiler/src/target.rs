@@ -0,0 +1,1054 @@
+use crate::ast_utils::{assign, block, call, expr_stmt, member, var_decl};
+use serde::Deserialize;
+use std::collections::HashSet;
+use swc_atoms::JsWord;
+use swc_common::{chain, Mark, Span, DUMMY_SP};
+use swc_ecma_ast::*;
+use swc_ecma_utils::{
+	alias_ident_for, collect_decls, prepend, private_ident, quote_ident, quote_str, Id, StmtLike,
+};
+use swc_ecma_visit::{noop_fold_type, Fold, FoldWith, Node, Visit, VisitWith};
+use swc_ecmascript::parser::JscTarget;
+use swc_ecmascript::transforms::{
+	compat::{es2015::regenerator, es2016, es2017, es2018, es2019, es2020, es2022},
+	helpers::HELPERS,
+	pass::Optional,
+};
+
+/// The runtime of the generators lowered from the async functions, for the targets older than
+/// `es2017`.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum GeneratorRuntime {
+	/// keep the native generators, for the engines that support the generators but not the async
+	/// functions.
+	#[default]
+	Native,
+	/// lower the generators with the `regeneratorRuntime` provided globally, like by a polyfill
+	/// script.
+	Global,
+	/// lower the generators with the runtime imported from the specifier, like
+	/// `https://esm.sh/regenerator-runtime`, the specifier is resolved by the resolver.
+	Import(String),
+	/// lower the generators with the runtime code inlined in the module, like the `runtime.js` of
+	/// the `regenerator-runtime` package, the code is evaluated as a CommonJS module.
+	Inline(String),
+}
+
+/// Find the syntax that can't be lowered for the target, returns the error messages with the spans.
+/// The numeric separators(`1_000`) are always removed by the code generator, since the number
+/// literals are printed by their values.
+pub fn find_unsupported_syntax(module: &Module, target: JscTarget) -> Vec<(String, Span)> {
+	let mut finder = UnsupportedSyntaxFinder {
+		target,
+		errors: vec![],
+	};
+	module.visit_with(&Invalid { span: Span::default() } as _, &mut finder);
+	finder.errors
+}
+
+/// Lower the class members of es2022, the private members, the class properties and the static
+/// blocks, for the targets older than `es2022`.
+fn class_fields_fold(target: JscTarget) -> impl Fold {
+	chain!(
+		Optional::new(StaticBlocksFold {}, target < JscTarget::Es2022),
+		Optional::new(
+			es2022::es2022(es2022::Config { loose: false }),
+			target < JscTarget::Es2022
+		)
+	)
+}
+
+/// Lower the syntax newer than the target, like the private class members and the static blocks
+/// for the targets older than `es2022`, the logical assignments(`a ||= b` => `a || (a = b)`) for
+/// the targets older than `es2021`, the optional catch bindings(`catch {}` => `catch (_e) {}`)
+/// for the targets older than `es2019`, the `globalThis` for the targets older than `es2020`, the
+/// object rest/spread(`{ ...a }` => `_objectSpread({}, a)`) and the `for await` loops for the
+/// targets older than `es2018`, the async functions for the targets older than `es2017` and the
+/// exponentiation(`a ** b` => `Math.pow(a, b)`) for the targets older than `es2016`. The helpers
+/// are injected in the module or referenced from the shared helpers. The async functions are
+/// lowered to the generators that are lowered with the `runtime` if it's not native, the `Import`
+/// specifier must be resolved and the `Inline` code is passed parsed as the `inlined_runtime`. The
+/// other ES2015 syntax is kept for the `es5` and `es3` targets.
+pub fn downlevel_fold(
+	target: JscTarget,
+	runtime: &GeneratorRuntime,
+	inlined_runtime: Option<Vec<Stmt>>,
+	top_level_mark: Mark,
+) -> impl Fold {
+	let import_path = match runtime {
+		GeneratorRuntime::Import(specifier) => Some(specifier.as_str().into()),
+		_ => None,
+	};
+	let lower_generators = target < JscTarget::Es2017 && *runtime != GeneratorRuntime::Native;
+	chain!(
+		class_fields_fold(target),
+		Optional::new(
+			LogicalAssignmentsFold::new(LogicalAssignments {}),
+			target < JscTarget::Es2021
+		),
+		Optional::new(
+			ExponentiationAssignFold::new(ExponentiationAssign {}),
+			target < JscTarget::Es2016
+		),
+		Optional::new(GlobalThisFold { used: None }, target < JscTarget::Es2020),
+		Optional::new(es2020::es2020(), target < JscTarget::Es2020),
+		Optional::new(OptionalCatchBindingFold {}, target < JscTarget::Es2019),
+		Optional::new(es2019::es2019(), target < JscTarget::Es2019),
+		Optional::new(AsyncIterationFold {}, target < JscTarget::Es2018),
+		Optional::new(es2018::es2018(), target < JscTarget::Es2018),
+		Optional::new(es2017::es2017(), target < JscTarget::Es2017),
+		Optional::new(
+			regenerator::regenerator(regenerator::Config { import_path }, top_level_mark),
+			lower_generators && matches!(runtime, GeneratorRuntime::Import(_))
+		),
+		Optional::new(
+			RuntimeImportFold {
+				regenerator: regenerator::regenerator(Default::default(), top_level_mark),
+				mark: Mark::fresh(Mark::root()),
+				inlined_runtime,
+			},
+			lower_generators
+				&& matches!(runtime, GeneratorRuntime::Global | GeneratorRuntime::Inline(_))
+		),
+		Optional::new(es2016::es2016(), target < JscTarget::Es2016)
+	)
+}
+
+/// get the name of the target, like `es2020`.
+pub fn target_name(target: JscTarget) -> &'static str {
+	match target {
+		JscTarget::Es3 => "es3",
+		JscTarget::Es5 => "es5",
+		JscTarget::Es2015 => "es2015",
+		JscTarget::Es2016 => "es2016",
+		JscTarget::Es2017 => "es2017",
+		JscTarget::Es2018 => "es2018",
+		JscTarget::Es2019 => "es2019",
+		JscTarget::Es2020 => "es2020",
+		JscTarget::Es2021 => "es2021",
+		JscTarget::Es2022 => "es2022",
+	}
+}
+
+/// lower the static blocks to the static private properties initialized by the arrow functions,
+/// before the `es2022` pass lowering the class properties, the initialization order with the
+/// static properties is kept:
+/// ```js
+/// class A { static x = 1; static { init(this) } }
+/// // =>
+/// class A { static x = 1; static #_ = (() => { init(this) })() }
+/// ```
+/// The `es2022` pass of swc lowers the class properties before the static blocks and panics.
+struct StaticBlocksFold {}
+
+impl Fold for StaticBlocksFold {
+	noop_fold_type!();
+
+	fn fold_class(&mut self, class: Class) -> Class {
+		let mut class = class.fold_children_with(self);
+		let mut private_names: HashSet<JsWord> = class
+			.body
+			.iter()
+			.filter_map(|member| match member {
+				ClassMember::PrivateProp(PrivateProp { key, .. })
+				| ClassMember::PrivateMethod(PrivateMethod { key, .. }) => Some(key.id.sym.clone()),
+				_ => None,
+			})
+			.collect();
+		class.body = class
+			.body
+			.into_iter()
+			.map(|member| match member {
+				ClassMember::StaticBlock(StaticBlock { span, body }) => {
+					let mut name: JsWord = "_".into();
+					let mut i = 1;
+					while private_names.contains(&name) {
+						name = format!("_{}", i).into();
+						i += 1;
+					}
+					private_names.insert(name.clone());
+					ClassMember::PrivateProp(PrivateProp {
+						span,
+						key: PrivateName {
+							span: DUMMY_SP,
+							id: Ident::new(name, DUMMY_SP),
+						},
+						value: Some(Box::new(Expr::Call(CallExpr {
+							span: DUMMY_SP,
+							callee: ExprOrSuper::Expr(Box::new(Expr::Arrow(ArrowExpr {
+								span: DUMMY_SP,
+								params: vec![],
+								body: BlockStmtOrExpr::BlockStmt(body),
+								is_async: false,
+								is_generator: false,
+								type_params: None,
+								return_type: None,
+							}))),
+							args: vec![],
+							type_args: None,
+						}))),
+						type_ann: None,
+						is_static: true,
+						decorators: vec![],
+						computed: false,
+						accessibility: None,
+						is_abstract: false,
+						is_optional: false,
+						is_override: false,
+						readonly: false,
+						definite: false,
+					})
+				}
+				_ => member,
+			})
+			.collect();
+		class
+	}
+}
+
+/// add the dummy bindings to the optional catch clauses, the name doesn't collide with the names
+/// used in the catch body, the `es2019` pass uses the name `e` that may be shadowed by the body.
+struct OptionalCatchBindingFold {}
+
+impl Fold for OptionalCatchBindingFold {
+	noop_fold_type!();
+
+	// match: catch {} => catch (_e) {}
+	fn fold_catch_clause(&mut self, clause: CatchClause) -> CatchClause {
+		let mut clause = clause.fold_children_with(self);
+		if clause.param.is_none() {
+			let mut collector = IdentCollector {
+				names: HashSet::new(),
+			};
+			clause.body.visit_with(&Invalid { span: DUMMY_SP } as _, &mut collector);
+			let mut name: JsWord = "_e".into();
+			let mut i = 1;
+			while collector.names.contains(&name) {
+				name = format!("_e{}", i).into();
+				i += 1;
+			}
+			clause.param = Some(Pat::Ident(Ident::new(name, DUMMY_SP).into()));
+		}
+		clause
+	}
+}
+
+/// lower the logical assignments(`&&=`, `||=` and `??=`), the assignment only happens if the
+/// operator short-circuits to the right side:
+/// ```js
+/// (_ref = get())[_key = key()] ?? (_ref[_key] = y)
+/// ```
+/// The `es2021` pass of swc misses the `&&=` and evaluates the computed keys twice.
+type LogicalAssignmentsFold = SplitAssignFold<LogicalAssignments>;
+
+/// lower the exponentiation assignments(`**=`) for the `es2016` pass, which evaluates the object
+/// and the computed key of the member target twice:
+/// ```js
+/// (_ref = get())[_key = key()] = _ref[_key] ** y
+/// ```
+type ExponentiationAssignFold = SplitAssignFold<ExponentiationAssign>;
+
+/// The lowering of the assignment operators, the target is split into the expression tested or
+/// read first and the assigned reference.
+trait AssignLowering {
+	fn lowers(&self, op: AssignOp) -> bool;
+	fn lower(
+		&self,
+		span: Span,
+		op: AssignOp,
+		test: Box<Expr>,
+		target: Box<Expr>,
+		right: Box<Expr>,
+	) -> Expr;
+}
+
+struct LogicalAssignments {}
+
+impl AssignLowering for LogicalAssignments {
+	fn lowers(&self, op: AssignOp) -> bool {
+		matches!(op, AssignOp::AndAssign | AssignOp::OrAssign | AssignOp::NullishAssign)
+	}
+
+	// match: a ||= b => a || (a = b)
+	fn lower(
+		&self,
+		span: Span,
+		op: AssignOp,
+		test: Box<Expr>,
+		target: Box<Expr>,
+		right: Box<Expr>,
+	) -> Expr {
+		Expr::Bin(BinExpr {
+			span,
+			op: match op {
+				AssignOp::AndAssign => BinaryOp::LogicalAnd,
+				AssignOp::OrAssign => BinaryOp::LogicalOr,
+				_ => BinaryOp::NullishCoalescing,
+			},
+			left: test,
+			right: Box::new(Expr::Assign(AssignExpr {
+				span: DUMMY_SP,
+				op: AssignOp::Assign,
+				left: PatOrExpr::Expr(target),
+				right,
+			})),
+		})
+	}
+}
+
+struct ExponentiationAssign {}
+
+impl AssignLowering for ExponentiationAssign {
+	fn lowers(&self, op: AssignOp) -> bool {
+		op == AssignOp::ExpAssign
+	}
+
+	// match: a **= b => a = a ** b
+	fn lower(
+		&self,
+		span: Span,
+		_: AssignOp,
+		test: Box<Expr>,
+		target: Box<Expr>,
+		right: Box<Expr>,
+	) -> Expr {
+		Expr::Assign(AssignExpr {
+			span,
+			op: AssignOp::Assign,
+			left: PatOrExpr::Expr(test),
+			right: Box::new(Expr::Bin(BinExpr {
+				span: DUMMY_SP,
+				op: BinaryOp::Exp,
+				left: target,
+				right,
+			})),
+		})
+	}
+}
+
+/// split the targets of the assignments lowered by `L`, the object and the computed key of the
+/// member target are evaluated once with the temporary variables declared in the enclosing
+/// function.
+struct SplitAssignFold<L: AssignLowering> {
+	// the temporary variables of the current function
+	vars: Vec<VarDeclarator>,
+	lowering: L,
+}
+
+impl<L: AssignLowering> SplitAssignFold<L> {
+	fn new(lowering: L) -> Self {
+		SplitAssignFold {
+			vars: vec![],
+			lowering,
+		}
+	}
+
+	/// alias the expression with a temporary variable if it's not a plain reference, returns the
+	/// expression evaluated first and the later reference.
+	fn alias(&mut self, expr: Box<Expr>, default: &str) -> (Box<Expr>, Box<Expr>) {
+		match expr.as_ref() {
+			Expr::Ident(_) | Expr::This(_) | Expr::Lit(Lit::Str(_)) | Expr::Lit(Lit::Num(_)) => {
+				(expr.clone(), expr)
+			}
+			_ => {
+				let alias = alias_ident_for(&expr, default);
+				self.vars.push(VarDeclarator {
+					span: DUMMY_SP,
+					name: Pat::Ident(alias.clone().into()),
+					init: None,
+					definite: false,
+				});
+				let assign = Box::new(Expr::Assign(AssignExpr {
+					span: DUMMY_SP,
+					op: AssignOp::Assign,
+					left: PatOrExpr::Pat(Box::new(Pat::Ident(alias.clone().into()))),
+					right: expr,
+				}));
+				(assign, Box::new(Expr::Ident(alias)))
+			}
+		}
+	}
+
+	/// split the assignment target into the expression tested first and the assigned reference.
+	fn split_target(&mut self, target: Expr) -> Result<(Box<Expr>, Box<Expr>), Expr> {
+		match target {
+			Expr::Ident(_) => Ok((Box::new(target.clone()), Box::new(target))),
+			Expr::Member(MemberExpr {
+				span,
+				obj,
+				prop,
+				computed,
+			}) => {
+				let (test_obj, obj) = match obj {
+					ExprOrSuper::Expr(obj) => {
+						let (test_obj, obj) = self.alias(obj, "_ref");
+						(ExprOrSuper::Expr(test_obj), ExprOrSuper::Expr(obj))
+					}
+					ExprOrSuper::Super(_) => (obj.clone(), obj),
+				};
+				let (test_prop, prop) = if computed {
+					self.alias(prop, "_key")
+				} else {
+					(prop.clone(), prop)
+				};
+				Ok((
+					Box::new(Expr::Member(MemberExpr {
+						span,
+						obj: test_obj,
+						prop: test_prop,
+						computed,
+					})),
+					Box::new(Expr::Member(MemberExpr {
+						span,
+						obj,
+						prop,
+						computed,
+					})),
+				))
+			}
+			Expr::Paren(ParenExpr { expr, .. }) => self.split_target(*expr),
+			_ => Err(target),
+		}
+	}
+
+	/// fold the body of a function with its own temporary variables.
+	fn fold_scope(&mut self, body: Option<BlockStmt>) -> Option<BlockStmt> {
+		let vars = std::mem::take(&mut self.vars);
+		let mut body = body.fold_with(self);
+		match body.as_mut() {
+			Some(body) => self.declare_vars(&mut body.stmts, vars),
+			None => self.vars = vars,
+		}
+		body
+	}
+
+	/// fold the initializer of a class property with its own temporary variables, they are
+	/// declared in an arrow function evaluated with the `this` of the instance:
+	/// `x = (() => { var _ref; return (_ref = get()).x || (_ref.x = y) })()`.
+	fn fold_initializer(&mut self, value: Box<Expr>) -> Box<Expr> {
+		let vars = std::mem::take(&mut self.vars);
+		let value = value.fold_with(self);
+		if self.vars.is_empty() {
+			self.vars = vars;
+			return value;
+		}
+		let mut stmts = vec![Stmt::Return(ReturnStmt {
+			span: DUMMY_SP,
+			arg: Some(value),
+		})];
+		self.declare_vars(&mut stmts, vars);
+		let arrow = Expr::Arrow(ArrowExpr {
+			span: DUMMY_SP,
+			params: vec![],
+			body: BlockStmtOrExpr::BlockStmt(block(stmts)),
+			is_async: false,
+			is_generator: false,
+			type_params: None,
+			return_type: None,
+		});
+		Box::new(call(
+			Expr::Paren(ParenExpr {
+				span: DUMMY_SP,
+				expr: Box::new(arrow),
+			}),
+			vec![],
+		))
+	}
+
+	/// declare the temporary variables used in the statements.
+	fn declare_vars<T: StmtLike>(&mut self, stmts: &mut Vec<T>, vars: Vec<VarDeclarator>) {
+		let decls = std::mem::replace(&mut self.vars, vars);
+		if !decls.is_empty() {
+			prepend(
+				stmts,
+				T::from_stmt(Stmt::Decl(Decl::Var(VarDecl {
+					span: DUMMY_SP,
+					kind: VarDeclKind::Var,
+					declare: false,
+					decls,
+				}))),
+			);
+		}
+	}
+}
+
+impl<L: AssignLowering> Fold for SplitAssignFold<L> {
+	noop_fold_type!();
+
+	fn fold_module(&mut self, module: Module) -> Module {
+		let mut module = module.fold_children_with(self);
+		self.declare_vars(&mut module.body, vec![]);
+		module
+	}
+
+	// the temporary variables of the parameters are declared in the enclosing scope, the `var`
+	// declarations of the body aren't visible to the default values
+	fn fold_function(&mut self, function: Function) -> Function {
+		let params = function.params.fold_with(self);
+		let decorators = function.decorators.fold_with(self);
+		let body = self.fold_scope(function.body);
+		Function {
+			params,
+			decorators,
+			body,
+			..function
+		}
+	}
+
+	fn fold_constructor(&mut self, constructor: Constructor) -> Constructor {
+		let params = constructor.params.fold_with(self);
+		let body = self.fold_scope(constructor.body);
+		Constructor {
+			params,
+			body,
+			..constructor
+		}
+	}
+
+	// match: { get x() { return a ||= b } }
+	fn fold_getter_prop(&mut self, prop: GetterProp) -> GetterProp {
+		let key = prop.key.fold_with(self);
+		let body = self.fold_scope(prop.body);
+		GetterProp { key, body, ..prop }
+	}
+
+	// match: { set x(v = a ||= b) { a ||= v } }
+	fn fold_setter_prop(&mut self, prop: SetterProp) -> SetterProp {
+		let key = prop.key.fold_with(self);
+		let param = prop.param.fold_with(self);
+		let body = self.fold_scope(prop.body);
+		SetterProp {
+			key,
+			param,
+			body,
+			..prop
+		}
+	}
+
+	// match: class A { static { a ||= b } }
+	fn fold_static_block(&mut self, block: StaticBlock) -> StaticBlock {
+		let vars = std::mem::take(&mut self.vars);
+		let mut body = block.body.fold_with(self);
+		self.declare_vars(&mut body.stmts, vars);
+		StaticBlock { body, ..block }
+	}
+
+	// match: class A { x = a ||= b }
+	fn fold_class_prop(&mut self, prop: ClassProp) -> ClassProp {
+		let key = prop.key.fold_with(self);
+		let value = prop.value.map(|value| self.fold_initializer(value));
+		ClassProp { key, value, ..prop }
+	}
+
+	// match: class A { #x = a ||= b }
+	fn fold_private_prop(&mut self, prop: PrivateProp) -> PrivateProp {
+		let value = prop.value.map(|value| self.fold_initializer(value));
+		PrivateProp { value, ..prop }
+	}
+
+	// match: () => a ||= b => () => { var _ref; return a || (a = b) }
+	fn fold_arrow_expr(&mut self, arrow: ArrowExpr) -> ArrowExpr {
+		let params = arrow.params.fold_with(self);
+		let vars = std::mem::take(&mut self.vars);
+		let body = arrow.body.fold_with(self);
+		if self.vars.is_empty() {
+			self.vars = vars;
+			return ArrowExpr {
+				params,
+				body,
+				..arrow
+			};
+		}
+		let mut stmts = match body {
+			BlockStmtOrExpr::BlockStmt(block) => block.stmts,
+			BlockStmtOrExpr::Expr(expr) => vec![Stmt::Return(ReturnStmt {
+				span: DUMMY_SP,
+				arg: Some(expr),
+			})],
+		};
+		self.declare_vars(&mut stmts, vars);
+		ArrowExpr {
+			params,
+			body: BlockStmtOrExpr::BlockStmt(block(stmts)),
+			..arrow
+		}
+	}
+
+	fn fold_expr(&mut self, expr: Expr) -> Expr {
+		let expr = expr.fold_children_with(self);
+		let (span, op, left, right) = match expr {
+			Expr::Assign(AssignExpr {
+				span,
+				op,
+				left,
+				right,
+			}) if self.lowering.lowers(op) => (span, op, left, right),
+			_ => return expr,
+		};
+		let target = match left {
+			PatOrExpr::Expr(expr) => *expr,
+			PatOrExpr::Pat(pat) => match *pat {
+				Pat::Ident(BindingIdent { id, .. }) => Expr::Ident(id),
+				Pat::Expr(expr) => *expr,
+				pat => {
+					return Expr::Assign(AssignExpr {
+						span,
+						op,
+						left: PatOrExpr::Pat(Box::new(pat)),
+						right,
+					})
+				}
+			},
+		};
+		let (test, target) = match self.split_target(target) {
+			Ok(split) => split,
+			Err(target) => {
+				return Expr::Assign(AssignExpr {
+					span,
+					op,
+					left: PatOrExpr::Expr(Box::new(target)),
+					right,
+				})
+			}
+		};
+		self.lowering.lower(span, op, test, target, right)
+	}
+}
+
+/// replace the `globalThis` with a hoisted helper for the engines without it:
+/// ```js
+/// var _globalThis = typeof globalThis !== "undefined" ? globalThis : typeof self !== "undefined"
+///   ? self : typeof window !== "undefined" ? window : typeof global !== "undefined" ? global
+///   : Function("return this")();
+/// ```
+/// The module declaring its own `globalThis` binding is kept.
+struct GlobalThisFold {
+	// the helper binding, created with the first `globalThis` reference
+	used: Option<Ident>,
+}
+
+impl Fold for GlobalThisFold {
+	noop_fold_type!();
+
+	fn fold_module(&mut self, module: Module) -> Module {
+		let decls = collect_decls::<Id, _>(&module);
+		if decls.iter().any(|(sym, _)| sym.as_ref() == "globalThis") {
+			return module;
+		}
+		let mut module = module.fold_children_with(self);
+		if let Some(helper) = self.used.take() {
+			let init = ["global", "window", "self", "globalThis"].iter().fold(
+				// match: Function("return this")()
+				Expr::Call(CallExpr {
+					span: DUMMY_SP,
+					callee: ExprOrSuper::Expr(Box::new(Expr::Call(CallExpr {
+						span: DUMMY_SP,
+						callee: ExprOrSuper::Expr(Box::new(Expr::Ident(quote_ident!("Function")))),
+						args: vec![ExprOrSpread {
+							spread: None,
+							expr: Box::new(Expr::Lit(Lit::Str(quote_str!("return this")))),
+						}],
+						type_args: None,
+					}))),
+					args: vec![],
+					type_args: None,
+				}),
+				// match: typeof self !== "undefined" ? self : alt
+				|alt, name| {
+					Expr::Cond(CondExpr {
+						span: DUMMY_SP,
+						test: Box::new(Expr::Bin(BinExpr {
+							span: DUMMY_SP,
+							op: BinaryOp::NotEqEq,
+							left: Box::new(Expr::Unary(UnaryExpr {
+								span: DUMMY_SP,
+								op: UnaryOp::TypeOf,
+								arg: Box::new(Expr::Ident(quote_ident!(*name))),
+							})),
+							right: Box::new(Expr::Lit(Lit::Str(quote_str!("undefined")))),
+						})),
+						cons: Box::new(Expr::Ident(quote_ident!(*name))),
+						alt: Box::new(alt),
+					})
+				},
+			);
+			prepend(
+				&mut module.body,
+				ModuleItem::Stmt(Stmt::Decl(Decl::Var(VarDecl {
+					span: DUMMY_SP,
+					kind: VarDeclKind::Var,
+					declare: false,
+					decls: vec![VarDeclarator {
+						span: DUMMY_SP,
+						name: Pat::Ident(helper.into()),
+						init: Some(Box::new(init)),
+						definite: false,
+					}],
+				}))),
+			);
+		}
+		module
+	}
+
+	fn fold_expr(&mut self, expr: Expr) -> Expr {
+		match expr {
+			Expr::Ident(id) if id.sym.as_ref() == "globalThis" => {
+				Expr::Ident(self.used.get_or_insert_with(|| private_ident!("_globalThis")).clone())
+			}
+			_ => expr.fold_children_with(self),
+		}
+	}
+
+	// match: { globalThis }
+	fn fold_prop(&mut self, prop: Prop) -> Prop {
+		match prop {
+			Prop::Shorthand(key) if key.sym.as_ref() == "globalThis" => Prop::KeyValue(KeyValueProp {
+				value: Box::new(self.fold_expr(Expr::Ident(key.clone()))),
+				key: PropName::Ident(key),
+			}),
+			_ => prop.fold_children_with(self),
+		}
+	}
+
+	fn fold_member_expr(&mut self, mut expr: MemberExpr) -> MemberExpr {
+		expr.obj = expr.obj.fold_with(self);
+		if expr.computed {
+			expr.prop = expr.prop.fold_with(self);
+		}
+		expr
+	}
+}
+
+/// lower the `for await` loops to the `for` loops awaiting the steps of the async iterator, the
+/// iterator is closed if the loop exits early by `break`, `return` or `throw`:
+/// ```js
+/// var _iteratorAbruptCompletion = false, _didIteratorError = false, _iteratorError;
+/// try {
+///   for (var _iterator = _asyncIterator(y), _step;
+///     _iteratorAbruptCompletion = !(_step = await _iterator.next()).done;
+///     _iteratorAbruptCompletion = false) { const x = _step.value; ... }
+/// } catch (err) { _didIteratorError = true; _iteratorError = err; } finally {
+///   try {
+///     if (_iteratorAbruptCompletion && _iterator.return != null) await _iterator.return();
+///   } finally { if (_didIteratorError) throw _iteratorError; }
+/// }
+/// ```
+/// The awaits are lowered with the async functions for the targets older than `es2017`. The
+/// `es2017` pass of swc lowers the loops with the async functions only, and closes the iterator
+/// by calling `return` of the caught error instead of the iterator. The label of the loop is kept
+/// on the `for` loop, for the labeled `break` and `continue`.
+struct AsyncIterationFold {}
+
+impl Fold for AsyncIterationFold {
+	noop_fold_type!();
+
+	fn fold_stmt(&mut self, stmt: Stmt) -> Stmt {
+		// match: loop: for await (const x of y)
+		let (label, stmt) = match stmt {
+			Stmt::Labeled(LabeledStmt { label, body, .. }) if is_for_await(&body) => {
+				(Some(label), *body)
+			}
+			stmt => (None, stmt),
+		};
+		match stmt.fold_children_with(self) {
+			Stmt::ForOf(for_of) if for_of.await_token.is_some() => lower_for_await(for_of, label),
+			stmt => stmt,
+		}
+	}
+}
+
+fn is_for_await(stmt: &Stmt) -> bool {
+	matches!(stmt, Stmt::ForOf(ForOfStmt { await_token: Some(_), .. }))
+}
+
+fn lower_for_await(for_of: ForOfStmt, label: Option<Ident>) -> Stmt {
+	let iterator = private_ident!("_iterator");
+	let step = private_ident!("_step");
+	let abrupt = private_ident!("_iteratorAbruptCompletion");
+	let did_error = private_ident!("_didIteratorError");
+	let error = private_ident!("_iteratorError");
+	let err = private_ident!("err");
+
+	// match: const x = _step.value, x = _step.value
+	let value = Box::new(member(Expr::Ident(step.clone()), "value"));
+	let mut body = vec![match for_of.left {
+		VarDeclOrPat::VarDecl(VarDecl { kind, decls, .. }) => Stmt::Decl(Decl::Var(VarDecl {
+			span: DUMMY_SP,
+			kind,
+			declare: false,
+			decls: decls
+				.into_iter()
+				.take(1)
+				.map(|decl| VarDeclarator {
+					init: Some(value.clone()),
+					..decl
+				})
+				.collect(),
+		})),
+		VarDeclOrPat::Pat(pat) => expr_stmt(assign(PatOrExpr::Pat(Box::new(pat)), *value)),
+	}];
+	// the body is kept as a nested block, it may redeclare the binding of the loop:
+	// `for await (const x of xs) { const x = 1 }`
+	body.push(*for_of.body);
+
+	// match: _iteratorAbruptCompletion = !(_step = await _iterator.next()).done
+	let next = Expr::Await(AwaitExpr {
+		span: DUMMY_SP,
+		arg: Box::new(call(member(Expr::Ident(iterator.clone()), "next"), vec![])),
+	});
+	let next_step = Expr::Paren(ParenExpr {
+		span: DUMMY_SP,
+		expr: Box::new(assign(ident_target(&step), next)),
+	});
+	let test = assign(
+		ident_target(&abrupt),
+		Expr::Unary(UnaryExpr {
+			span: DUMMY_SP,
+			op: UnaryOp::Bang,
+			arg: Box::new(Expr::Member(MemberExpr {
+				span: DUMMY_SP,
+				obj: ExprOrSuper::Expr(Box::new(next_step)),
+				prop: Box::new(Expr::Ident(quote_ident!("done"))),
+				computed: false,
+			})),
+		}),
+	);
+	let mut for_stmt = Stmt::For(ForStmt {
+		span: for_of.span,
+		init: Some(VarDeclOrExpr::VarDecl(var_decl(VarDeclKind::Var, vec![
+			(iterator.clone(), Some(call(async_iterator_helper(), vec![*for_of.right]))),
+			(step, None),
+		]))),
+		test: Some(Box::new(test)),
+		update: Some(Box::new(assign(ident_target(&abrupt), bool_lit(false)))),
+		body: Box::new(Stmt::Block(BlockStmt {
+			span: DUMMY_SP,
+			stmts: body,
+		})),
+	});
+	if let Some(label) = label {
+		for_stmt = Stmt::Labeled(LabeledStmt {
+			span: DUMMY_SP,
+			label,
+			body: Box::new(for_stmt),
+		});
+	}
+
+	// match: catch (err) { _didIteratorError = true; _iteratorError = err; }
+	let handler = CatchClause {
+		span: DUMMY_SP,
+		param: Some(Pat::Ident(err.clone().into())),
+		body: block(vec![
+			expr_stmt(assign(ident_target(&did_error), bool_lit(true))),
+			expr_stmt(assign(ident_target(&error), Expr::Ident(err))),
+		]),
+	};
+	// match: if (_iteratorAbruptCompletion && _iterator.return != null) await _iterator.return()
+	let close = Stmt::If(IfStmt {
+		span: DUMMY_SP,
+		test: Box::new(Expr::Bin(BinExpr {
+			span: DUMMY_SP,
+			op: BinaryOp::LogicalAnd,
+			left: Box::new(Expr::Ident(abrupt.clone())),
+			right: Box::new(Expr::Bin(BinExpr {
+				span: DUMMY_SP,
+				op: BinaryOp::NotEq,
+				left: Box::new(member(Expr::Ident(iterator.clone()), "return")),
+				right: Box::new(Expr::Lit(Lit::Null(Null { span: DUMMY_SP }))),
+			})),
+		})),
+		cons: Box::new(Stmt::Block(block(vec![expr_stmt(Expr::Await(AwaitExpr {
+			span: DUMMY_SP,
+			arg: Box::new(call(member(Expr::Ident(iterator), "return"), vec![])),
+		}))]))),
+		alt: None,
+	});
+	// match: if (_didIteratorError) throw _iteratorError
+	let rethrow = Stmt::If(IfStmt {
+		span: DUMMY_SP,
+		test: Box::new(Expr::Ident(did_error.clone())),
+		cons: Box::new(Stmt::Block(block(vec![Stmt::Throw(ThrowStmt {
+			span: DUMMY_SP,
+			arg: Box::new(Expr::Ident(error.clone())),
+		})]))),
+		alt: None,
+	});
+	let finalizer = block(vec![Stmt::Try(TryStmt {
+		span: DUMMY_SP,
+		block: block(vec![close]),
+		handler: None,
+		finalizer: Some(block(vec![rethrow])),
+	})]);
+
+	Stmt::Block(block(vec![
+		Stmt::Decl(Decl::Var(var_decl(VarDeclKind::Var, vec![
+			(abrupt, Some(bool_lit(false))),
+			(did_error, Some(bool_lit(false))),
+			(error, None),
+		]))),
+		Stmt::Try(TryStmt {
+			span: for_of.span,
+			block: block(vec![for_stmt]),
+			handler: Some(handler),
+			finalizer: Some(finalizer),
+		}),
+	]))
+}
+
+/// get the `_asyncIterator` helper, it's injected in the module or referenced from the shared
+/// helpers like the helpers of the swc passes.
+fn async_iterator_helper() -> Expr {
+	HELPERS.with(|helpers| {
+		helpers.async_iterator();
+		let span = DUMMY_SP.apply_mark(helpers.mark());
+		if helpers.external() {
+			Expr::Member(MemberExpr {
+				span: DUMMY_SP,
+				obj: ExprOrSuper::Expr(Box::new(Expr::Ident(quote_ident!(span, "swcHelpers")))),
+				prop: Box::new(Expr::Ident(quote_ident!("asyncIterator"))),
+				computed: false,
+			})
+		} else {
+			Expr::Ident(quote_ident!(span, "_asyncIterator"))
+		}
+	})
+}
+
+fn ident_target(id: &Ident) -> PatOrExpr {
+	PatOrExpr::Pat(Box::new(Pat::Ident(id.clone().into())))
+}
+
+fn bool_lit(value: bool) -> Expr {
+	Expr::Lit(Lit::Bool(Bool {
+		span: DUMMY_SP,
+		value,
+	}))
+}
+
+/// replace the runtime import added by the `regenerator` pass, the imports of the module are
+/// marked before the pass to be told apart. The `regeneratorRuntime` references are kept as the
+/// global references, or bound to the inlined runtime:
+/// ```js
+/// const regeneratorRuntime = (() => {
+///   var module = { exports: {} }, exports = module.exports;
+///   // the inlined runtime
+///   return module.exports;
+/// })();
+/// ```
+struct RuntimeImportFold<F: Fold> {
+	regenerator: F,
+	mark: Mark,
+	inlined_runtime: Option<Vec<Stmt>>,
+}
+
+impl<F: Fold> Fold for RuntimeImportFold<F> {
+	noop_fold_type!();
+
+	fn fold_module(&mut self, mut module: Module) -> Module {
+		for item in module.body.iter_mut() {
+			if let ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl { span, .. })) = item {
+				*span = span.apply_mark(self.mark);
+			}
+		}
+		let mut module = self.regenerator.fold_module(module);
+		let mut body = Vec::with_capacity(module.body.len());
+		for item in module.body {
+			match item {
+				ModuleItem::ModuleDecl(ModuleDecl::Import(mut decl)) => {
+					let mut ctxt = decl.span.ctxt;
+					if ctxt.remove_mark() == self.mark {
+						decl.span.ctxt = ctxt;
+						body.push(ModuleItem::ModuleDecl(ModuleDecl::Import(decl)));
+					} else if let Some(stmts) = &self.inlined_runtime {
+						// match: import regeneratorRuntime from "regenerator-runtime"
+						for specifier in decl.specifiers {
+							if let ImportSpecifier::Default(specifier) = specifier {
+								let decl = inlined_runtime_decl(specifier.local, stmts.clone());
+								body.push(ModuleItem::Stmt(decl));
+							}
+						}
+					}
+				}
+				_ => body.push(item),
+			}
+		}
+		module.body = body;
+		module
+	}
+}
+
+// match: const regeneratorRuntime = (() => { ...; return module.exports })()
+fn inlined_runtime_decl(local: Ident, mut stmts: Vec<Stmt>) -> Stmt {
+	let exports = member(Expr::Ident(quote_ident!("module")), "exports");
+	let module_obj = Expr::Object(ObjectLit {
+		span: DUMMY_SP,
+		props: vec![PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+			key: PropName::Ident(quote_ident!("exports")),
+			value: Box::new(Expr::Object(ObjectLit {
+				span: DUMMY_SP,
+				props: vec![],
+			})),
+		})))],
+	});
+	stmts.insert(
+		0,
+		Stmt::Decl(Decl::Var(var_decl(
+			VarDeclKind::Var,
+			vec![
+				(quote_ident!("module"), Some(module_obj)),
+				(quote_ident!("exports"), Some(exports.clone())),
+			],
+		))),
+	);
+	stmts.push(Stmt::Return(ReturnStmt {
+		span: DUMMY_SP,
+		arg: Some(Box::new(exports)),
+	}));
+	let init = call(
+		Expr::Paren(ParenExpr {
+			span: DUMMY_SP,
+			expr: Box::new(Expr::Arrow(ArrowExpr {
+				span: DUMMY_SP,
+				params: vec![],
+				body: BlockStmtOrExpr::BlockStmt(block(stmts)),
+				is_async: false,
+				is_generator: false,
+				type_params: None,
+				return_type: None,
+			})),
+		}),
+		vec![],
+	);
+	Stmt::Decl(Decl::Var(var_decl(VarDeclKind::Const, vec![(local, Some(init))])))
+}
+
+struct IdentCollector {
+	names: HashSet<JsWord>,
+}
+
+impl Visit for IdentCollector {
+	fn visit_ident(&mut self, n: &Ident, _: &dyn Node) {
+		self.names.insert(n.sym.clone());
+	}
+}
+
+struct UnsupportedSyntaxFinder {
+	target: JscTarget,
+	errors: Vec<(String, Span)>,
+}
+
+impl Visit for UnsupportedSyntaxFinder {
+	// match: 123n
+	fn visit_big_int(&mut self, n: &BigInt, _: &dyn Node) {
+		if self.target < JscTarget::Es2020 {
+			self.errors.push((
+				format!(
+					"BigInt literals are not supported by the {} target",
+					target_name(self.target)
+				),
+				n.span,
+			));
+		}
+	}
+}
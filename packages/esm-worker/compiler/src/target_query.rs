@@ -0,0 +1,30 @@
+use std::collections::HashMap;
+use swc_ecma_ast::*;
+use swc_ecma_visit::{noop_fold_type, Fold};
+
+/// Rewrite the resolved remote specifiers to request the builds of the target, the `urls` map
+/// the specifiers of the dependencies to the specifiers with the target query, like
+/// `https://esm.sh/react` => `https://esm.sh/react?target=es2017`.
+pub fn target_query_fold(urls: HashMap<String, String>) -> impl Fold {
+	TargetQueryFold { urls }
+}
+
+struct TargetQueryFold {
+	urls: HashMap<String, String>,
+}
+
+impl Fold for TargetQueryFold {
+	noop_fold_type!();
+
+	fn fold_str(&mut self, str: Str) -> Str {
+		match self.urls.get(str.value.as_ref()) {
+			Some(url) => Str {
+				value: url.as_str().into(),
+				has_escape: false,
+				kind: StrKind::Synthesized,
+				..str
+			},
+			None => str,
+		}
+	}
+}
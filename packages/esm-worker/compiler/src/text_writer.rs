@@ -0,0 +1,200 @@
+use serde::Deserialize;
+use swc_common::Span;
+use swc_ecmascript::{codegen::text_writer::WriteJs, parser::JscTarget};
+
+type Result = std::io::Result<()>;
+
+/// The indentation of the emitted code.
+#[derive(Debug, Clone, Copy, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum Indent {
+	Tabs,
+	Spaces(u8),
+}
+
+impl Default for Indent {
+	fn default() -> Self {
+		Indent::Spaces(4)
+	}
+}
+
+impl Indent {
+	fn as_string(&self) -> String {
+		match self {
+			Indent::Tabs => "\t".into(),
+			Indent::Spaces(n) => " ".repeat(*n as usize),
+		}
+	}
+}
+
+/// The line ending of the emitted code.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum Newline {
+	#[default]
+	Lf,
+	CrLf,
+}
+
+impl Newline {
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			Newline::Lf => "\n",
+			Newline::CrLf => "\r\n",
+		}
+	}
+}
+
+/// A writer wraps the `JsWriter` of swc to write the indentation by the `Indent` option,
+/// the inner writer is kept at the zero indentation level.
+///
+/// If the `line_width` is set, the lines longer than the width are wrapped after the commas,
+/// which is safe for the automatic semicolon insertion, the wrapped lines are indented one
+/// more level. The lines without commas are kept as they are.
+pub struct IndentWriter<W: WriteJs> {
+	inner: W,
+	indent: String,
+	level: usize,
+	line_start: bool,
+	line_width: Option<usize>,
+	column: usize,
+	wrapped: bool,
+}
+
+impl<W: WriteJs> IndentWriter<W> {
+	pub fn new(inner: W, indent: Indent, line_width: Option<usize>) -> Self {
+		IndentWriter {
+			inner,
+			indent: indent.as_string(),
+			level: 0,
+			line_start: true,
+			line_width,
+			column: 0,
+			wrapped: false,
+		}
+	}
+
+	fn write_indent(&mut self, s: &str) -> Result {
+		if self.line_start && !s.is_empty() {
+			self.line_start = false;
+			let level = if self.wrapped { self.level + 1 } else { self.level };
+			if level > 0 {
+				let indent = self.indent.repeat(level);
+				self.inner.write_str(indent.as_str())?;
+				self.column = indent.chars().count();
+			}
+		}
+		match s.rfind('\n') {
+			Some(i) => self.column = s[i + 1..].chars().count(),
+			None => self.column += s.chars().count(),
+		}
+		Ok(())
+	}
+
+	fn wrap_line(&mut self) -> Result {
+		if let Some(width) = self.line_width {
+			if self.column >= width {
+				self.inner.write_line()?;
+				self.line_start = true;
+				self.wrapped = true;
+				self.column = 0;
+			}
+		}
+		Ok(())
+	}
+}
+
+impl<W: WriteJs> WriteJs for IndentWriter<W> {
+	fn target(&self) -> JscTarget {
+		self.inner.target()
+	}
+
+	fn increase_indent(&mut self) -> Result {
+		self.level += 1;
+		Ok(())
+	}
+
+	fn decrease_indent(&mut self) -> Result {
+		self.level = self.level.saturating_sub(1);
+		Ok(())
+	}
+
+	fn write_semi(&mut self, span: Option<Span>) -> Result {
+		self.write_indent(";")?;
+		self.inner.write_semi(span)
+	}
+
+	fn write_space(&mut self) -> Result {
+		// the space after the comma of a wrapped line
+		if self.wrapped && self.line_start {
+			return Ok(());
+		}
+		self.write_indent(" ")?;
+		self.inner.write_space()
+	}
+
+	fn write_keyword(&mut self, span: Option<Span>, s: &'static str) -> Result {
+		self.write_indent(s)?;
+		self.inner.write_keyword(span, s)
+	}
+
+	fn write_operator(&mut self, span: Option<Span>, s: &str) -> Result {
+		self.write_indent(s)?;
+		self.inner.write_operator(span, s)
+	}
+
+	fn write_param(&mut self, s: &str) -> Result {
+		self.write_indent(s)?;
+		self.inner.write_param(s)
+	}
+
+	fn write_property(&mut self, s: &str) -> Result {
+		self.write_indent(s)?;
+		self.inner.write_property(s)
+	}
+
+	fn write_line(&mut self) -> Result {
+		self.line_start = true;
+		self.wrapped = false;
+		self.column = 0;
+		self.inner.write_line()
+	}
+
+	fn write_lit(&mut self, span: Span, s: &str) -> Result {
+		self.write_indent(s)?;
+		self.inner.write_lit(span, s)
+	}
+
+	fn write_comment(&mut self, span: Span, s: &str) -> Result {
+		self.write_indent(s)?;
+		self.inner.write_comment(span, s)
+	}
+
+	fn write_str_lit(&mut self, span: Span, s: &str) -> Result {
+		self.write_indent(s)?;
+		self.inner.write_str_lit(span, s)
+	}
+
+	fn write_str(&mut self, s: &str) -> Result {
+		self.write_indent(s)?;
+		self.inner.write_str(s)
+	}
+
+	fn write_symbol(&mut self, span: Span, s: &str) -> Result {
+		self.write_indent(s)?;
+		self.inner.write_symbol(span, s)
+	}
+
+	fn write_punct(&mut self, span: Option<Span>, s: &'static str) -> Result {
+		self.write_indent(s)?;
+		self.inner.write_punct(span, s)?;
+		if s == "," {
+			self.wrap_line()?;
+		}
+		Ok(())
+	}
+
+	fn care_about_srcmap(&self) -> bool {
+		self.inner.care_about_srcmap()
+	}
+}
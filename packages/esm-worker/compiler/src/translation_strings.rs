@@ -0,0 +1,55 @@
+use crate::ast_utils::get_callee_name;
+use swc_common::{Span, DUMMY_SP};
+use swc_ecma_ast::*;
+use swc_ecma_visit::{Node, Visit, VisitWith};
+
+/// Find the string literals passed to the translation functions, like `t("hello")` and
+/// `i18n.t("hello")` with `t` and `i18n.t` in the `functions`. Only the first argument is
+/// extracted, it can be a string literal or a template literal without interpolations.
+pub fn find_translation_strings(module: &Module, functions: &[String]) -> Vec<(String, Span)> {
+	let mut collector = TranslationStringCollector {
+		functions,
+		strings: vec![],
+	};
+	module.visit_with(&Invalid { span: DUMMY_SP } as _, &mut collector);
+	collector.strings
+}
+
+struct TranslationStringCollector<'a> {
+	functions: &'a [String],
+	strings: Vec<(String, Span)>,
+}
+
+impl Visit for TranslationStringCollector<'_> {
+	fn visit_call_expr(&mut self, n: &CallExpr, _: &dyn Node) {
+		if let ExprOrSuper::Expr(callee) = &n.callee {
+			let is_translation = match get_callee_name(callee) {
+				Some(name) => self.functions.contains(&name),
+				None => false,
+			};
+			if let (true, Some(ExprOrSpread { spread: None, expr })) = (is_translation, n.args.first()) {
+				match expr.as_ref() {
+					// match: t("hello")
+					Expr::Lit(Lit::Str(Str { value, span, .. })) => {
+						self.strings.push((value.as_ref().into(), *span));
+					}
+					// match: t(`hello`)
+					Expr::Tpl(Tpl {
+						exprs, quasis, span, ..
+					}) if exprs.is_empty() => {
+						let key: String = quasis
+							.iter()
+							.map(|quasi| match &quasi.cooked {
+								Some(cooked) => cooked.value.as_ref(),
+								None => quasi.raw.value.as_ref(),
+							})
+							.collect();
+						self.strings.push((key, *span));
+					}
+					_ => {}
+				}
+			}
+		}
+		n.visit_children_with(self);
+	}
+}
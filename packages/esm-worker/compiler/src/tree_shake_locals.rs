@@ -0,0 +1,151 @@
+use std::collections::HashSet;
+use swc_atoms::JsWord;
+use swc_common::DUMMY_SP;
+use swc_ecma_ast::*;
+use swc_ecma_utils::{find_ids, ExprExt};
+use swc_ecma_visit::{noop_fold_type, Fold, Node, Visit, VisitWith};
+
+/// Remove the top-level declarations of the ES module that aren't referenced by the exports or
+/// by the retained code, the references are followed transitively:
+/// ```js
+/// function helper() {}      // removed
+/// function used() {}        // kept, referenced by `run`
+/// export function run() { used() }
+/// ```
+/// The declarations that may have side effects are kept, like `const x = init()` and the classes
+/// with the static fields. The names are matched by the symbols, so a shadowed name keeps its
+/// top-level declaration. The scripts are kept since their declarations are globals.
+pub fn tree_shake_locals_fold() -> impl Fold {
+	TreeShakeLocalsFold {}
+}
+
+struct TreeShakeLocalsFold {}
+
+impl Fold for TreeShakeLocalsFold {
+	noop_fold_type!();
+
+	fn fold_module(&mut self, mut module: Module) -> Module {
+		if !module.body.iter().any(|item| matches!(item, ModuleItem::ModuleDecl(_))) {
+			return module;
+		}
+		// the names declared by the removable declarations of the items
+		let declared: Vec<Option<Vec<JsWord>>> =
+			module.body.iter().map(get_removable_names).collect();
+		let refs: Vec<HashSet<JsWord>> = module.body.iter().map(collect_refs).collect();
+		let mut retained: Vec<bool> = declared.iter().map(|names| names.is_none()).collect();
+		let mut reachable: HashSet<JsWord> = HashSet::new();
+		let mut queue: Vec<usize> = (0..module.body.len()).filter(|i| retained[*i]).collect();
+		while let Some(i) = queue.pop() {
+			reachable.extend(refs[i].iter().cloned());
+			for (j, names) in declared.iter().enumerate() {
+				if let (false, Some(names)) = (retained[j], names) {
+					if names.iter().any(|name| reachable.contains(name)) {
+						retained[j] = true;
+						queue.push(j);
+					}
+				}
+			}
+		}
+		let mut retained = retained.into_iter();
+		module.body.retain(|_| retained.next().unwrap_or(true));
+		module
+	}
+}
+
+/// get the names of the top-level declaration if it can be removed, the declarations with side
+/// effects and the other items return `None`.
+fn get_removable_names(item: &ModuleItem) -> Option<Vec<JsWord>> {
+	match item {
+		// match: function helper() {}
+		ModuleItem::Stmt(Stmt::Decl(Decl::Fn(FnDecl { ident, .. }))) => {
+			Some(vec![ident.sym.clone()])
+		}
+		// match: class Helper {}
+		ModuleItem::Stmt(Stmt::Decl(Decl::Class(ClassDecl { ident, class, .. }))) => {
+			if class_may_have_side_effects(class) {
+				None
+			} else {
+				Some(vec![ident.sym.clone()])
+			}
+		}
+		// match: const a = 1, b = () => {}
+		ModuleItem::Stmt(Stmt::Decl(Decl::Var(VarDecl { decls, .. }))) => {
+			let is_pure = decls.iter().all(|decl| {
+				matches!(decl.name, Pat::Ident(_))
+					&& decl.init.as_ref().map_or(true, |init| !init.may_have_side_effects())
+			});
+			if is_pure {
+				Some(find_ids::<_, Ident>(decls).into_iter().map(|id| id.sym).collect())
+			} else {
+				None
+			}
+		}
+		_ => None,
+	}
+}
+
+fn class_may_have_side_effects(class: &Class) -> bool {
+	if !class.decorators.is_empty() {
+		return true;
+	}
+	if let Some(super_class) = &class.super_class {
+		if !matches!(super_class.as_ref(), Expr::Ident(_)) {
+			return true;
+		}
+	}
+	class.body.iter().any(|member| match member {
+		ClassMember::Constructor(_) | ClassMember::Empty(_) | ClassMember::TsIndexSignature(_) => {
+			false
+		}
+		ClassMember::Method(ClassMethod { key, .. }) => match key {
+			PropName::Computed(ComputedPropName { expr, .. }) => expr.may_have_side_effects(),
+			_ => false,
+		},
+		ClassMember::PrivateMethod(_) => false,
+		ClassMember::ClassProp(ClassProp {
+			key,
+			value,
+			is_static,
+			computed,
+			decorators,
+			..
+		}) => {
+			!decorators.is_empty()
+				|| (*computed && key.may_have_side_effects())
+				|| (*is_static && static_value_may_have_side_effects(value))
+		}
+		ClassMember::PrivateProp(PrivateProp {
+			value,
+			is_static,
+			decorators,
+			..
+		}) => {
+			!decorators.is_empty()
+				|| (*is_static && static_value_may_have_side_effects(value))
+		}
+		ClassMember::StaticBlock(_) => true,
+	})
+}
+
+fn static_value_may_have_side_effects(value: &Option<Box<Expr>>) -> bool {
+	value.as_ref().map_or(false, |value| value.may_have_side_effects())
+}
+
+/// collect the referenced names of the item, the property names are included.
+fn collect_refs(item: &ModuleItem) -> HashSet<JsWord> {
+	let mut collector = RefCollector {
+		refs: HashSet::new(),
+	};
+	item.visit_with(&Invalid { span: DUMMY_SP } as _, &mut collector);
+	collector.refs
+}
+
+struct RefCollector {
+	refs: HashSet<JsWord>,
+}
+
+impl Visit for RefCollector {
+	fn visit_ident(&mut self, n: &Ident, _: &dyn Node) {
+		self.refs.insert(n.sym.clone());
+	}
+}
@@ -0,0 +1,138 @@
+use swc_common::DUMMY_SP;
+use swc_ecma_ast::*;
+use swc_ecma_utils::{find_ids, quote_ident};
+use swc_ecma_visit::{noop_fold_type, Fold};
+
+/// Strip all the runtime code of the module but keep the export shape, each exported binding
+/// is declared as `export const name = undefined`. The re-exports(`export * from "./mod.ts"`)
+/// are kept, the type declarations are left to the `strip` pass.
+pub fn types_only_fold() -> impl Fold {
+	TypesOnlyFold {}
+}
+
+struct TypesOnlyFold {}
+
+impl Fold for TypesOnlyFold {
+	noop_fold_type!();
+
+	fn fold_module_items(&mut self, items: Vec<ModuleItem>) -> Vec<ModuleItem> {
+		// the local interfaces and type aliases, for `export { T }`
+		let local_types: Vec<String> = items
+			.iter()
+			.filter_map(|item| match item {
+				ModuleItem::Stmt(Stmt::Decl(Decl::TsInterface(TsInterfaceDecl { id, .. })))
+				| ModuleItem::Stmt(Stmt::Decl(Decl::TsTypeAlias(TsTypeAliasDecl { id, .. }))) => {
+					Some(id.sym.as_ref().into())
+				}
+				_ => None,
+			})
+			.collect();
+		let mut output: Vec<ModuleItem> = vec![];
+		for item in items {
+			match item {
+				ModuleItem::ModuleDecl(decl) => match decl {
+					ModuleDecl::ExportDecl(ExportDecl { decl, .. }) => match decl {
+						// match: export const foo = expensiveCall()
+						Decl::Var(VarDecl { decls, .. }) => {
+							let ids: Vec<Ident> = find_ids(&decls);
+							for id in ids {
+								output.push(create_export_undefined(id));
+							}
+						}
+						Decl::Fn(FnDecl { ident, .. }) => output.push(create_export_undefined(ident)),
+						Decl::Class(ClassDecl { ident, .. }) => output.push(create_export_undefined(ident)),
+						Decl::TsEnum(TsEnumDecl { id, .. }) => output.push(create_export_undefined(id)),
+						// match: export namespace foo {}
+						Decl::TsModule(TsModuleDecl {
+							id: TsModuleName::Ident(id),
+							..
+						}) => output.push(create_export_undefined(id)),
+						decl => output.push(ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl {
+							span: DUMMY_SP,
+							decl,
+						}))),
+					},
+					// match: export default foo
+					ModuleDecl::ExportDefaultDecl(ExportDefaultDecl {
+						decl: DefaultDecl::TsInterfaceDecl(_),
+						..
+					}) => {}
+					ModuleDecl::ExportDefaultDecl(_) | ModuleDecl::ExportDefaultExpr(_) => {
+						output.push(ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultExpr(
+							ExportDefaultExpr {
+								span: DUMMY_SP,
+								expr: Box::new(Expr::Ident(quote_ident!("undefined"))),
+							},
+						)))
+					}
+					// match: export { foo, bar as default }
+					ModuleDecl::ExportNamed(NamedExport {
+						src: None,
+						type_only: false,
+						specifiers,
+						..
+					}) => {
+						for specifier in specifiers {
+							if let ExportSpecifier::Named(ExportNamedSpecifier {
+								orig,
+								exported,
+								is_type_only: false,
+								..
+							}) = specifier
+							{
+								if local_types.iter().any(|t| t == orig.sym.as_ref()) {
+									continue;
+								}
+								let name = exported.unwrap_or(orig);
+								if name.sym.as_ref() == "default" {
+									output.push(ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultExpr(
+										ExportDefaultExpr {
+											span: DUMMY_SP,
+											expr: Box::new(Expr::Ident(quote_ident!("undefined"))),
+										},
+									)))
+								} else {
+									output.push(create_export_undefined(quote_ident!(name.sym.as_ref())));
+								}
+							}
+						}
+					}
+					// match: export { foo } from "./foo.ts"
+					// match: export * from "./foo.ts"
+					ModuleDecl::ExportNamed(NamedExport { src: Some(_), .. }) | ModuleDecl::ExportAll(_) => {
+						output.push(ModuleItem::ModuleDecl(decl))
+					}
+					_ => {}
+				},
+				// the type declarations are removed by the `strip` pass
+				ModuleItem::Stmt(Stmt::Decl(decl @ Decl::TsInterface(_)))
+				| ModuleItem::Stmt(Stmt::Decl(decl @ Decl::TsTypeAlias(_))) => {
+					output.push(ModuleItem::Stmt(Stmt::Decl(decl)))
+				}
+				_ => {}
+			}
+		}
+		output
+	}
+}
+
+// match: export const foo = undefined
+fn create_export_undefined(id: Ident) -> ModuleItem {
+	ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl {
+		span: DUMMY_SP,
+		decl: Decl::Var(VarDecl {
+			span: DUMMY_SP,
+			kind: VarDeclKind::Const,
+			declare: false,
+			decls: vec![VarDeclarator {
+				span: DUMMY_SP,
+				name: Pat::Ident(BindingIdent {
+					id: quote_ident!(id.sym.as_ref()),
+					type_ann: None,
+				}),
+				init: Some(Box::new(Expr::Ident(quote_ident!("undefined")))),
+				definite: false,
+			}],
+		}),
+	}))
+}
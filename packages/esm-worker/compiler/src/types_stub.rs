@@ -0,0 +1,36 @@
+/// Generate an ES module stub that re-exports the value names and the type names from the `src`,
+/// the `names` and `types` are the result of `SWC::parse_export_names_with_types`.
+/// A star export(`{https://esm.sh/react}`) in the names is re-exported by `export * from "src"`.
+pub fn generate_types_stub(src: &str, names: &[String], types: &[String]) -> String {
+	let src = quote(src);
+	let mut lines: Vec<String> = vec![];
+	if names.iter().any(|name| is_star_export(name)) {
+		lines.push(format!("export * from {};", src));
+	}
+	let values: Vec<&str> = names
+		.iter()
+		.filter(|name| !is_star_export(name))
+		.map(|name| name.as_str())
+		.collect();
+	if !values.is_empty() {
+		lines.push(format!("export {{ {} }} from {};", values.join(", "), src));
+	}
+	if !types.is_empty() {
+		lines.push(format!("export type {{ {} }} from {};", types.join(", "), src));
+	}
+	if lines.is_empty() {
+		lines.push("export {};".into());
+	}
+	let mut stub = lines.join("\n");
+	stub.push('\n');
+	stub
+}
+
+// match: {https://esm.sh/react}
+fn is_star_export(name: &str) -> bool {
+	name.starts_with('{') && name.ends_with('}')
+}
+
+fn quote(s: &str) -> String {
+	format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
@@ -0,0 +1,85 @@
+use indexmap::IndexSet;
+use swc_common::Span;
+use swc_ecma_ast::*;
+use swc_ecma_visit::{Node, Visit, VisitWith};
+
+/// Find the named/default/namespace import bindings that are never referenced in the module.
+/// An identifier used in JSX or in a type position counts as a reference, and the root of the
+/// JSX factories(`React` of `React.createElement`) is referenced by any JSX element.
+pub fn find_unused_imports(module: &Module, jsx_factories: &[String]) -> Vec<(String, Span)> {
+	let mut imports: Vec<(String, Span)> = vec![];
+	for item in &module.body {
+		if let ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl { specifiers, .. })) = item {
+			for specifier in specifiers {
+				let local = match specifier {
+					ImportSpecifier::Named(ImportNamedSpecifier { local, .. }) => local,
+					ImportSpecifier::Default(ImportDefaultSpecifier { local, .. }) => local,
+					ImportSpecifier::Namespace(ImportStarAsSpecifier { local, .. }) => local,
+				};
+				imports.push((local.sym.as_ref().into(), local.span));
+			}
+		}
+	}
+	if imports.is_empty() {
+		return imports;
+	}
+
+	let mut collector = RefCollector {
+		refs: IndexSet::new(),
+		has_jsx: false,
+	};
+	module.visit_with(&Invalid { span: Span::default() } as _, &mut collector);
+	if collector.has_jsx {
+		for factory in jsx_factories {
+			if let Some(root) = factory.split('.').next() {
+				collector.refs.insert(root.into());
+			}
+		}
+	}
+	imports
+		.into_iter()
+		.filter(|(name, _)| !collector.refs.contains(name))
+		.collect()
+}
+
+struct RefCollector {
+	refs: IndexSet<String>,
+	has_jsx: bool,
+}
+
+impl Visit for RefCollector {
+	// the bindings of import declarations are not references
+	fn visit_import_decl(&mut self, _: &ImportDecl, _: &dyn Node) {}
+
+	fn visit_ident(&mut self, ident: &Ident, _: &dyn Node) {
+		self.refs.insert(ident.sym.as_ref().into());
+	}
+
+	// match: obj.prop, only the computed property is a reference
+	fn visit_member_expr(&mut self, expr: &MemberExpr, _: &dyn Node) {
+		expr.obj.visit_with(expr as _, self);
+		if expr.computed {
+			expr.prop.visit_with(expr as _, self);
+		}
+	}
+
+	// match: { key: value }, only the computed key is a reference
+	fn visit_prop_name(&mut self, name: &PropName, _: &dyn Node) {
+		if let PropName::Computed(computed) = name {
+			computed.visit_with(name as _, self);
+		}
+	}
+
+	// match: <div className="foo" />
+	fn visit_jsx_attr_name(&mut self, _: &JSXAttrName, _: &dyn Node) {}
+
+	fn visit_jsx_element(&mut self, el: &JSXElement, _: &dyn Node) {
+		self.has_jsx = true;
+		el.visit_children_with(self);
+	}
+
+	fn visit_jsx_fragment(&mut self, frag: &JSXFragment, _: &dyn Node) {
+		self.has_jsx = true;
+		frag.visit_children_with(self);
+	}
+}
@@ -0,0 +1,316 @@
+use crate::ast_utils::{assign, block, expr_stmt, var_decl};
+use swc_common::{BytePos, DUMMY_SP};
+use swc_ecma_ast::*;
+use swc_ecma_utils::{find_ids, private_ident, quote_ident, StmtLike};
+use swc_ecma_visit::{noop_fold_type, Fold, FoldWith};
+
+/// Lower the `using` declarations of the explicit resource management, the statements after the
+/// declaration are wrapped in a `try` block disposing the resources in the reverse order:
+/// ```js
+/// const c = connect();
+/// try {
+///   ...
+/// } finally {
+///   if (c != null) await c[Symbol.asyncDispose]();
+/// }
+/// ```
+/// The `using` keyword is replaced with `const` by the parser, the `decls` are the positions of
+/// the declarations and if they are `await using`. In the module top level, the declarations
+/// after the `using` are kept in the module scope with their kinds, the statements between them
+/// are wrapped in the `try` blocks disposing the resources and rethrowing the errors, and the
+/// initializers are evaluated in the blocks:
+/// `export const x = f()` => `let _x; try { _x = f() } catch ...; export const x = _x`.
+pub fn using_decl_fold(decls: Vec<(BytePos, bool)>) -> impl Fold {
+	UsingDeclFold { decls }
+}
+
+struct UsingDeclFold {
+	decls: Vec<(BytePos, bool)>,
+}
+
+/// The resources of a `using` declaration to dispose.
+struct Resources {
+	ids: Vec<Ident>,
+	is_await: bool,
+}
+
+impl UsingDeclFold {
+	/// get the resources of the `using` declaration.
+	fn get_using(&self, stmt: &Stmt) -> Option<Resources> {
+		match stmt {
+			Stmt::Decl(Decl::Var(var)) => Some(Resources {
+				ids: find_ids(&var.decls),
+				is_await: self.get_using_var(var)?,
+			}),
+			_ => None,
+		}
+	}
+
+	fn lower_stmts(&self, mut stmts: Vec<Stmt>) -> Vec<Stmt> {
+		let (index, resources) = match stmts
+			.iter()
+			.enumerate()
+			.find_map(|(i, stmt)| self.get_using(stmt).map(|resources| (i, resources)))
+		{
+			Some(using) => using,
+			None => return stmts,
+		};
+		// the function declarations are kept in the block to be hoisted, like the declarations of
+		// the module scope
+		let (fns, rest): (Vec<Stmt>, Vec<Stmt>) = stmts
+			.split_off(index + 1)
+			.into_iter()
+			.partition(|stmt| matches!(stmt, Stmt::Decl(Decl::Fn(_))));
+		stmts.extend(fns);
+		stmts.push(create_try_finally_stmt(self.lower_stmts(rest), &resources));
+		stmts
+	}
+
+	fn lower_module_items(&self, mut items: Vec<ModuleItem>) -> Vec<ModuleItem> {
+		let (index, resources) = match items.iter().enumerate().find_map(|(i, item)| match item {
+			ModuleItem::Stmt(stmt) => self.get_using(stmt).map(|resources| (i, resources)),
+			_ => None,
+		}) {
+			Some(using) => using,
+			None => return items,
+		};
+		let rest = self.lower_module_items(items.split_off(index + 1));
+		// the statements before the next declaration of the module scope
+		let mut stmts: Vec<Stmt> = vec![];
+		for item in rest {
+			match item {
+				ModuleItem::Stmt(Stmt::Decl(Decl::Var(var))) => {
+					for var in split_var_decl(var) {
+						flush_stmts(&mut items, &mut stmts, &resources);
+						let var = eval_initializers(&mut items, var, &resources);
+						items.push(ModuleItem::Stmt(Stmt::Decl(Decl::Var(var))));
+					}
+				}
+				ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl {
+					span,
+					decl: Decl::Var(var),
+				})) => {
+					for var in split_var_decl(var) {
+						flush_stmts(&mut items, &mut stmts, &resources);
+						let decl = Decl::Var(eval_initializers(&mut items, var, &resources));
+						items.push(ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl {
+							span,
+							decl,
+						})));
+					}
+				}
+				// match: export default expr => let _default; try { _default = expr } ...
+				ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultExpr(ExportDefaultExpr {
+					span,
+					expr,
+				})) => {
+					flush_stmts(&mut items, &mut stmts, &resources);
+					let expr = eval_expr(&mut items, "_default", *expr, &resources);
+					items.push(ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultExpr(
+						ExportDefaultExpr {
+							span,
+							expr: Box::new(expr),
+						},
+					)));
+				}
+				// the other declarations, the imports and the exports lists are kept in the
+				// module scope
+				ModuleItem::Stmt(Stmt::Decl(_)) | ModuleItem::ModuleDecl(_) => {
+					flush_stmts(&mut items, &mut stmts, &resources);
+					items.push(item);
+				}
+				ModuleItem::Stmt(stmt) => stmts.push(stmt),
+			}
+		}
+		if stmts.is_empty() {
+			items.extend(create_dispose_stmts(&resources).into_iter().map(ModuleItem::Stmt));
+		} else {
+			items.push(ModuleItem::Stmt(create_try_finally_stmt(stmts, &resources)));
+		}
+		items
+	}
+
+	/// split the `using` declarations of the multiple resources, the resources declared before a
+	/// throwing initializer are disposed:
+	/// `using a = f(), b = g()` => `using a = f(); using b = g()`.
+	fn split_using_decls<T: StmtLike>(&self, items: Vec<T>) -> Vec<T> {
+		let mut output = Vec::with_capacity(items.len());
+		for item in items {
+			match item.try_into_stmt() {
+				Ok(Stmt::Decl(Decl::Var(var))) if self.get_using_var(&var).is_some() => {
+					for var in split_var_decl(var) {
+						output.push(T::from_stmt(Stmt::Decl(Decl::Var(var))));
+					}
+				}
+				Ok(stmt) => output.push(T::from_stmt(stmt)),
+				Err(item) => output.push(item),
+			}
+		}
+		output
+	}
+
+	/// check if the variable declaration is a `using` declaration, returns if it's `await using`.
+	fn get_using_var(&self, var: &VarDecl) -> Option<bool> {
+		self.decls.iter().find(|(pos, _)| *pos == var.span.lo).map(|(_, is_await)| *is_await)
+	}
+}
+
+impl Fold for UsingDeclFold {
+	noop_fold_type!();
+
+	fn fold_module_items(&mut self, items: Vec<ModuleItem>) -> Vec<ModuleItem> {
+		let items = items.fold_children_with(self);
+		if self.decls.is_empty() {
+			return items;
+		}
+		self.lower_module_items(self.split_using_decls(items))
+	}
+
+	fn fold_stmts(&mut self, stmts: Vec<Stmt>) -> Vec<Stmt> {
+		let stmts = stmts.fold_children_with(self);
+		if self.decls.is_empty() {
+			return stmts;
+		}
+		self.lower_stmts(self.split_using_decls(stmts))
+	}
+}
+
+// match: try { ... } finally { if (c != null) await c[Symbol.asyncDispose]() }
+fn create_try_finally_stmt(stmts: Vec<Stmt>, resources: &Resources) -> Stmt {
+	Stmt::Try(TryStmt {
+		span: DUMMY_SP,
+		block: block(stmts),
+		handler: None,
+		finalizer: Some(block(create_dispose_stmts(resources))),
+	})
+}
+
+// match: try { ... } catch (_e) { if (c != null) await c[Symbol.asyncDispose](); throw _e }
+fn create_try_catch_stmt(stmts: Vec<Stmt>, resources: &Resources) -> Stmt {
+	let err = private_ident!("_e");
+	let mut handler = create_dispose_stmts(resources);
+	handler.push(Stmt::Throw(ThrowStmt {
+		span: DUMMY_SP,
+		arg: Box::new(Expr::Ident(err.clone())),
+	}));
+	Stmt::Try(TryStmt {
+		span: DUMMY_SP,
+		block: block(stmts),
+		handler: Some(CatchClause {
+			span: DUMMY_SP,
+			param: Some(Pat::Ident(err.into())),
+			body: block(handler),
+		}),
+		finalizer: None,
+	})
+}
+
+/// wrap the statements before a declaration of the module scope in a `try` block, the errors
+/// stop the module evaluation so the resources are disposed only once.
+fn flush_stmts(items: &mut Vec<ModuleItem>, stmts: &mut Vec<Stmt>, resources: &Resources) {
+	if !stmts.is_empty() {
+		items.push(ModuleItem::Stmt(create_try_catch_stmt(stmts.split_off(0), resources)));
+	}
+}
+
+/// evaluate the initializers of the declaration in the `try` blocks, the declaration is split by
+/// `split_var_decl` so the later initializers see the bindings.
+fn eval_initializers(items: &mut Vec<ModuleItem>, var: VarDecl, resources: &Resources) -> VarDecl {
+	let decls = var
+		.decls
+		.into_iter()
+		.map(|decl| {
+			let name = match &decl.name {
+				Pat::Ident(BindingIdent { id, .. }) => format!("_{}", id.sym),
+				_ => "_ref".into(),
+			};
+			let init = decl
+				.init
+				.map(|init| Box::new(eval_expr(items, name.as_str(), *init, resources)));
+			VarDeclarator { init, ..decl }
+		})
+		.collect();
+	VarDecl { decls, ..var }
+}
+
+/// evaluate the expression in a `try` block, returns the temporary variable of the value. The
+/// identifiers and the literals can't throw so they are returned unchanged.
+fn eval_expr(items: &mut Vec<ModuleItem>, name: &str, expr: Expr, resources: &Resources) -> Expr {
+	if matches!(expr, Expr::Ident(_) | Expr::Lit(_)) {
+		return expr;
+	}
+	let temp = private_ident!(name);
+	items.push(ModuleItem::Stmt(Stmt::Decl(Decl::Var(var_decl(
+		VarDeclKind::Let,
+		vec![(temp.clone(), None)],
+	)))));
+	let init = expr_stmt(assign(PatOrExpr::Pat(Box::new(Pat::Ident(temp.clone().into()))), expr));
+	items.push(ModuleItem::Stmt(create_try_catch_stmt(vec![init], resources)));
+	Expr::Ident(temp)
+}
+
+// match: const a = 1, b = a => const a = 1; const b = a
+fn split_var_decl(var: VarDecl) -> Vec<VarDecl> {
+	let VarDecl {
+		span,
+		kind,
+		declare,
+		decls,
+	} = var;
+	decls
+		.into_iter()
+		.map(|decl| VarDecl {
+			span,
+			kind,
+			declare,
+			decls: vec![decl],
+		})
+		.collect()
+}
+
+// match: if (c != null) await c[Symbol.asyncDispose]()
+fn create_dispose_stmts(resources: &Resources) -> Vec<Stmt> {
+	let method = if resources.is_await { "asyncDispose" } else { "dispose" };
+	resources
+		.ids
+		.iter()
+		.rev()
+		.map(|id| {
+			let call = Expr::Call(CallExpr {
+				span: DUMMY_SP,
+				callee: ExprOrSuper::Expr(Box::new(Expr::Member(MemberExpr {
+					span: DUMMY_SP,
+					obj: ExprOrSuper::Expr(Box::new(Expr::Ident(id.clone()))),
+					prop: Box::new(Expr::Member(MemberExpr {
+						span: DUMMY_SP,
+						obj: ExprOrSuper::Expr(Box::new(Expr::Ident(quote_ident!("Symbol")))),
+						prop: Box::new(Expr::Ident(quote_ident!(method))),
+						computed: false,
+					})),
+					computed: true,
+				}))),
+				args: vec![],
+				type_args: None,
+			});
+			let call = if resources.is_await {
+				Expr::Await(AwaitExpr {
+					span: DUMMY_SP,
+					arg: Box::new(call),
+				})
+			} else {
+				call
+			};
+			Stmt::If(IfStmt {
+				span: DUMMY_SP,
+				test: Box::new(Expr::Bin(BinExpr {
+					span: DUMMY_SP,
+					op: BinaryOp::NotEq,
+					left: Box::new(Expr::Ident(id.clone())),
+					right: Box::new(Expr::Lit(Lit::Null(Null { span: DUMMY_SP }))),
+				})),
+				cons: Box::new(expr_stmt(call)),
+				alt: None,
+			})
+		})
+		.collect()
+}